@@ -0,0 +1,315 @@
+# Interface text. Page copy lives in assets/content.ron.
+# Every other catalog in this folder must translate every message here, which
+# the tests in src/i18n.rs check; at runtime anything missing there falls
+# back to the English here.
+
+language-name = English
+app-name = Plistr
+
+## Navigation
+
+page-home = Home
+page-about = About Us
+page-timeline = Timeline
+page-deliverables = Deliverables
+page-contact = Contact
+page-focus = Focus
+page-meditate = Meditate
+page-library = Library
+page-social = Friends
+page-rooms = Study rooms
+nav-home-label = { app-name } home
+nav-shortcut = Shortcut: { $key }
+nav-previous = Previous: { $page }
+nav-next = Next: { $page }
+nav-sitemap = Sitemap
+nav-menu = Menu
+nav-more = More
+
+## Header menus
+
+menu-theme = Theme
+menu-theme-system = Match system
+menu-accent = Accent
+menu-accent-default = Theme default
+menu-text-size = Text size
+menu-text-size-hint = Ctrl +/- also work.
+menu-screen-reader = Read controls aloud
+menu-language = Language
+menu-language-system = Match system
+
+## Team
+
+team-photo = Photo of { $name }
+team-responsibilities = Responsibilities:
+
+## Timeline
+
+timeline-zoom-out = Zoom out
+timeline-zoom-in = Zoom in
+timeline-fit =
+    { $count ->
+        [one] Show the milestone
+       *[other] Show all { $count } milestones
+    }
+timeline-centre = Centre on today
+timeline-today = Today
+
+month-1 = January
+month-2 = February
+month-3 = March
+month-4 = April
+month-5 = May
+month-6 = June
+month-7 = July
+month-8 = August
+month-9 = September
+month-10 = October
+month-11 = November
+month-12 = December
+
+## Deliverables
+
+status-planned = Planned
+status-in-progress = In progress
+status-complete = Complete
+deliverable-preview = Preview
+deliverable-view-pdf = View PDF
+deliverable-unpublished = Not yet published
+deliverable-completed = COMPLETION DATE:
+deliverable-due = DUE DATE:
+
+## PDF viewer
+
+pdf-previous = Previous
+pdf-next = Next
+pdf-page = Page { $page } of { $count }
+pdf-open = Open PDF
+pdf-read-failed = Couldn't read { $asset }: { $error }
+pdf-open-failed = Couldn't open { $asset }: { $error }
+pdf-render-failed = Couldn't render page { $page }: { $error }
+pdf-needs-pdfium = Previews need the Pdfium library, either next to the app or installed system-wide. Open the PDF instead.
+pdf-needs-pdfjs = Previews need pdf.js, which couldn't be loaded. Open the PDF instead.
+
+## Player
+
+player-play = Play
+player-pause = Pause
+player-previous = Previous track
+player-next = Next track
+player-seek = Position
+player-volume = Volume
+player-queue = Queue ({ $count })
+player-remove = Remove from queue
+player-clear = Clear queue
+player-empty = Drop audio files on the window to play them.
+player-output = Output: { $name }
+player-crossfade = Crossfade
+player-locked = Click anywhere on the page to let it play sound.
+player-playlist-file = Playlist file
+player-playlist-formats = Use a file ending in one of { $formats }.
+player-playlist-drop = Drop { $formats } playlists on the window to queue them.
+player-import = Import
+player-export = Export
+player-export-name = queue
+player-imported = { $count ->
+    [one] Queued one track from { $name }
+   *[other] Queued { $count } tracks from { $name }
+}
+player-import-failed = Couldn't import { $name }:
+    { $error }
+player-import-unreadable = the file couldn't be read
+player-exported = Saved the queue to { $path }
+player-export-failed = Couldn't save { $path }: { $error }
+player-download-failed = The browser wouldn't download the playlist.
+
+## Focus timer
+
+focus-phase-focus = Focus
+focus-phase-short-break = Short break
+focus-phase-long-break = Long break
+focus-start = Start
+focus-pause = Pause
+focus-skip = Skip to the next phase
+focus-reset = Reset
+focus-completed = { $count ->
+    [0] No focus blocks finished yet
+    [one] One focus block finished
+   *[other] { $count } focus blocks finished
+}
+focus-lengths = Lengths
+focus-length-focus = Focus
+focus-length-short-break = Short break
+focus-length-long-break = Long break
+focus-long-break-every = Long break after this many focus blocks
+focus-minutes = min
+focus-music = Music
+focus-music-hint = Queue tracks in the player, then keep them here for focus or for breaks. They take over after the chime at each change.
+focus-music-focus = While focusing:
+focus-music-break = On breaks:
+focus-music-none = nothing set, the queue plays on
+focus-music-tracks = { $count ->
+    [one] one track
+   *[other] { $count } tracks
+}
+focus-use-queue = Use the current queue
+focus-music-clear = Clear
+
+## Meditation
+
+meditate-scripts = Sessions
+meditate-script = { $title } ({ $minutes } min)
+meditate-drop-hint = Drop a .ron script here to add your own session.
+meditate-load-failed = Couldn't load { $name }: { $error }
+meditate-start = Begin
+meditate-pause = Pause
+meditate-resume = Resume
+meditate-stop = End session
+meditate-breathe-in = Breathe in
+meditate-hold = Hold
+meditate-breathe-out = Breathe out
+meditate-rest = Rest
+
+## Soundscape
+
+soundscape = Soundscape
+soundscape-play = Play
+soundscape-stop = Stop
+soundscape-seed = Variation
+soundscape-seed-hint = Each number gives the same sound every time; try another for a different mix of drops and gusts.
+soundscape-rain = Rain
+soundscape-wind = Wind
+soundscape-white = White noise
+soundscape-pink = Pink noise
+soundscape-brown = Brown noise
+soundscape-binaural = Binaural beats
+soundscape-carrier = Tone
+soundscape-beat = Beat
+soundscape-headphones = Binaural beats only work through headphones.
+soundscape-export = Save { $minutes } minutes as WAV
+soundscape-exported = Saved to { $path }
+
+## Library
+
+library-folders = Music folders
+library-folder-hint = Folder path
+library-add-folder = Add folder
+library-remove-folder = Stop scanning this folder
+library-drop-hint = Drop folders on the window to add them too.
+library-rescan = Rescan
+library-scanning = Checked { $checked } of { $found } files
+library-count = { $count ->
+    [one] One track
+   *[other] { $count } tracks
+}
+library-tracks = Tracks
+library-search = Search titles, artists and albums
+library-queue-all = Queue { $count ->
+    [one] this track
+   *[other] all { $count }
+}
+library-empty = No tracks yet. Add a folder with music in it.
+library-no-matches = Nothing matches that search.
+library-title = Title
+library-artist = Artist
+library-album = Album
+library-duration = Length
+library-desktop-only = The library reads music folders on your computer, so it's only in the desktop app.
+
+## Friends
+
+social-profile = Your profile
+social-loading = Loading…
+social-counts = { $followers ->
+    [one] One follower
+   *[other] { $followers } followers
+} · following { $following }
+social-name = Name
+social-bio = About you
+social-save-profile = Save profile
+social-share = Share
+social-caption = Caption
+social-playlist-name = Playlist name
+social-share-queue = Share the queue as a playlist
+social-share-session = Share your last focus block ({ $minutes } min)
+social-no-session = Finish a focus block to share it
+social-people = People
+social-search = Search names and handles
+social-followers = { $count ->
+    [one] One follower
+   *[other] { $count } followers
+}
+social-follow = Follow
+social-unfollow = Unfollow
+social-nobody = Nobody matches that search.
+social-feed = Feed
+social-feed-empty = Nothing shared yet. Follow someone or share something yourself.
+social-someone = Someone
+social-shared-playlist = Shared the playlist “{ $name }” ({ $count ->
+    [one] one track
+   *[other] { $count } tracks
+})
+social-queue = Queue it
+social-shared-study = Studied for { $minutes } min
+social-shared-meditation = Meditated for { $minutes } min
+social-like = Like ({ $count })
+social-unlike = Liked ({ $count })
+social-comment-hint = Write a comment
+social-comment = Send
+social-failed = Couldn't reach your friends: { $error }
+social-account = Account
+social-offline-hint = You're looking at made-up friends. Sign up on a plistr-server to share with real ones.
+social-server = Server
+social-handle = Handle
+social-sign-up = Sign up
+social-signing-up = Signing up…
+social-signed-in = Signed in to { $server } as @{ $handle }
+social-sign-out = Sign out
+social-saved = Saved on the server
+social-save-queue = Save the queue as a playlist
+social-save-session = Save your last focus block ({ $minutes } min)
+social-nothing-saved = Nothing saved yet.
+social-saved-playlist = { $name } ({ $count ->
+    [one] one track
+   *[other] { $count } tracks
+})
+social-delete = Delete
+social-saved-sessions = { $count ->
+    [one] One focus block
+   *[other] { $count } focus blocks
+}, { $minutes } min in all
+
+## Study rooms
+rooms-intro = Study with friends in a room: everyone hears the host's music at the same moment and shares one focus timer. Start a relay with plistr-relay serve, then join the same room.
+rooms-relay = Relay
+rooms-room = Room
+rooms-your-name = Your name
+rooms-join = Join
+rooms-connecting = Connecting…
+rooms-cancel = Cancel
+rooms-leave = Leave
+rooms-clock = Your clock is { $offset } ms off the relay's, { $round_trip } ms there and back
+rooms-clock-unknown = Measuring the connection…
+rooms-guest-hint = Only the host can change the music and the timer.
+rooms-members = Members
+rooms-host = host
+rooms-you = you
+rooms-make-host = Make host
+rooms-music = Music
+rooms-track = Track { $number } of { $count }
+rooms-paused = Paused
+rooms-nothing = Nothing queued yet.
+rooms-play = Play
+rooms-pause = Pause
+rooms-next = Next
+rooms-no-player = There's no audio here, so you'll only see what the room is playing.
+rooms-queue-differs = Your queue isn't the room's, so your player isn't following along.
+rooms-share-queue = Play my queue in the room
+rooms-follow = Follow the room again
+rooms-host-hint = To play something else, change your queue, then play it in the room.
+rooms-timer = Timer
+rooms-use-lengths = Use my focus lengths
+rooms-rejected = The room said no: { $reason }
+rooms-failed = Couldn't reach the relay: { $error }
+rooms-lost = Lost the room: { $error }
+rooms-closed = the relay closed the connection
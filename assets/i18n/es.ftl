@@ -0,0 +1,310 @@
+language-name = Español
+app-name = Plistr
+
+## Navigation
+
+page-home = Inicio
+page-about = Sobre nosotros
+page-timeline = Cronología
+page-deliverables = Entregables
+page-contact = Contacto
+page-focus = Concentración
+page-meditate = Meditar
+page-library = Biblioteca
+page-social = Amigos
+page-rooms = Salas de estudio
+nav-home-label = Inicio de { app-name }
+nav-shortcut = Atajo: { $key }
+nav-previous = Anterior: { $page }
+nav-next = Siguiente: { $page }
+nav-sitemap = Mapa del sitio
+nav-menu = Menú
+nav-more = Más
+
+## Header menus
+
+menu-theme = Tema
+menu-theme-system = Según el sistema
+menu-accent = Color de acento
+menu-accent-default = El del tema
+menu-text-size = Tamaño del texto
+menu-text-size-hint = También funciona Ctrl +/-.
+menu-screen-reader = Leer los controles en voz alta
+menu-language = Idioma
+menu-language-system = Según el sistema
+
+## Team
+
+team-photo = Foto de { $name }
+team-responsibilities = Responsabilidades:
+
+## Timeline
+
+timeline-zoom-out = Alejar
+timeline-zoom-in = Acercar
+timeline-fit =
+    { $count ->
+        [one] Mostrar el hito
+       *[other] Mostrar los { $count } hitos
+    }
+timeline-centre = Centrar en hoy
+timeline-today = Hoy
+
+month-1 = enero
+month-2 = febrero
+month-3 = marzo
+month-4 = abril
+month-5 = mayo
+month-6 = junio
+month-7 = julio
+month-8 = agosto
+month-9 = septiembre
+month-10 = octubre
+month-11 = noviembre
+month-12 = diciembre
+
+## Deliverables
+
+status-planned = Previsto
+status-in-progress = En curso
+status-complete = Terminado
+deliverable-preview = Vista previa
+deliverable-view-pdf = Ver PDF
+deliverable-unpublished = Aún no publicado
+deliverable-completed = FECHA DE ENTREGA:
+deliverable-due = FECHA LÍMITE:
+
+## PDF viewer
+
+pdf-previous = Anterior
+pdf-next = Siguiente
+pdf-page = Página { $page } de { $count }
+pdf-open = Abrir PDF
+pdf-read-failed = No se pudo leer { $asset }: { $error }
+pdf-open-failed = No se pudo abrir { $asset }: { $error }
+pdf-render-failed = No se pudo mostrar la página { $page }: { $error }
+pdf-needs-pdfium = Las vistas previas necesitan la biblioteca Pdfium, junto a la aplicación o instalada en el sistema. Abre el PDF en su lugar.
+pdf-needs-pdfjs = Las vistas previas necesitan pdf.js, que no se pudo cargar. Abre el PDF en su lugar.
+
+## Player
+
+player-play = Reproducir
+player-pause = Pausa
+player-previous = Pista anterior
+player-next = Pista siguiente
+player-seek = Posición
+player-volume = Volumen
+player-queue = Cola ({ $count })
+player-remove = Quitar de la cola
+player-clear = Vaciar la cola
+player-empty = Suelta archivos de audio en la ventana para reproducirlos.
+player-output = Salida: { $name }
+player-crossfade = Fundido
+player-locked = Haz clic en cualquier parte de la página para permitir el sonido.
+player-playlist-file = Archivo de lista
+player-playlist-formats = Usa un archivo terminado en { $formats }.
+player-playlist-drop = Suelta listas { $formats } en la ventana para ponerlas en cola.
+player-import = Importar
+player-export = Exportar
+player-export-name = cola
+player-imported = { $count ->
+    [one] Se añadió una pista de { $name } a la cola
+   *[other] Se añadieron { $count } pistas de { $name } a la cola
+}
+player-import-failed = No se pudo importar { $name }:
+    { $error }
+player-import-unreadable = no se pudo leer el archivo
+player-exported = La cola se guardó en { $path }
+player-export-failed = No se pudo guardar { $path }: { $error }
+player-download-failed = El navegador no quiso descargar la lista.
+
+## Focus timer
+
+focus-phase-focus = Concentración
+focus-phase-short-break = Descanso corto
+focus-phase-long-break = Descanso largo
+focus-start = Empezar
+focus-pause = Pausa
+focus-skip = Pasar a la siguiente fase
+focus-reset = Reiniciar
+focus-completed = { $count ->
+    [0] Aún no has terminado ningún bloque
+    [one] Un bloque de concentración terminado
+   *[other] { $count } bloques de concentración terminados
+}
+focus-lengths = Duraciones
+focus-length-focus = Concentración
+focus-length-short-break = Descanso corto
+focus-length-long-break = Descanso largo
+focus-long-break-every = Descanso largo tras este número de bloques
+focus-minutes = min
+focus-music = Música
+focus-music-hint = Pon pistas en la cola del reproductor y guárdalas aquí para concentrarte o para los descansos. Suenan tras la campanilla de cada cambio.
+focus-music-focus = Al concentrarte:
+focus-music-break = En los descansos:
+focus-music-none = nada elegido, sigue la cola
+focus-music-tracks = { $count ->
+    [one] una pista
+   *[other] { $count } pistas
+}
+focus-use-queue = Usar la cola actual
+focus-music-clear = Quitar
+
+## Meditation
+
+meditate-scripts = Sesiones
+meditate-script = { $title } ({ $minutes } min)
+meditate-drop-hint = Suelta aquí un guion .ron para añadir tu propia sesión.
+meditate-load-failed = No se pudo cargar { $name }: { $error }
+meditate-start = Empezar
+meditate-pause = Pausa
+meditate-resume = Continuar
+meditate-stop = Terminar la sesión
+meditate-breathe-in = Inspira
+meditate-hold = Mantén
+meditate-breathe-out = Espira
+meditate-rest = Descansa
+
+## Soundscape
+
+soundscape = Paisaje sonoro
+soundscape-play = Reproducir
+soundscape-stop = Detener
+soundscape-seed = Variación
+soundscape-seed-hint = Cada número da siempre el mismo sonido; prueba otro para otra mezcla de gotas y ráfagas.
+soundscape-rain = Lluvia
+soundscape-wind = Viento
+soundscape-white = Ruido blanco
+soundscape-pink = Ruido rosa
+soundscape-brown = Ruido marrón
+soundscape-binaural = Pulsos binaurales
+soundscape-carrier = Tono
+soundscape-beat = Pulso
+soundscape-headphones = Los pulsos binaurales solo funcionan con auriculares.
+soundscape-export = Guardar { $minutes } minutos como WAV
+soundscape-exported = Guardado en { $path }
+
+## Biblioteca
+
+library-folders = Carpetas de música
+library-folder-hint = Ruta de la carpeta
+library-add-folder = Añadir carpeta
+library-remove-folder = Dejar de buscar en esta carpeta
+library-drop-hint = También puedes soltar carpetas en la ventana para añadirlas.
+library-rescan = Volver a buscar
+library-scanning = Revisados { $checked } de { $found } archivos
+library-count = { $count ->
+    [one] Una pista
+   *[other] { $count } pistas
+}
+library-tracks = Pistas
+library-search = Buscar títulos, artistas y álbumes
+library-queue-all = { $count ->
+    [one] Poner esta pista en cola
+   *[other] Poner las { $count } en cola
+}
+library-empty = Aún no hay pistas. Añade una carpeta con música.
+library-no-matches = Nada coincide con esa búsqueda.
+library-title = Título
+library-artist = Artista
+library-album = Álbum
+library-duration = Duración
+library-desktop-only = La biblioteca lee las carpetas de música de tu ordenador, así que solo está en la aplicación de escritorio.
+
+## Friends
+
+social-profile = Tu perfil
+social-loading = Cargando…
+social-counts = { $followers ->
+    [one] Un seguidor
+   *[other] { $followers } seguidores
+} · sigues a { $following }
+social-name = Nombre
+social-bio = Sobre ti
+social-save-profile = Guardar perfil
+social-share = Compartir
+social-caption = Comentario
+social-playlist-name = Nombre de la lista
+social-share-queue = Compartir la cola como lista
+social-share-session = Compartir tu último bloque de concentración ({ $minutes } min)
+social-no-session = Termina un bloque de concentración para compartirlo
+social-people = Personas
+social-search = Buscar nombres y usuarios
+social-followers = { $count ->
+    [one] Un seguidor
+   *[other] { $count } seguidores
+}
+social-follow = Seguir
+social-unfollow = Dejar de seguir
+social-nobody = Nadie coincide con esa búsqueda.
+social-feed = Novedades
+social-feed-empty = Aún no se ha compartido nada. Sigue a alguien o comparte algo tú.
+social-someone = Alguien
+social-shared-playlist = Compartió la lista «{ $name }» ({ $count ->
+    [one] una pista
+   *[other] { $count } pistas
+})
+social-queue = Ponerla en cola
+social-shared-study = Estudió durante { $minutes } min
+social-shared-meditation = Meditó durante { $minutes } min
+social-like = Me gusta ({ $count })
+social-unlike = Te gusta ({ $count })
+social-comment-hint = Escribe un comentario
+social-comment = Enviar
+social-failed = No se pudo contactar con tus amigos: { $error }
+social-account = Cuenta
+social-offline-hint = Estos amigos son inventados. Regístrate en un plistr-server para compartir con amigos de verdad.
+social-server = Servidor
+social-handle = Usuario
+social-sign-up = Registrarse
+social-signing-up = Registrando…
+social-signed-in = Conectado a { $server } como @{ $handle }
+social-sign-out = Cerrar sesión
+social-saved = Guardado en el servidor
+social-save-queue = Guardar la cola como lista
+social-save-session = Guardar tu último bloque de concentración ({ $minutes } min)
+social-nothing-saved = Aún no hay nada guardado.
+social-saved-playlist = { $name } ({ $count ->
+    [one] una pista
+   *[other] { $count } pistas
+})
+social-delete = Borrar
+social-saved-sessions = { $count ->
+    [one] Un bloque de concentración
+   *[other] { $count } bloques de concentración
+}, { $minutes } min en total
+
+## Study rooms
+rooms-intro = Estudia con amigos en una sala: todos oyen la música del anfitrión en el mismo momento y comparten un temporizador. Inicia un relé con plistr-relay serve y entrad en la misma sala.
+rooms-relay = Relé
+rooms-room = Sala
+rooms-your-name = Tu nombre
+rooms-join = Entrar
+rooms-connecting = Conectando…
+rooms-cancel = Cancelar
+rooms-leave = Salir
+rooms-clock = Tu reloj va { $offset } ms desfasado del relé, { $round_trip } ms de ida y vuelta
+rooms-clock-unknown = Midiendo la conexión…
+rooms-guest-hint = Solo el anfitrión puede cambiar la música y el temporizador.
+rooms-members = Miembros
+rooms-host = anfitrión
+rooms-you = tú
+rooms-make-host = Hacer anfitrión
+rooms-music = Música
+rooms-track = Pista { $number } de { $count }
+rooms-paused = En pausa
+rooms-nothing = Aún no hay nada en la cola.
+rooms-play = Reproducir
+rooms-pause = Pausar
+rooms-next = Siguiente
+rooms-no-player = Aquí no hay audio, así que solo verás lo que suena en la sala.
+rooms-queue-differs = Tu cola no es la de la sala, así que tu reproductor no la sigue.
+rooms-share-queue = Poner mi cola en la sala
+rooms-follow = Volver a seguir la sala
+rooms-host-hint = Para poner otra cosa, cambia tu cola y ponla en la sala.
+rooms-timer = Temporizador
+rooms-use-lengths = Usar mis duraciones
+rooms-rejected = La sala lo rechazó: { $reason }
+rooms-failed = No se pudo contactar con el relé: { $error }
+rooms-lost = Se perdió la sala: { $error }
+rooms-closed = el relé cerró la conexión
@@ -0,0 +1,132 @@
+//! Fails the build if a content file or a meditation script points at an
+//! asset that isn't in the repository, so a broken link can't be deployed.
+//! Also lists `assets/meditations` for the app to bundle.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+
+#[derive(Deserialize)]
+struct Content {
+    about: About,
+    deliverables: Deliverables,
+}
+
+#[derive(Deserialize)]
+struct About {
+    team: Vec<Member>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    name: String,
+    #[serde(default)]
+    avatar: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Deliverables {
+    items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    title: String,
+    #[serde(default)]
+    asset: Option<String>,
+}
+
+/// Just the sounds a meditation script plays.
+#[derive(Deserialize)]
+struct Script {
+    segments: Vec<Segment>,
+}
+
+#[derive(Deserialize)]
+struct Segment {
+    #[serde(default)]
+    voice: Option<String>,
+    #[serde(default)]
+    ambient: Option<Ambient>,
+}
+
+#[derive(Deserialize)]
+struct Ambient {
+    source: String,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=assets");
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    bundle_meditations(root);
+
+    // `content.ron` and its translations, like `content.es.ron`.
+    let mut files: Vec<_> = std::fs::read_dir(root.join("assets"))
+        .expect("assets is missing")
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with("content.") && name.ends_with(".ron"))
+        .collect();
+    files.sort();
+    assert!(files.iter().any(|name| name == "content.ron"), "assets/content.ron is missing");
+    for name in files {
+        check_content(root, &name);
+    }
+}
+
+fn check_content(root: &Path, name: &str) {
+    let text = std::fs::read_to_string(root.join("assets").join(name))
+        .unwrap_or_else(|e| panic!("assets/{name}: {e}"));
+    // Only the asset references are read here; empty fields and the like
+    // are reported by `Content::parse` with better context.
+    let content: Content = ron::from_str(&text).unwrap_or_else(|e| panic!("assets/{name}: {e}"));
+
+    let referenced = content.about.team.iter()
+        .filter_map(|member| Some((&member.name, member.avatar.as_ref()?)))
+        .chain(content.deliverables.items.iter()
+            .filter_map(|item| Some((&item.title, item.asset.as_ref()?))));
+    let missing: Vec<_> = referenced
+        .filter(|(_, path)| !root.join(path).is_file())
+        .map(|(owner, path)| format!("  - {path} (used by {owner:?})"))
+        .collect();
+    if !missing.is_empty() {
+        panic!("assets/{name} references files that don't exist:\n{}", missing.join("\n"));
+    }
+}
+
+/// Writes `meditations.rs` to `OUT_DIR`: every script as `(file name,
+/// text)`. Like the content files, a script that isn't RON fails the build,
+/// but only the files it plays are checked here; the app reports anything
+/// else wrong with one when it starts.
+fn bundle_meditations(root: &Path) {
+    let dir = root.join("assets/meditations");
+    let mut scripts: Vec<_> = std::fs::read_dir(&dir)
+        .expect("assets/meditations is missing")
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    scripts.sort();
+
+    let mut listed = String::from("&[\n");
+    for path in scripts {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let text = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("assets/meditations/{name}: {e}"));
+        let script: Script = ron::from_str(&text).unwrap_or_else(|e| panic!("assets/meditations/{name}: {e}"));
+        let missing: Vec<_> = script.segments.iter()
+            .flat_map(|segment| segment.voice.iter().chain(segment.ambient.iter().map(|ambient| &ambient.source)))
+            .filter(|source| !source.contains("://") && !root.join(source).is_file())
+            .map(|source| format!("  - {source}"))
+            .collect();
+        if !missing.is_empty() {
+            panic!("assets/meditations/{name} plays files that don't exist:\n{}", missing.join("\n"));
+        }
+        listed += &format!("    ({name:?}, include_str!({:?})),\n", path.display().to_string());
+    }
+    listed += "]\n";
+
+    let out = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    std::fs::write(Path::new(&out).join("meditations.rs"), listed)
+        .expect("can't write meditations.rs");
+}
@@ -0,0 +1,124 @@
+//! `plistr`, for working with playlist files from a shell:
+//!
+//! ```text
+//! plistr convert <input> <output>   rewrite a playlist in another format
+//! plistr check <file>...            list what's wrong with playlist files
+//! plistr tracks <file>              print a playlist's tracks
+//! ```
+//!
+//! Formats come from the file extensions: .m3u, .m3u8, .pls or .xspf.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use plistr_core::{decode_text, Playlist, PlaylistFormat, PlaylistId, TrackId, UserId};
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["convert", input, output] => convert(input, output),
+        ["check", files @ ..] if !files.is_empty() => check(files),
+        ["tracks", file] => tracks(file),
+        ["help" | "--help" | "-h"] => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprint!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Relative paths are resolved against the input's folder and written
+/// relative to the output's, so the new file finds the same tracks.
+fn convert(input: &str, output: &str) -> Result<(), String> {
+    let playlist = read(input)?;
+    let format = format(output)?;
+    std::fs::write(output, format.write(&playlist, &folder(output))).map_err(|e| format!("{output}: {e}"))?;
+    println!("{output}: {} tracks as {format}", playlist.len());
+    Ok(())
+}
+
+fn check(files: &[&str]) -> Result<(), String> {
+    let mut failed = 0;
+    for file in files {
+        match read(file) {
+            Ok(playlist) => println!("{file}: {} tracks", playlist.len()),
+            Err(message) => {
+                println!("{message}");
+                failed += 1;
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        1 => Err("1 file has problems".to_owned()),
+        _ => Err(format!("{failed} files have problems")),
+    }
+}
+
+fn tracks(file: &str) -> Result<(), String> {
+    let playlist = read(file)?;
+    if !playlist.name.is_empty() {
+        println!("# {}", playlist.name);
+    }
+    for track in playlist.tracks() {
+        let length = track.duration.map_or_else(|| "-:--".to_owned(), |duration| {
+            let seconds = duration.as_secs();
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        });
+        let artist = track.artist.as_deref().map_or_else(String::new, |artist| format!("{artist} - "));
+        println!("{length:>6}  {artist}{}  ({})", track.title, track.source);
+    }
+    Ok(())
+}
+
+/// Problems come back one per line as `file:line: message`, the way
+/// compilers and editors expect.
+fn read(file: &str) -> Result<Playlist, String> {
+    let format = format(file)?;
+    let bytes = std::fs::read(file).map_err(|e| format!("{file}: {e}"))?;
+    let mut playlist = Playlist::new(PlaylistId(0), UserId(0), "");
+    let mut next = 0;
+    let new_id = || {
+        next += 1;
+        TrackId(next)
+    };
+    format.read(&decode_text(&bytes), &folder(file), &mut playlist, new_id).map_err(|e| {
+        let lines: Vec<String> =
+            e.problems.iter().map(|problem| format!("{file}:{}: {}", problem.line, problem.message)).collect();
+        lines.join("\n")
+    })?;
+    Ok(playlist)
+}
+
+fn format(file: &str) -> Result<PlaylistFormat, String> {
+    PlaylistFormat::from_path(file).ok_or_else(|| {
+        let extensions: Vec<&str> = PlaylistFormat::ALL.iter().map(|format| format.extension()).collect();
+        format!("{file}: expected a file ending in .{}", extensions.join(", ."))
+    })
+}
+
+fn folder(file: &str) -> String {
+    let parent = Path::new(file).parent();
+    parent.map(|parent| parent.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+
+const USAGE: &str = "\
+usage: plistr convert <input> <output>
+       plistr check <file>...
+       plistr tracks <file>
+
+Playlists are M3U, M3U8, PLS or XSPF, going by their extension.
+";
@@ -0,0 +1,160 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{encode_query, Account, ErrorBody, NewComment, NewPost, ProfileUpdate, SignUp};
+use crate::{Playlist, PlaylistId, Post, PostId, Profile, Reply, Session, Shared, SocialClient, SocialError, UserId};
+
+
+/// Enough to sign back in, kept between runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Credentials {
+    /// The server's address, like `http://127.0.0.1:8080`.
+    pub server: String,
+    pub user: UserId,
+    pub token: String,
+}
+
+/// A typed client for `plistr-server`'s REST API. It answers through
+/// `Reply`s like any `SocialClient`, so the app can use it in place of the
+/// mock, and adds the playlists and sessions kept there.
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    credentials: Credentials,
+}
+
+impl ApiClient {
+    pub fn new(credentials: Credentials) -> Self {
+        Self { credentials }
+    }
+
+    /// Makes an account on `server` and answers with a client signed in to
+    /// it.
+    pub fn sign_up(server: &str, handle: &str, display_name: &str, reply: Reply<ApiClient>) {
+        let server = server.trim().trim_end_matches('/').to_owned();
+        let body = SignUp { handle: handle.trim().to_owned(), display_name: display_name.trim().to_owned() };
+        let request = request("POST", &format!("{server}/users"), None, Some(to_json(&body)));
+        send(request, Box::new(move |result: Result<Account, SocialError>| {
+            reply(result.map(|account| {
+                ApiClient::new(Credentials { server, user: account.profile.user.id, token: account.token })
+            }));
+        }));
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    /// In id order.
+    pub fn playlists(&self, user: UserId, reply: Reply<Vec<Playlist>>) {
+        self.call("GET", &format!("/users/{}/playlists", user.0), None, reply);
+    }
+
+    /// Saves a copy as the signed-in user's, under an id the server picks.
+    pub fn create_playlist(&self, playlist: &Playlist, reply: Reply<Playlist>) {
+        self.call("POST", "/playlists", Some(to_json(playlist)), reply);
+    }
+
+    pub fn delete_playlist(&self, id: PlaylistId, reply: Reply<()>) {
+        self.call("DELETE", &format!("/playlists/{}", id.0), None, reply);
+    }
+
+    /// Earliest first.
+    pub fn sessions(&self, user: UserId, reply: Reply<Vec<Session>>) {
+        self.call("GET", &format!("/users/{}/sessions", user.0), None, reply);
+    }
+
+    /// Saves a copy as the signed-in user's, under an id the server picks.
+    pub fn create_session(&self, session: &Session, reply: Reply<Session>) {
+        self.call("POST", "/sessions", Some(to_json(session)), reply);
+    }
+
+    fn call<T: DeserializeOwned + 'static>(&self, method: &str, path: &str, body: Option<String>, reply: Reply<T>) {
+        let url = format!("{}{path}", self.credentials.server);
+        send(request(method, &url, Some(&self.credentials.token), body), reply);
+    }
+}
+
+impl SocialClient for ApiClient {
+    fn me(&self) -> UserId {
+        self.credentials.user
+    }
+
+    fn profile(&self, user: UserId, reply: Reply<Profile>) {
+        self.call("GET", &format!("/users/{}", user.0), None, reply);
+    }
+
+    fn update_profile(&self, display_name: String, bio: String, reply: Reply<Profile>) {
+        self.call("PUT", "/me", Some(to_json(&ProfileUpdate { display_name, bio })), reply);
+    }
+
+    fn people(&self, query: &str, reply: Reply<Vec<Profile>>) {
+        self.call("GET", &format!("/users?query={}", encode_query(query)), None, reply);
+    }
+
+    fn follow(&self, user: UserId, reply: Reply<Profile>) {
+        self.call("PUT", &format!("/me/following/{}", user.0), None, reply);
+    }
+
+    fn unfollow(&self, user: UserId, reply: Reply<Profile>) {
+        self.call("DELETE", &format!("/me/following/{}", user.0), None, reply);
+    }
+
+    fn feed(&self, limit: usize, reply: Reply<Vec<Post>>) {
+        self.call("GET", &format!("/me/feed?limit={limit}"), None, reply);
+    }
+
+    fn share(&self, shared: Shared, caption: String, reply: Reply<Post>) {
+        self.call("POST", "/posts", Some(to_json(&NewPost { shared, caption })), reply);
+    }
+
+    fn like(&self, post: PostId, reply: Reply<Post>) {
+        self.call("PUT", &format!("/posts/{}/like", post.0), None, reply);
+    }
+
+    fn unlike(&self, post: PostId, reply: Reply<Post>) {
+        self.call("DELETE", &format!("/posts/{}/like", post.0), None, reply);
+    }
+
+    fn comment(&self, post: PostId, text: String, reply: Reply<Post>) {
+        self.call("POST", &format!("/posts/{}/comments", post.0), Some(to_json(&NewComment { text })), reply);
+    }
+}
+
+fn request(method: &str, url: &str, token: Option<&str>, body: Option<String>) -> ehttp::Request {
+    let mut request = ehttp::Request::get(url);
+    request.method = method.to_owned();
+    request.headers = ehttp::Headers::new(&[("Accept", "application/json")]);
+    if let Some(token) = token {
+        request.headers.insert("Authorization", format!("Bearer {token}"));
+    }
+    if let Some(body) = body {
+        request.headers.insert("Content-Type", "application/json");
+        request.body = body.into_bytes();
+    }
+    request
+}
+
+/// Sends the request and answers with its body read as `T`. A 204's empty
+/// body reads as `()`.
+fn send<T: DeserializeOwned + 'static>(request: ehttp::Request, reply: Reply<T>) {
+    ehttp::fetch(request, move |result| {
+        let answer = match result {
+            Err(e) => Err(SocialError::Network(e)),
+            Ok(response) if response.ok => {
+                let bytes: &[u8] = if response.bytes.is_empty() { b"null" } else { &response.bytes };
+                serde_json::from_slice(bytes).map_err(|e| SocialError::Network(e.to_string()))
+            }
+            Ok(response) if response.status == 404 => Err(SocialError::NotFound),
+            Ok(response) => {
+                let reason = serde_json::from_slice::<ErrorBody>(&response.bytes)
+                    .map_or_else(|_| format!("{} {}", response.status, response.status_text), |body| body.error);
+                Err(if response.status < 500 { SocialError::Rejected(reason) } else { SocialError::Network(reason) })
+            }
+        };
+        reply(answer);
+    });
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("API types always serialize")
+}
@@ -0,0 +1,94 @@
+//! The bodies `plistr-server`'s REST API takes and gives, as JSON, so the
+//! server and the app's client can't disagree about them. Everything else
+//! goes as the types themselves: `Profile`, `Playlist`, `Session` and
+//! `Post`. The paths are described in the server's `openapi.json`, and
+//! with the `client` feature `ApiClient` calls them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Profile, Shared};
+
+#[cfg(feature = "client")]
+mod client;
+
+#[cfg(feature = "client")]
+pub use client::{ApiClient, Credentials};
+
+
+/// `POST /users`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignUp {
+    pub handle: String,
+    pub display_name: String,
+}
+
+/// The answer to signing up. The token goes in an `Authorization: Bearer`
+/// header on every request made as this user, and isn't given out again.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub profile: Profile,
+    pub token: String,
+}
+
+/// `PUT /me`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileUpdate {
+    pub display_name: String,
+    #[serde(default)]
+    pub bio: String,
+}
+
+/// `POST /posts`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NewPost {
+    pub shared: Shared,
+    #[serde(default)]
+    pub caption: String,
+}
+
+/// `POST /posts/{id}/comments`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NewComment {
+    pub text: String,
+}
+
+/// The body of every answer that isn't a success.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+/// Percent-encodes `text` for a query string value.
+pub fn encode_query(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Undoes `encode_query`, and reads `+` as a space as forms send it.
+/// Escapes that don't decode are kept as they are.
+pub fn decode_query(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => match text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,371 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+
+/// Where a `FocusTimer` gets the time. Only differences between readings
+/// matter, so any monotonic source will do.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/// A clock that only moves when told to, for driving a timer in tests.
+/// Clones share the same time.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock(Rc<Cell<Duration>>);
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FocusSettings {
+    pub focus: Duration,
+    pub short_break: Duration,
+    pub long_break: Duration,
+    /// A long break replaces every this-many-th short one.
+    pub long_break_every: u32,
+}
+
+impl Default for FocusSettings {
+    fn default() -> Self {
+        Self {
+            focus: Duration::from_secs(25 * 60),
+            short_break: Duration::from_secs(5 * 60),
+            long_break: Duration::from_secs(15 * 60),
+            long_break_every: 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Phase {
+    Focus,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn is_break(self) -> bool {
+        self != Phase::Focus
+    }
+}
+
+/// Everything about a `FocusTimer` but its clock, for sending it to
+/// someone else's.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FocusSnapshot {
+    pub settings: FocusSettings,
+    pub phase: Phase,
+    pub streak: u32,
+    pub completed: u32,
+    pub elapsed: Duration,
+    pub running: bool,
+}
+
+/// A Pomodoro timer: focus, break, focus, break… with a long break after
+/// every few focus blocks. Phases follow on by themselves once started;
+/// call `poll` often to find out when they change.
+#[derive(Debug)]
+pub struct FocusTimer<C> {
+    clock: C,
+    settings: FocusSettings,
+    phase: Phase,
+    /// Focus blocks finished since the last long break.
+    streak: u32,
+    /// Focus blocks finished in all.
+    completed: u32,
+    /// Time spent in this phase before the last resume.
+    banked: Duration,
+    /// When the timer was last resumed, if it's running.
+    resumed_at: Option<Duration>,
+}
+
+impl<C: Clock> FocusTimer<C> {
+    /// Stopped, at the start of a focus block.
+    pub fn new(settings: FocusSettings, clock: C) -> Self {
+        Self {
+            clock,
+            settings,
+            phase: Phase::Focus,
+            streak: 0,
+            completed: 0,
+            banked: Duration::ZERO,
+            resumed_at: None,
+        }
+    }
+
+    /// Picks up where `snapshot` was taken, as of `clock`'s time now.
+    pub fn restore(snapshot: FocusSnapshot, clock: C) -> Self {
+        let resumed_at = snapshot.running.then(|| clock.now());
+        Self {
+            clock,
+            settings: snapshot.settings,
+            phase: snapshot.phase,
+            streak: snapshot.streak,
+            completed: snapshot.completed,
+            banked: snapshot.elapsed,
+            resumed_at,
+        }
+    }
+
+    pub fn snapshot(&self) -> FocusSnapshot {
+        FocusSnapshot {
+            settings: self.settings,
+            phase: self.phase,
+            streak: self.streak,
+            completed: self.completed,
+            elapsed: self.elapsed(),
+            running: self.is_running(),
+        }
+    }
+
+    pub fn settings(&self) -> FocusSettings {
+        self.settings
+    }
+
+    /// Takes effect straight away; a phase now longer than its new length
+    /// ends at the next `poll`.
+    pub fn set_settings(&mut self, settings: FocusSettings) {
+        self.settings = settings;
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn completed(&self) -> u32 {
+        self.completed
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.resumed_at.is_some()
+    }
+
+    pub fn length(&self) -> Duration {
+        match self.phase {
+            Phase::Focus => self.settings.focus,
+            Phase::ShortBreak => self.settings.short_break,
+            Phase::LongBreak => self.settings.long_break,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        let running = self.resumed_at.map_or(Duration::ZERO, |at| self.clock.now().saturating_sub(at));
+        (self.banked + running).min(self.length())
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.length() - self.elapsed()
+    }
+
+    /// How far through the phase, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.length().is_zero() {
+            return 1.0;
+        }
+        self.elapsed().as_secs_f32() / self.length().as_secs_f32()
+    }
+
+    pub fn resume(&mut self) {
+        if self.resumed_at.is_none() {
+            self.resumed_at = Some(self.clock.now());
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.banked = self.elapsed();
+        self.resumed_at = None;
+    }
+
+    /// Ends the phase early and starts the next, running or not as before.
+    /// A skipped focus block isn't completed and doesn't bring the long
+    /// break closer; a short break follows it.
+    pub fn skip(&mut self) -> Phase {
+        let next = self.next_phase(false);
+        self.enter(next, Duration::ZERO);
+        next
+    }
+
+    /// Back to a stopped focus block, forgetting the streak towards a long
+    /// break but not the blocks completed.
+    pub fn reset(&mut self) {
+        self.phase = Phase::Focus;
+        self.streak = 0;
+        self.banked = Duration::ZERO;
+        self.resumed_at = None;
+    }
+
+    /// Moves on if the phase has run out, returning the phase it moved to.
+    /// Time past the end carries into the next phase, so a late poll doesn't
+    /// stretch the schedule; call again until `None` to catch up.
+    pub fn poll(&mut self) -> Option<Phase> {
+        let at = self.resumed_at?;
+        let spent = self.banked + self.clock.now().saturating_sub(at);
+        let overrun = spent.checked_sub(self.length())?;
+        let next = self.next_phase(true);
+        self.enter(next, overrun);
+        Some(next)
+    }
+
+    fn next_phase(&mut self, finished: bool) -> Phase {
+        match self.phase {
+            Phase::Focus => {
+                if !finished {
+                    return Phase::ShortBreak;
+                }
+                self.completed += 1;
+                self.streak += 1;
+                if self.streak >= self.settings.long_break_every.max(1) {
+                    self.streak = 0;
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Focus,
+        }
+    }
+
+    fn enter(&mut self, phase: Phase, already: Duration) {
+        self.phase = phase;
+        self.banked = already;
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(self.clock.now());
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    fn settings() -> FocusSettings {
+        FocusSettings { focus: minutes(25), short_break: minutes(5), long_break: minutes(15), long_break_every: 3 }
+    }
+
+    fn running() -> (FocusTimer<ManualClock>, ManualClock) {
+        let clock = ManualClock::default();
+        let mut timer = FocusTimer::new(settings(), clock.clone());
+        timer.resume();
+        (timer, clock)
+    }
+
+    #[test]
+    fn a_long_break_follows_every_few_focus_blocks() {
+        let (mut timer, clock) = running();
+        let mut phases = Vec::new();
+        for _ in 0..8 {
+            clock.advance(timer.remaining());
+            phases.push(timer.poll().unwrap());
+            assert_eq!(timer.poll(), None);
+        }
+        use Phase::*;
+        assert_eq!(phases, [ShortBreak, Focus, ShortBreak, Focus, LongBreak, Focus, ShortBreak, Focus]);
+        assert_eq!(timer.completed(), 4);
+    }
+
+    #[test]
+    fn nothing_happens_before_the_phase_ends_or_while_stopped() {
+        let clock = ManualClock::default();
+        let mut timer = FocusTimer::new(settings(), clock.clone());
+        clock.advance(minutes(60));
+        assert_eq!(timer.poll(), None);
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+        timer.resume();
+        clock.advance(minutes(24));
+        assert_eq!(timer.poll(), None);
+        assert_eq!(timer.remaining(), minutes(1));
+    }
+
+    #[test]
+    fn a_late_poll_carries_the_overrun_over() {
+        let (mut timer, clock) = running();
+        // Focus, a short break and two minutes into the next focus block.
+        clock.advance(minutes(25 + 5 + 2));
+        assert_eq!(timer.poll(), Some(Phase::ShortBreak));
+        assert_eq!(timer.elapsed(), minutes(5));
+        assert_eq!(timer.poll(), Some(Phase::Focus));
+        assert_eq!(timer.poll(), None);
+        assert_eq!(timer.elapsed(), minutes(2));
+        assert_eq!(timer.completed(), 1);
+    }
+
+    #[test]
+    fn pausing_banks_the_time_spent() {
+        let (mut timer, clock) = running();
+        clock.advance(minutes(10));
+        timer.pause();
+        assert!(!timer.is_running());
+        clock.advance(minutes(60));
+        assert_eq!(timer.elapsed(), minutes(10));
+        assert_eq!(timer.poll(), None);
+        timer.resume();
+        // Resuming twice doesn't lose what was banked.
+        timer.resume();
+        clock.advance(minutes(5));
+        assert_eq!(timer.elapsed(), minutes(15));
+        assert!((timer.progress() - 0.6).abs() < 1e-6);
+        clock.advance(minutes(10));
+        assert_eq!(timer.poll(), Some(Phase::ShortBreak));
+    }
+
+    #[test]
+    fn skipping_focus_counts_for_nothing() {
+        let (mut timer, clock) = running();
+        for _ in 0..5 {
+            clock.advance(minutes(3));
+            assert_eq!(timer.skip(), Phase::ShortBreak);
+            assert_eq!(timer.elapsed(), Duration::ZERO);
+            assert!(timer.is_running());
+            assert_eq!(timer.skip(), Phase::Focus);
+        }
+        assert_eq!(timer.completed(), 0);
+        assert_eq!(timer.snapshot().streak, 0);
+        // Finished blocks still bring on the long break on schedule.
+        for expected in [Phase::ShortBreak, Phase::Focus, Phase::ShortBreak, Phase::Focus, Phase::LongBreak] {
+            clock.advance(timer.remaining());
+            assert_eq!(timer.poll(), Some(expected));
+        }
+        assert_eq!(timer.completed(), 3);
+    }
+
+    #[test]
+    fn reset_forgets_the_streak_but_not_the_blocks_completed() {
+        let (mut timer, clock) = running();
+        clock.advance(minutes(25));
+        timer.poll();
+        timer.reset();
+        assert_eq!((timer.phase(), timer.is_running(), timer.completed()), (Phase::Focus, false, 1));
+        assert_eq!(timer.snapshot().streak, 0);
+    }
+
+    #[test]
+    fn a_snapshot_restores_on_another_clock() {
+        let (mut timer, clock) = running();
+        clock.advance(minutes(25 + 2));
+        timer.poll();
+        let snapshot = timer.snapshot();
+        let other = ManualClock::default();
+        other.advance(minutes(1000));
+        let restored = FocusTimer::restore(snapshot, other.clone());
+        assert_eq!(restored.snapshot(), snapshot);
+        other.advance(minutes(1));
+        assert_eq!(restored.elapsed(), minutes(3));
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt::Write as _;
+
+use super::{one_line, parse_seconds, relative, seconds, Entry, FormatError, Parsed, Problem};
+use crate::Playlist;
+
+
+/// Plain and extended M3U. `#EXTINF` gives the length and title of the
+/// path after it, as "Artist - Title" when there's an artist; `#EXTART`
+/// says which part that is, and `#PLAYLIST` names the list. Other `#` lines
+/// are comments.
+pub(super) fn read(text: &str) -> Result<Parsed, FormatError> {
+    let mut parsed = Parsed::default();
+    let mut problems = Vec::new();
+    // Details for the next path, and the line they started on.
+    let mut pending: Option<(usize, Entry)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.eq_ignore_ascii_case("#EXTM3U") {
+            continue;
+        }
+        if let Some(info) = directive(line, "#EXTINF:") {
+            problems.extend(dangling(&pending));
+            match parse_info(info) {
+                Ok((duration, title)) => {
+                    let artist = pending.take().and_then(|(_, entry)| entry.artist);
+                    pending = Some((number, Entry { duration, title: Some(title), artist, ..Entry::default() }));
+                }
+                Err(message) => {
+                    problems.push(Problem { line: number, message });
+                    pending = None;
+                }
+            }
+        } else if let Some(artist) = directive(line, "#EXTART:") {
+            let (_, entry) = pending.get_or_insert_with(|| (number, Entry::default()));
+            entry.artist = Some(artist.trim().to_owned()).filter(|artist| !artist.is_empty());
+        } else if let Some(name) = directive(line, "#PLAYLIST:") {
+            parsed.name = Some(name.trim().to_owned());
+        } else if line.starts_with('#') {
+            continue;
+        } else {
+            let (_, mut entry) = pending.take().unwrap_or_default();
+            if let (Some(artist), Some(title)) = (&entry.artist, &mut entry.title) {
+                // The line was trimmed, so an empty title leaves "Artist -".
+                match title.strip_prefix(artist.as_str()) {
+                    Some(" -") => title.clear(),
+                    Some(rest) => {
+                        if let Some(rest) = rest.strip_prefix(" - ") {
+                            *title = rest.to_owned();
+                        }
+                    }
+                    None => {}
+                }
+            }
+            entry.source = line.to_owned();
+            parsed.entries.push(entry);
+        }
+    }
+    problems.extend(dangling(&pending));
+    if problems.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(FormatError { problems })
+    }
+}
+
+pub(super) fn write(playlist: &Playlist, base: &str) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    if !playlist.name.is_empty() {
+        let _ = writeln!(out, "#PLAYLIST:{}", one_line(&playlist.name));
+    }
+    for track in playlist.tracks() {
+        let length = track.duration.map_or_else(|| "-1".to_owned(), seconds);
+        let title = one_line(&track.title);
+        match &track.artist {
+            Some(artist) => {
+                let artist = one_line(artist);
+                let _ = writeln!(out, "#EXTINF:{length},{artist} - {title}");
+                let _ = writeln!(out, "#EXTART:{artist}");
+            }
+            None => {
+                let _ = writeln!(out, "#EXTINF:{length},{title}");
+            }
+        }
+        let _ = writeln!(out, "{}", relative(base, &track.source));
+    }
+    out
+}
+
+/// The rest of `line` after `name`, which may be in any case.
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let head = line.get(..name.len())?;
+    head.eq_ignore_ascii_case(name).then(|| &line[name.len()..])
+}
+
+/// `#EXTINF:<seconds> [attributes],<title>`. Attributes, as IPTV lists
+/// use, are skipped.
+fn parse_info(info: &str) -> Result<(Option<std::time::Duration>, String), String> {
+    let Some((length, title)) = info.split_once(',') else {
+        return Err("#EXTINF needs a comma between the length and the title".to_owned());
+    };
+    let length = length.split_whitespace().next().unwrap_or("");
+    let duration = parse_seconds(length).map_err(|e| format!("#EXTINF length {e}"))?;
+    Ok((duration, title.trim().to_owned()))
+}
+
+/// An `#EXTINF` that another one, or the end of the file, came before its
+/// path. An `#EXTART` on its own just waits for the next `#EXTINF`.
+fn dangling(pending: &Option<(usize, Entry)>) -> Option<Problem> {
+    let (line, entry) = pending.as_ref()?;
+    entry.title.is_some().then(|| Problem { line: *line, message: "#EXTINF has no path after it".to_owned() })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(text: &str) -> Vec<(usize, String)> {
+        let error = read(text).err().expect("should not parse");
+        error.problems.into_iter().map(|problem| (problem.line, problem.message)).collect()
+    }
+
+    #[test]
+    fn extinf_needs_a_length_and_a_comma() {
+        assert_eq!(
+            problems("#EXTM3U\n#EXTINF:abc,Title\na.mp3\n#EXTINF:10 Title\nb.mp3\n"),
+            [
+                (2, "#EXTINF length \"abc\" isn't a number of seconds".to_owned()),
+                (4, "#EXTINF needs a comma between the length and the title".to_owned()),
+            ],
+        );
+    }
+
+    #[test]
+    fn extinf_needs_a_path_after_it() {
+        assert_eq!(
+            problems("#EXTINF:1,One\n#EXTINF:2,Two\nb.mp3\n\n#EXTINF:3,Three\n"),
+            [
+                (1, "#EXTINF has no path after it".to_owned()),
+                (5, "#EXTINF has no path after it".to_owned()),
+            ],
+        );
+    }
+
+    #[test]
+    fn artist_is_split_off_the_title() {
+        let parsed = read("#EXTINF:-1 tvg-id=\"x\",Ana - Rain\n#EXTART:Ana\nrain.ogg\n#EXTINF:5,Bo - Live\nlive.ogg\n").unwrap();
+        let [rain, live] = parsed.entries.as_slice() else { panic!("two entries") };
+        assert_eq!((rain.title.as_deref(), rain.artist.as_deref(), rain.duration), (Some("Rain"), Some("Ana"), None));
+        // Without #EXTART there's no telling, so the title is left whole.
+        assert_eq!((live.title.as_deref(), live.artist.as_deref()), (Some("Bo - Live"), None));
+    }
+}
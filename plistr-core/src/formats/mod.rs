@@ -0,0 +1,364 @@
+//! Playlist files other players use: M3U and M3U8 (with `#EXTINF`), PLS
+//! and XSPF. Writing a `Playlist` then reading it back keeps its name and
+//! every track's source, title (even an empty one), artist and duration (to
+//! the millisecond) in all four, and the description in XSPF.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::{Playlist, Track, TrackId};
+
+mod m3u;
+mod pls;
+mod xspf;
+
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PlaylistFormat {
+    M3u,
+    /// M3U that is always UTF-8.
+    M3u8,
+    Pls,
+    Xspf,
+}
+
+/// Everything wrong with a playlist file, first to last. A file with any
+/// problem adds nothing.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FormatError {
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Problem {
+    /// Counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+/// A track as the file has it, before it gets an id.
+#[derive(Default)]
+struct Entry {
+    source: String,
+    /// `None` when the file has no title for it, which isn't the same as an
+    /// empty one.
+    title: Option<String>,
+    artist: Option<String>,
+    duration: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Parsed {
+    name: Option<String>,
+    description: Option<String>,
+    entries: Vec<Entry>,
+}
+
+impl PlaylistFormat {
+    pub const ALL: [PlaylistFormat; 4] = [
+        PlaylistFormat::M3u,
+        PlaylistFormat::M3u8,
+        PlaylistFormat::Pls,
+        PlaylistFormat::Xspf,
+    ];
+
+    /// Without the dot, in any case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// From the extension of a file name, path or URL.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let (_, extension) = file_name(path).rsplit_once('.')?;
+        Self::from_extension(extension)
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u => "m3u",
+            PlaylistFormat::M3u8 => "m3u8",
+            PlaylistFormat::Pls => "pls",
+            PlaylistFormat::Xspf => "xspf",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u => "M3U",
+            PlaylistFormat::M3u8 => "M3U8",
+            PlaylistFormat::Pls => "PLS",
+            PlaylistFormat::Xspf => "XSPF",
+        }
+    }
+
+    /// Adds the file's tracks to the end of `playlist`, taking the file's
+    /// name and description too if it has them. Relative paths are resolved
+    /// against `base`, the folder or URL the file is in; `new_id` numbers
+    /// the tracks.
+    pub fn read(
+        self,
+        text: &str,
+        base: &str,
+        playlist: &mut Playlist,
+        mut new_id: impl FnMut() -> TrackId,
+    ) -> Result<(), FormatError> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let parsed = match self {
+            PlaylistFormat::M3u | PlaylistFormat::M3u8 => m3u::read(text),
+            PlaylistFormat::Pls => pls::read(text),
+            PlaylistFormat::Xspf => xspf::read(text),
+        }?;
+        if let Some(name) = parsed.name {
+            playlist.name = name;
+        }
+        if let Some(description) = parsed.description {
+            playlist.description = description;
+        }
+        for entry in parsed.entries {
+            let source = resolve(base, &entry.source);
+            let title = entry.title.unwrap_or_else(|| default_title(&source));
+            let mut track = Track::new(new_id(), title, source);
+            track.artist = entry.artist;
+            track.duration = entry.duration;
+            playlist.add(track);
+        }
+        Ok(())
+    }
+
+    /// Sources inside `base` are written relative to it, so the file still
+    /// works if the folder moves.
+    pub fn write(self, playlist: &Playlist, base: &str) -> String {
+        match self {
+            PlaylistFormat::M3u | PlaylistFormat::M3u8 => m3u::write(playlist, base),
+            PlaylistFormat::Pls => pls::write(playlist, base),
+            PlaylistFormat::Xspf => xspf::write(playlist, base),
+        }
+    }
+}
+
+impl fmt::Display for PlaylistFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FormatError {
+    fn at(line: usize, message: impl Into<String>) -> Self {
+        Self { problems: vec![Problem { line, message: message.into() }] }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, problem) in self.problems.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Playlist files from Windows players are often Windows-1252 rather than
+/// UTF-8. Anything that isn't valid UTF-8 is read as Latin-1, which
+/// matches it for letters.
+pub fn decode_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_owned(),
+        Err(_) => bytes.iter().map(|&byte| char::from(byte)).collect(),
+    }
+}
+
+/// `location` as the player needs it: unchanged if it's already absolute,
+/// otherwise joined onto `base`.
+fn resolve(base: &str, location: &str) -> String {
+    if base.is_empty() || is_absolute(location) {
+        return location.to_owned();
+    }
+    let separator = separator(base);
+    let location = if separator == '/' { location.replace('\\', "/") } else { location.to_owned() };
+    format!("{}{separator}{location}", base.trim_end_matches(['/', '\\']))
+}
+
+/// The reverse of `resolve`, for sources under `base`.
+fn relative(base: &str, source: &str) -> String {
+    let base = base.trim_end_matches(['/', '\\']);
+    if base.is_empty() {
+        return source.to_owned();
+    }
+    match source.strip_prefix(base) {
+        Some(rest) if rest.starts_with(['/', '\\']) => rest[1..].to_owned(),
+        _ => source.to_owned(),
+    }
+}
+
+/// URLs, and paths from the root or a drive.
+fn is_absolute(location: &str) -> bool {
+    let bytes = location.as_bytes();
+    location.contains("://")
+        || location.starts_with(['/', '\\'])
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// Windows paths keep their backslashes.
+fn separator(base: &str) -> char {
+    if base.contains('\\') && !base.contains('/') { '\\' } else { '/' }
+}
+
+fn file_name(source: &str) -> &str {
+    // A URL's query and fragment aren't part of the name; a path's `#` is.
+    let source = match source.contains("://") {
+        true => source.split(['?', '#']).next().unwrap_or(source),
+        false => source,
+    };
+    source.rsplit(['/', '\\']).next().unwrap_or(source)
+}
+
+/// The file name without its extension, for entries with no title.
+fn default_title(source: &str) -> String {
+    let name = file_name(source);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    if stem.is_empty() { source.to_owned() } else { stem.to_owned() }
+}
+
+/// Seconds, whole where possible and to the millisecond otherwise.
+fn seconds(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis.is_multiple_of(1000) {
+        (millis / 1000).to_string()
+    } else {
+        format!("{}.{:03}", millis / 1000, millis % 1000)
+    }
+}
+
+/// `-1` and other negatives mean the length isn't known.
+fn parse_seconds(text: &str) -> Result<Option<Duration>, String> {
+    let seconds: f64 = text.trim().parse().map_err(|_| format!("{:?} isn't a number of seconds", text.trim()))?;
+    if !seconds.is_finite() {
+        return Err(format!("{:?} isn't a number of seconds", text.trim()));
+    }
+    Ok((seconds >= 0.0).then(|| Duration::from_millis((seconds * 1000.0).round() as u64)))
+}
+
+/// Titles and names are one line in every format.
+fn one_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PlaylistId, UserId};
+
+    fn track(id: u64, title: &str, artist: Option<&str>, seconds: Option<f64>, source: &str) -> Track {
+        let mut track = Track::new(TrackId(id), title, source);
+        track.artist = artist.map(str::to_owned);
+        track.duration = seconds.map(Duration::from_secs_f64);
+        track
+    }
+
+    /// Everything a format could trip over: empty titles with and without an
+    /// artist, a title that starts with its artist, characters each format
+    /// escapes, and sources inside and outside the playlist's folder.
+    fn awkward() -> Playlist {
+        let mut playlist = Playlist::new(PlaylistId(1), UserId(1), "Focus & <Flow> = 1");
+        playlist.description = "For the exam".to_owned();
+        playlist.add(track(1, "Rain", Some("Ana"), Some(61.5), "/music/rain.ogg"));
+        playlist.add(track(2, "", None, None, "https://example.com/a%20b.mp3?x=1"));
+        playlist.add(track(3, "", Some("Bo"), Some(3.0), "/elsewhere/b c.flac"));
+        playlist.add(track(4, "Bo - Live", Some("Bo"), Some(0.001), "/music/live.mp3"));
+        playlist.add(track(5, "Ünïcode, & <tags> = #1", Some("Çé"), None, "/music/sub/ü.ogg"));
+        playlist
+    }
+
+    fn read(format: PlaylistFormat, text: &str, base: &str) -> Result<Playlist, FormatError> {
+        let mut playlist = Playlist::new(PlaylistId(1), UserId(1), "");
+        let mut next = 0;
+        format.read(text, base, &mut playlist, || {
+            next += 1;
+            TrackId(next)
+        })?;
+        Ok(playlist)
+    }
+
+    fn assert_round_trip(format: PlaylistFormat) {
+        let original = awkward();
+        let text = format.write(&original, "/music");
+        let read = read(format, &text, "/music").unwrap_or_else(|e| panic!("{format}: {e}\n{text}"));
+        assert_eq!(read.name, original.name, "{format}:\n{text}");
+        assert_eq!(read.tracks(), original.tracks(), "{format}:\n{text}");
+        if format == PlaylistFormat::Xspf {
+            assert_eq!(read.description, original.description, "{format}:\n{text}");
+        }
+    }
+
+    #[test]
+    fn m3u_round_trips() {
+        assert_round_trip(PlaylistFormat::M3u);
+    }
+
+    #[test]
+    fn m3u8_round_trips() {
+        assert_round_trip(PlaylistFormat::M3u8);
+    }
+
+    #[test]
+    fn pls_round_trips() {
+        assert_round_trip(PlaylistFormat::Pls);
+    }
+
+    #[test]
+    fn xspf_round_trips() {
+        assert_round_trip(PlaylistFormat::Xspf);
+    }
+
+    #[test]
+    fn sources_inside_the_folder_are_written_relative_to_it() {
+        let text = PlaylistFormat::M3u.write(&awkward(), "/music");
+        assert!(text.lines().any(|line| line == "rain.ogg"), "{text}");
+        assert!(text.lines().any(|line| line == "sub/ü.ogg"), "{text}");
+        assert!(text.lines().any(|line| line == "/elsewhere/b c.flac"), "{text}");
+    }
+
+    #[test]
+    fn tracks_without_a_title_are_named_after_their_file() {
+        for (format, text) in [
+            (PlaylistFormat::M3u, "C:\\Music\\One.mp3\n"),
+            (PlaylistFormat::Pls, "[playlist]\nFile1=C:\\Music\\One.mp3\n"),
+            (PlaylistFormat::Xspf, "<playlist><trackList><track><location>file:///C:/Music/One.mp3</location></track></trackList></playlist>"),
+        ] {
+            let playlist = read(format, text, "").unwrap();
+            assert_eq!(playlist.tracks()[0].title, "One", "{format}");
+        }
+    }
+
+    #[test]
+    fn a_file_with_problems_adds_nothing() {
+        let mut playlist = awkward();
+        let before = playlist.clone();
+        let result = PlaylistFormat::M3u.read("#EXTINF:x,Broken\na.mp3\n", "", &mut playlist, || TrackId(99));
+        assert!(result.is_err());
+        assert_eq!(playlist, before);
+    }
+
+    #[test]
+    fn errors_name_their_lines() {
+        let error = FormatError {
+            problems: vec![
+                Problem { line: 2, message: "first".to_owned() },
+                Problem { line: 7, message: "second".to_owned() },
+            ],
+        };
+        assert_eq!(error.to_string(), "line 2: first\nline 7: second");
+    }
+}
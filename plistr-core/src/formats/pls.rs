@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use super::{one_line, parse_seconds, relative, seconds, Entry, FormatError, Parsed, Problem};
+use crate::Playlist;
+
+
+/// An INI-style `[playlist]` section of numbered `FileN`, `TitleN` and
+/// `LengthN` keys. `ArtistN` and the playlist's `X-Name` are Plistr's own;
+/// other players skip keys they don't know, as this does.
+pub(super) fn read(text: &str) -> Result<Parsed, FormatError> {
+    let mut problems = Vec::new();
+    let mut name = None;
+    let mut section = false;
+    // Entry number to the entry and the line of its first key.
+    let mut entries: BTreeMap<u32, (usize, Entry)> = BTreeMap::new();
+    let mut counted: Option<(usize, usize)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with([';', '#']) {
+            continue;
+        }
+        if !section {
+            if line.eq_ignore_ascii_case("[playlist]") {
+                section = true;
+            } else {
+                return Err(FormatError::at(number, "PLS files start with [playlist]"));
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            problems.push(Problem { line: number, message: format!("expected key=value, got {line:?}") });
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.eq_ignore_ascii_case("NumberOfEntries") {
+            match value.parse() {
+                Ok(count) => counted = Some((number, count)),
+                Err(_) => problems.push(Problem { line: number, message: format!("NumberOfEntries {value:?} isn't a number") }),
+            }
+            continue;
+        }
+        if key.eq_ignore_ascii_case(NAME) {
+            name = Some(value.to_owned());
+            continue;
+        }
+        let Some((field, index)) = numbered(key) else {
+            continue;
+        };
+        let field = field.to_ascii_lowercase();
+        if !matches!(field.as_str(), "file" | "title" | "artist" | "length") {
+            continue;
+        }
+        let Ok(index) = index.parse::<u32>() else {
+            problems.push(Problem { line: number, message: format!("{key} should end in an entry number") });
+            continue;
+        };
+        let (_, entry) = entries.entry(index).or_insert_with(|| (number, Entry::default()));
+        match field.as_str() {
+            "file" => entry.source = value.to_owned(),
+            "title" => entry.title = Some(value.to_owned()),
+            "artist" => entry.artist = Some(value.to_owned()).filter(|artist| !artist.is_empty()),
+            "length" => match parse_seconds(value) {
+                Ok(duration) => entry.duration = duration,
+                Err(e) => problems.push(Problem { line: number, message: format!("{key} {e}") }),
+            },
+            _ => {}
+        }
+    }
+    if !section {
+        return Err(FormatError::at(1, "PLS files start with [playlist]"));
+    }
+    for (index, (line, entry)) in &entries {
+        if entry.source.is_empty() {
+            problems.push(Problem { line: *line, message: format!("entry {index} has no File{index}") });
+        }
+    }
+    if let Some((line, count)) = counted {
+        if count != entries.len() {
+            problems.push(Problem {
+                line,
+                message: format!("NumberOfEntries says {count}, but there are {}", entries.len()),
+            });
+        }
+    }
+    if !problems.is_empty() {
+        problems.sort_by_key(|problem| problem.line);
+        return Err(FormatError { problems });
+    }
+    Ok(Parsed {
+        name,
+        entries: entries.into_values().map(|(_, entry)| entry).collect(),
+        ..Parsed::default()
+    })
+}
+
+pub(super) fn write(playlist: &Playlist, base: &str) -> String {
+    let mut out = String::from("[playlist]\n");
+    if !playlist.name.is_empty() {
+        let _ = writeln!(out, "{NAME}={}", one_line(&playlist.name));
+    }
+    for (i, track) in playlist.tracks().iter().enumerate() {
+        let n = i + 1;
+        let _ = writeln!(out, "File{n}={}", relative(base, &track.source));
+        let _ = writeln!(out, "Title{n}={}", one_line(&track.title));
+        let _ = writeln!(out, "Length{n}={}", track.duration.map_or_else(|| "-1".to_owned(), seconds));
+        if let Some(artist) = &track.artist {
+            let _ = writeln!(out, "Artist{n}={}", one_line(artist));
+        }
+    }
+    let _ = writeln!(out, "NumberOfEntries={}", playlist.len());
+    out.push_str("Version=2\n");
+    out
+}
+
+/// Splits `Title12` into `("Title", "12")`. Keys without a number, like
+/// `Version`, give `None`.
+fn numbered(key: &str) -> Option<(&str, &str)> {
+    let split = key.find(|c: char| c.is_ascii_digit())?;
+    Some(key.split_at(split)).filter(|(field, _)| !field.is_empty())
+}
+
+
+const NAME: &str = "X-Name";
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(text: &str) -> Vec<(usize, String)> {
+        let error = read(text).err().expect("should not parse");
+        error.problems.into_iter().map(|problem| (problem.line, problem.message)).collect()
+    }
+
+    #[test]
+    fn the_playlist_section_comes_first() {
+        assert_eq!(problems("; exported\nFile1=a.mp3\n"), [(2, "PLS files start with [playlist]".to_owned())]);
+        assert_eq!(problems("\n"), [(1, "PLS files start with [playlist]".to_owned())]);
+    }
+
+    #[test]
+    fn every_problem_is_reported_in_line_order() {
+        let text = "[playlist]\nFile1=a.mp3\nLength1=soon\nTitle2=No file\nNumberOfEntries=3\nnonsense\nFileX=b.mp3\n";
+        assert_eq!(
+            problems(text),
+            [
+                (3, "Length1 \"soon\" isn't a number of seconds".to_owned()),
+                (4, "entry 2 has no File2".to_owned()),
+                (5, "NumberOfEntries says 3, but there are 2".to_owned()),
+                (6, "expected key=value, got \"nonsense\"".to_owned()),
+            ],
+        );
+    }
+
+    #[test]
+    fn entries_are_in_number_order_and_unknown_keys_are_skipped() {
+        let parsed = read("[playlist]\nX-Name=Mix\nFile2=b.mp3\nFile1=a.mp3\nTitle1=\nPlayerSkin=dark\nVersion=2\n").unwrap();
+        assert_eq!(parsed.name.as_deref(), Some("Mix"));
+        let sources: Vec<_> = parsed.entries.iter().map(|entry| entry.source.as_str()).collect();
+        assert_eq!(sources, ["a.mp3", "b.mp3"]);
+        assert_eq!(parsed.entries[0].title.as_deref(), Some(""));
+        assert_eq!(parsed.entries[1].title, None);
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use super::{is_absolute, one_line, relative, separator, Entry, FormatError, Parsed, Problem};
+use crate::Playlist;
+
+
+/// XML Shareable Playlist Format. Locations are URIs: local files are
+/// `file://` URLs, or relative references from the playlist's folder.
+pub(super) fn read(text: &str) -> Result<Parsed, FormatError> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut parsed = Parsed::default();
+    let mut problems = Vec::new();
+    // Names of the open elements, outermost first.
+    let mut path: Vec<String> = Vec::new();
+    // The track being read, and the line it started on.
+    let mut track: Option<(usize, Entry)> = None;
+    loop {
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e) => {
+                problems.push(Problem { line: line_at(text, reader.error_position()), message: e.to_string() });
+                break;
+            }
+        };
+        match event {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                if path.is_empty() && name != "playlist" {
+                    let line = line_at(text, reader.buffer_position());
+                    return Err(FormatError::at(line, format!("expected a <playlist>, found <{name}>")));
+                }
+                if name == "track" && path.last().is_some_and(|parent| parent == "trackList") {
+                    track = Some((line_at(text, reader.buffer_position()), Entry::default()));
+                }
+                // `<title></title>` has no text at all, but is still a title.
+                if name == "title" && path.last().is_some_and(|parent| parent == "track") {
+                    if let Some((_, entry)) = &mut track {
+                        entry.title = Some(String::new());
+                    }
+                }
+                path.push(name);
+            }
+            Event::Text(content) => {
+                let value = match content.unescape() {
+                    Ok(value) => value.into_owned(),
+                    Err(e) => {
+                        problems.push(Problem { line: line_at(text, reader.buffer_position()), message: e.to_string() });
+                        continue;
+                    }
+                };
+                let line = line_at(text, reader.buffer_position());
+                let names: Vec<&str> = path.iter().map(String::as_str).collect();
+                match (names.as_slice(), &mut track) {
+                    (["playlist", "title"], _) => parsed.name = Some(value),
+                    (["playlist", "annotation"], _) => parsed.description = Some(value),
+                    ([.., "track", "location"], Some((_, entry))) if entry.source.is_empty() => {
+                        entry.source = from_uri(&value);
+                    }
+                    ([.., "track", "title"], Some((_, entry))) => entry.title = Some(value),
+                    ([.., "track", "creator"], Some((_, entry))) => entry.artist = Some(value),
+                    ([.., "track", "duration"], Some((_, entry))) => match value.parse() {
+                        Ok(millis) => entry.duration = Some(Duration::from_millis(millis)),
+                        Err(_) => problems.push(Problem {
+                            line,
+                            message: format!("<duration> should be whole milliseconds, got {value:?}"),
+                        }),
+                    },
+                    _ => {}
+                }
+            }
+            Event::End(_) => {
+                let name = path.pop();
+                if name.as_deref() == Some("track") && path.last().is_some_and(|parent| parent == "trackList") {
+                    if let Some((line, entry)) = track.take() {
+                        if entry.source.is_empty() {
+                            problems.push(Problem { line, message: "<track> has no <location>".to_owned() });
+                        } else {
+                            parsed.entries.push(entry);
+                        }
+                    }
+                }
+            }
+            Event::Empty(empty) if path.last().is_some_and(|parent| parent == "track") => {
+                if let (b"title", Some((_, entry))) = (empty.local_name().as_ref(), &mut track) {
+                    entry.title = Some(String::new());
+                }
+            }
+            Event::Empty(empty) if path.is_empty() => {
+                let name = String::from_utf8_lossy(empty.local_name().as_ref()).into_owned();
+                if name != "playlist" {
+                    let line = line_at(text, reader.buffer_position());
+                    return Err(FormatError::at(line, format!("expected a <playlist>, found <{name}>")));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if problems.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(FormatError { problems })
+    }
+}
+
+pub(super) fn write(playlist: &Playlist, base: &str) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n");
+    if !playlist.name.is_empty() {
+        let _ = writeln!(out, "  <title>{}</title>", escape(one_line(&playlist.name)));
+    }
+    if !playlist.description.is_empty() {
+        let _ = writeln!(out, "  <annotation>{}</annotation>", escape(playlist.description.as_str()));
+    }
+    out.push_str("  <trackList>\n");
+    for track in playlist.tracks() {
+        out.push_str("    <track>\n");
+        let _ = writeln!(out, "      <location>{}</location>", escape(to_uri(base, &track.source)));
+        let _ = writeln!(out, "      <title>{}</title>", escape(one_line(&track.title)));
+        if let Some(artist) = &track.artist {
+            let _ = writeln!(out, "      <creator>{}</creator>", escape(one_line(artist)));
+        }
+        if let Some(duration) = track.duration {
+            let _ = writeln!(out, "      <duration>{}</duration>", duration.as_millis());
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+    out
+}
+
+/// A path from a `file://` URL or relative reference; other URLs as they
+/// are.
+fn from_uri(location: &str) -> String {
+    if let Some(path) = location.strip_prefix("file://") {
+        // `file:///C:/Music` is `C:/Music`; `file:///home` is `/home`.
+        let path = path.strip_prefix("localhost").unwrap_or(path);
+        let path = percent_decode(path);
+        let bytes = path.as_bytes();
+        if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+            return path[1..].to_owned();
+        }
+        return path;
+    }
+    if location.contains("://") {
+        return location.to_owned();
+    }
+    percent_decode(location)
+}
+
+fn to_uri(base: &str, source: &str) -> String {
+    if source.contains("://") {
+        return source.to_owned();
+    }
+    let relative = relative(base, source);
+    if !is_absolute(&relative) {
+        return percent_encode(&relative.replace('\\', "/"));
+    }
+    let path = if separator(&relative) == '\\' { relative.replace('\\', "/") } else { relative };
+    let root = if path.starts_with('/') { "" } else { "/" };
+    format!("file://{root}{}", percent_encode(&path))
+}
+
+/// Escapes everything a URI path can't hold as it is.
+fn percent_encode(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for &byte in path.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/!$&'()*+,;=:@".contains(&byte) {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The line number of a byte offset into `text`.
+fn line_at(text: &str, position: u64) -> usize {
+    let end = (position as usize).min(text.len());
+    text.as_bytes()[..end].iter().filter(|&&byte| byte == b'\n').count() + 1
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(text: &str) -> Vec<(usize, String)> {
+        let error = read(text).err().expect("should not parse");
+        error.problems.into_iter().map(|problem| (problem.line, problem.message)).collect()
+    }
+
+    #[test]
+    fn the_root_is_a_playlist() {
+        assert_eq!(
+            problems("<?xml version=\"1.0\"?>\n<rss>\n</rss>\n"),
+            [(2, "expected a <playlist>, found <rss>".to_owned())],
+        );
+    }
+
+    #[test]
+    fn tracks_need_a_location_and_whole_milliseconds() {
+        let text = "<playlist>\n<trackList>\n<track>\n<title>Lost</title>\n</track>\n<track>\n<location>a.ogg</location>\n<duration>1.5</duration>\n</track>\n</trackList>\n</playlist>\n";
+        assert_eq!(
+            problems(text),
+            [
+                (3, "<track> has no <location>".to_owned()),
+                (8, "<duration> should be whole milliseconds, got \"1.5\"".to_owned()),
+            ],
+        );
+    }
+
+    #[test]
+    fn broken_xml_is_reported_where_it_breaks() {
+        let problems = problems("<playlist>\n<trackList>\n<track>\n</trackList>\n</playlist>\n");
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert_eq!(problems[0].0, 4, "{problems:?}");
+    }
+
+    #[test]
+    fn locations_are_uris() {
+        assert_eq!(from_uri("file:///C:/My%20Music/a.mp3"), "C:/My Music/a.mp3");
+        assert_eq!(from_uri("file://localhost/home/a%23b.ogg"), "/home/a#b.ogg");
+        assert_eq!(from_uri("https://example.com/a%20b.mp3"), "https://example.com/a%20b.mp3");
+        assert_eq!(to_uri("/music", "/music/sub/a b.ogg"), "sub/a%20b.ogg");
+        assert_eq!(to_uri("", "C:\\Music\\a.mp3"), "file:///C:/Music/a.mp3");
+    }
+}
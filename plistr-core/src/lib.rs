@@ -0,0 +1,49 @@
+//! The Plistr app's data: tracks, the playlists built from them, the people
+//! who make them, the study or meditation sessions they're played in, the
+//! focus timer that paces them and the soundscapes generated to go under
+//! them. Playlists can be read from and written to other players' files,
+//! and tracks picked from the library of the user's own. A `Repository`
+//! keeps all of it, with the history of what was played, between runs, and
+//! a `SocialClient` shares playlists and sessions with friends, on a mock
+//! server or a real `plistr-server`, whose API bodies are here. Study rooms
+//! keep several people's players and timers in step through a `Relay`.
+//! Nothing here knows about egui, so the website, the app and any tooling
+//! can share it.
+
+mod api;
+mod focus;
+mod formats;
+mod library;
+mod playlist;
+mod repository;
+mod room;
+mod session;
+mod social;
+mod soundscape;
+mod tag;
+mod track;
+mod user;
+
+#[cfg(feature = "client")]
+pub use api::{ApiClient, Credentials};
+pub use api::{decode_query, encode_query, Account, ErrorBody, NewComment, NewPost, ProfileUpdate, SignUp};
+pub use focus::{Clock, FocusSettings, FocusSnapshot, FocusTimer, ManualClock, Phase};
+pub use formats::{decode_text, FormatError, PlaylistFormat, Problem};
+pub use library::{Library, LibraryColumn, LibraryEntry};
+pub use playlist::{Playlist, PlaylistError, PlaylistId};
+#[cfg(feature = "sqlite")]
+pub use repository::SqliteRepository;
+pub use repository::{MemoryRepository, Play, Repository, RepositoryError};
+pub use room::{
+    ClientMessage, ClockSync, Command, ConnectionId, Member, MemberId, Relay, RoomClient, RoomError, RoomState,
+    ServerMessage, SharedTimer, TimerCommand, Transport,
+};
+pub use session::{Session, SessionId, SessionKind};
+pub use social::{
+    valid_handle, Comment, MockClient, MockServer, Post, PostId, Profile, Reply, Shared, SocialClient, SocialError,
+    MAX_TEXT,
+};
+pub use soundscape::{Generator, Layer, Sound, Soundscape};
+pub use tag::{Tag, TagError};
+pub use track::{Track, TrackId};
+pub use user::{User, UserId};
@@ -0,0 +1,206 @@
+use std::cmp::Reverse;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Track, TrackId};
+
+
+/// The audio files found in the user's music folders, in path order. Kept
+/// between runs so a rescan only has to read the files that changed.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+    entries: Vec<LibraryEntry>,
+}
+
+/// One file and what its tags say.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub path: String,
+    /// Seconds since the Unix epoch. With `size`, what a rescan checks to
+    /// decide whether the file needs reading again.
+    pub modified: u64,
+    pub size: u64,
+    /// The file name when the tags have no title.
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+    /// The file name of the embedded cover. Covers are saved beside the
+    /// index rather than in it, once for every track that shares one.
+    pub art: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LibraryColumn {
+    Title,
+    Artist,
+    Album,
+    Duration,
+}
+
+impl Library {
+    pub fn new(mut entries: Vec<LibraryEntry>) -> Self {
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries.dedup_by(|a, b| a.path == b.path);
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[LibraryEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry for `path` if the file hasn't been touched since it was
+    /// read.
+    pub fn unchanged(&self, path: &str, modified: u64, size: u64) -> Option<&LibraryEntry> {
+        let index = self.entries.binary_search_by(|entry| entry.path.as_str().cmp(path)).ok()?;
+        let entry = &self.entries[index];
+        (entry.modified == modified && entry.size == size).then_some(entry)
+    }
+
+    /// Indices into `entries` whose title, artist or album contain every
+    /// word of `query`, ignoring case, sorted on `column`. Ties stay in path
+    /// order, which keeps an album's tracks together.
+    pub fn search(&self, query: &str, column: LibraryColumn, ascending: bool) -> Vec<usize> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut found: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| words.is_empty() || self.entries[i].matches(&words))
+            .collect();
+        let entries = &self.entries;
+        match column {
+            LibraryColumn::Title => sort(&mut found, ascending, |i| entries[i].title.to_lowercase()),
+            LibraryColumn::Artist => sort(&mut found, ascending, |i| lowercase(&entries[i].artist)),
+            LibraryColumn::Album => sort(&mut found, ascending, |i| lowercase(&entries[i].album)),
+            LibraryColumn::Duration => sort(&mut found, ascending, |i| entries[i].duration),
+        }
+        found
+    }
+}
+
+impl LibraryEntry {
+    pub fn track(&self, id: TrackId) -> Track {
+        let mut track = Track::new(id, self.title.clone(), self.path.clone());
+        track.artist = self.artist.clone();
+        track.duration = self.duration;
+        track
+    }
+
+    /// `words` are already lowercase.
+    fn matches(&self, words: &[String]) -> bool {
+        let fields: Vec<String> = [Some(&self.title), self.artist.as_ref(), self.album.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|field| field.to_lowercase())
+            .collect();
+        words.iter().all(|word| fields.iter().any(|field| field.contains(word.as_str())))
+    }
+}
+
+fn sort<K: Ord>(indices: &mut [usize], ascending: bool, key: impl Fn(usize) -> K) {
+    if ascending {
+        indices.sort_by_cached_key(|&i| key(i));
+    } else {
+        indices.sort_by_cached_key(|&i| Reverse(key(i)));
+    }
+}
+
+/// Missing values sort before everything, like an empty string.
+fn lowercase(field: &Option<String>) -> String {
+    field.as_deref().map(str::to_lowercase).unwrap_or_default()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, title: &str, artist: Option<&str>, album: Option<&str>, seconds: Option<u64>) -> LibraryEntry {
+        LibraryEntry {
+            path: path.to_owned(),
+            modified: 100,
+            size: 2_000,
+            title: title.to_owned(),
+            artist: artist.map(str::to_owned),
+            album: album.map(str::to_owned),
+            duration: seconds.map(Duration::from_secs),
+            art: None,
+        }
+    }
+
+    fn library() -> Library {
+        Library::new(vec![
+            entry("/music/c.ogg", "Rain", Some("Nils Frahm"), Some("Spaces"), Some(240)),
+            entry("/music/a.ogg", "says", Some("Nils Frahm"), Some("Spaces"), Some(500)),
+            entry("/music/d.ogg", "Intro", None, None, None),
+            entry("/music/b.ogg", "Ambre", Some("Nils Frahm"), Some("Wintermusik"), Some(240)),
+        ])
+    }
+
+    fn paths(library: &Library, found: &[usize]) -> Vec<String> {
+        found.iter().map(|&i| library.entries()[i].path.replace("/music/", "")).collect()
+    }
+
+    #[test]
+    fn new_sorts_by_path_and_keeps_the_first_of_each() {
+        let library = Library::new(vec![
+            entry("/b.ogg", "B", None, None, None),
+            entry("/a.ogg", "First", None, None, None),
+            entry("/a.ogg", "Second", None, None, None),
+        ]);
+        let titles: Vec<&str> = library.entries().iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["First", "B"]);
+        assert_eq!(library.len(), 2);
+        assert!(Library::default().is_empty());
+    }
+
+    #[test]
+    fn unchanged_needs_the_same_time_and_size() {
+        let library = library();
+        assert_eq!(library.unchanged("/music/b.ogg", 100, 2_000).map(|entry| entry.title.as_str()), Some("Ambre"));
+        assert_eq!(library.unchanged("/music/b.ogg", 101, 2_000), None);
+        assert_eq!(library.unchanged("/music/b.ogg", 100, 2_001), None);
+        assert_eq!(library.unchanged("/music/e.ogg", 100, 2_000), None);
+    }
+
+    #[test]
+    fn search_wants_every_word_in_any_field() {
+        let library = library();
+        let search = |query| paths(&library, &library.search(query, LibraryColumn::Title, true));
+        assert_eq!(search(""), ["b.ogg", "d.ogg", "c.ogg", "a.ogg"]);
+        assert_eq!(search("  "), search(""));
+        assert_eq!(search("FRAHM spaces"), ["c.ogg", "a.ogg"]);
+        assert_eq!(search("frahm winter"), ["b.ogg"]);
+        assert_eq!(search("rain intro"), Vec::<String>::new());
+        // "Ambre" is only found by its album.
+        assert_eq!(search("in"), ["b.ogg", "d.ogg", "c.ogg"]);
+    }
+
+    #[test]
+    fn each_column_sorts_with_ties_in_path_order() {
+        let library = library();
+        let sorted = |column, ascending| paths(&library, &library.search("", column, ascending));
+        assert_eq!(sorted(LibraryColumn::Title, false), ["a.ogg", "c.ogg", "d.ogg", "b.ogg"]);
+        // Missing artists and albums come first, like empty ones.
+        assert_eq!(sorted(LibraryColumn::Artist, true), ["d.ogg", "a.ogg", "b.ogg", "c.ogg"]);
+        assert_eq!(sorted(LibraryColumn::Album, true), ["d.ogg", "a.ogg", "c.ogg", "b.ogg"]);
+        assert_eq!(sorted(LibraryColumn::Album, false), ["b.ogg", "a.ogg", "c.ogg", "d.ogg"]);
+        // Unknown lengths are shortest.
+        assert_eq!(sorted(LibraryColumn::Duration, true), ["d.ogg", "b.ogg", "c.ogg", "a.ogg"]);
+        assert_eq!(sorted(LibraryColumn::Duration, false), ["a.ogg", "b.ogg", "c.ogg", "d.ogg"]);
+    }
+
+    #[test]
+    fn an_entry_becomes_a_track() {
+        let track = library().entries()[2].track(TrackId(9));
+        assert_eq!((track.id, track.title.as_str(), track.source.as_str()), (TrackId(9), "Rain", "/music/c.ogg"));
+        assert_eq!((track.artist.as_deref(), track.duration), (Some("Nils Frahm"), Some(Duration::from_secs(240))));
+    }
+}
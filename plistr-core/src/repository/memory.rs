@@ -0,0 +1,121 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use super::{without_tracks, Play, Repository, RepositoryError};
+use crate::{Playlist, PlaylistId, Session, SessionId, Track, TrackId};
+
+
+/// Everything in maps, gone when dropped. For tests, and for builds with
+/// nowhere to save.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryRepository {
+    tracks: BTreeMap<TrackId, Track>,
+    /// Playlists without their tracks, and the ids of those in order.
+    playlists: BTreeMap<PlaylistId, (Playlist, Vec<TrackId>)>,
+    sessions: BTreeMap<SessionId, Session>,
+    /// In the order recorded.
+    plays: Vec<Play>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn assemble(&self, shell: &Playlist, tracks: &[TrackId]) -> Playlist {
+        let mut playlist = shell.clone();
+        for track in tracks.iter().filter_map(|id| self.tracks.get(id)) {
+            playlist.add(track.clone());
+        }
+        playlist
+    }
+}
+
+impl Repository for MemoryRepository {
+    fn save_track(&mut self, track: &Track) -> Result<(), RepositoryError> {
+        self.tracks.insert(track.id, track.clone());
+        Ok(())
+    }
+
+    fn track(&self, id: TrackId) -> Result<Option<Track>, RepositoryError> {
+        Ok(self.tracks.get(&id).cloned())
+    }
+
+    fn tracks(&self) -> Result<Vec<Track>, RepositoryError> {
+        Ok(self.tracks.values().cloned().collect())
+    }
+
+    fn remember_track(&mut self, track: &Track) -> Result<Track, RepositoryError> {
+        if let Some(stored) = self.tracks.values().find(|stored| stored.id.is_stored() && stored.source == track.source) {
+            return Ok(stored.clone());
+        }
+        let last = self.tracks.keys().rev().find(|id| id.is_stored());
+        let id = last.map_or(TrackId(1), |last| TrackId(last.0 + 1));
+        let stored = Track { id, ..track.clone() };
+        self.tracks.insert(id, stored.clone());
+        Ok(stored)
+    }
+
+    fn delete_track(&mut self, id: TrackId) -> Result<(), RepositoryError> {
+        self.tracks.remove(&id);
+        for (_, tracks) in self.playlists.values_mut() {
+            tracks.retain(|track| *track != id);
+        }
+        Ok(())
+    }
+
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError> {
+        for track in playlist.tracks() {
+            self.save_track(track)?;
+        }
+        let tracks = playlist.tracks().iter().map(|track| track.id).collect();
+        self.playlists.insert(playlist.id, (without_tracks(playlist), tracks));
+        Ok(())
+    }
+
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError> {
+        Ok(self.playlists.get(&id).map(|(shell, tracks)| self.assemble(shell, tracks)))
+    }
+
+    fn playlists(&self) -> Result<Vec<Playlist>, RepositoryError> {
+        Ok(self.playlists.values().map(|(shell, tracks)| self.assemble(shell, tracks)).collect())
+    }
+
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError> {
+        self.playlists.remove(&id);
+        Ok(())
+    }
+
+    fn save_session(&mut self, session: &Session) -> Result<(), RepositoryError> {
+        self.sessions.insert(session.id, session.clone());
+        Ok(())
+    }
+
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+        Ok(self.sessions.get(&id).cloned())
+    }
+
+    fn sessions(&self) -> Result<Vec<Session>, RepositoryError> {
+        let mut sessions: Vec<Session> = self.sessions.values().cloned().collect();
+        sessions.sort_by_key(|session| session.started_at);
+        Ok(sessions)
+    }
+
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError> {
+        self.sessions.remove(&id);
+        Ok(())
+    }
+
+    fn record_play(&mut self, play: &Play) -> Result<(), RepositoryError> {
+        self.plays.push(play.clone());
+        Ok(())
+    }
+
+    fn history(&self, limit: usize) -> Result<Vec<Play>, RepositoryError> {
+        // Plays recorded in the same second come out newest first too.
+        let mut plays: Vec<Play> = self.plays.iter().rev().cloned().collect();
+        plays.sort_by_key(|play| Reverse(play.played_at));
+        plays.truncate(limit);
+        Ok(plays)
+    }
+}
@@ -0,0 +1,100 @@
+//! Keeping tracks, playlists, sessions and listening history between runs.
+//! `MemoryRepository` holds the rules every backend follows; the SQLite one
+//! here and the app's IndexedDB one store the same things the same way.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Playlist, PlaylistId, Session, SessionId, Track, TrackId};
+
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(test)]
+mod tests;
+
+pub use memory::MemoryRepository;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteRepository;
+
+
+/// Storage for everything worth keeping. Reads take `&self` and writes
+/// `&mut self`; nothing is cached behind the caller's back, so a write is
+/// there for the next read.
+pub trait Repository {
+    /// Adds the track, or replaces the one with the same id.
+    fn save_track(&mut self, track: &Track) -> Result<(), RepositoryError>;
+    fn track(&self, id: TrackId) -> Result<Option<Track>, RepositoryError>;
+    /// In id order.
+    fn tracks(&self) -> Result<Vec<Track>, RepositoryError>;
+    /// The stored track with `track`'s source, or `track` saved under a new
+    /// id if there isn't one. Dropped files and library entries are known
+    /// by path, and the ids they're given only last a session.
+    fn remember_track(&mut self, track: &Track) -> Result<Track, RepositoryError>;
+    /// Also takes the track out of every playlist. Its plays stay in the
+    /// history.
+    fn delete_track(&mut self, id: TrackId) -> Result<(), RepositoryError>;
+
+    /// Saves the playlist's tracks too, so loading it gives them back.
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError>;
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError>;
+    /// In id order.
+    fn playlists(&self) -> Result<Vec<Playlist>, RepositoryError>;
+    /// Leaves the tracks, which other playlists may share.
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError>;
+
+    fn save_session(&mut self, session: &Session) -> Result<(), RepositoryError>;
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError>;
+    /// Earliest first.
+    fn sessions(&self) -> Result<Vec<Session>, RepositoryError>;
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError>;
+
+    fn record_play(&mut self, play: &Play) -> Result<(), RepositoryError>;
+    /// Up to `limit` plays, latest first.
+    fn history(&self, limit: usize) -> Result<Vec<Play>, RepositoryError>;
+}
+
+/// One time a track was listened to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Play {
+    pub track: TrackId,
+    /// Seconds since the Unix epoch.
+    pub played_at: u64,
+    /// How far it got before it ended or was skipped.
+    pub listened: Duration,
+}
+
+#[derive(Debug)]
+pub enum RepositoryError {
+    /// The database couldn't be reached or refused the change.
+    Backend(String),
+    /// Something stored doesn't read back as what it should be.
+    Corrupt(String),
+    /// Written by a newer build, whose changes this one can't undo.
+    TooNew { version: u32, supported: u32 },
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepositoryError::Backend(message) => write!(f, "storage failed: {message}"),
+            RepositoryError::Corrupt(message) => write!(f, "stored data is damaged: {message}"),
+            RepositoryError::TooNew { version, supported } => {
+                write!(f, "the database is version {version}, newer than this build's {supported}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {}
+
+/// A copy of `playlist` without its tracks, for backends that keep those
+/// apart and put them back with `Playlist::add`.
+fn without_tracks(playlist: &Playlist) -> Playlist {
+    let mut shell = Playlist::new(playlist.id, playlist.owner, playlist.name.clone());
+    shell.description = playlist.description.clone();
+    shell.tags = playlist.tags.clone();
+    shell
+}
@@ -0,0 +1,368 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use super::{Play, Repository, RepositoryError};
+use crate::{Playlist, PlaylistId, Session, SessionId, SessionKind, Tag, Track, TrackId, UserId};
+
+
+/// One SQLite file. Opening it brings the schema up to date.
+pub struct SqliteRepository {
+    connection: Connection,
+}
+
+impl SqliteRepository {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RepositoryError> {
+        Self::migrate(Connection::open(path).map_err(backend)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, RepositoryError> {
+        Self::migrate(Connection::open_in_memory().map_err(backend)?)
+    }
+
+    /// Runs each migration the file hasn't had yet, in its own transaction,
+    /// and records it in `user_version`.
+    fn migrate(mut connection: Connection) -> Result<Self, RepositoryError> {
+        connection.pragma_update(None, "foreign_keys", true).map_err(backend)?;
+        let version: u32 = connection.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(backend)?;
+        let supported = MIGRATIONS.len() as u32;
+        if version > supported {
+            return Err(RepositoryError::TooNew { version, supported });
+        }
+        for (done, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let transaction = connection.transaction().map_err(backend)?;
+            transaction.execute_batch(migration).map_err(backend)?;
+            transaction.pragma_update(None, "user_version", done as u32 + 1).map_err(backend)?;
+            transaction.commit().map_err(backend)?;
+        }
+        Ok(Self { connection })
+    }
+
+    fn playlist_tracks(&self, id: PlaylistId) -> Result<Vec<Track>, RepositoryError> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {TRACK_COLUMNS} FROM playlist_tracks JOIN tracks ON tracks.id = playlist_tracks.track \
+             WHERE playlist_tracks.playlist = ?1 ORDER BY playlist_tracks.position",
+        )).map_err(backend)?;
+        let rows = statement.query_map([id.0 as i64], read_track).map_err(backend)?;
+        rows.map(|row| row.map_err(backend)?).collect()
+    }
+
+    fn assemble(&self, shell: Result<Playlist, RepositoryError>) -> Result<Playlist, RepositoryError> {
+        let mut playlist = shell?;
+        for track in self.playlist_tracks(playlist.id)? {
+            playlist.add(track);
+        }
+        Ok(playlist)
+    }
+}
+
+impl Repository for SqliteRepository {
+    fn save_track(&mut self, track: &Track) -> Result<(), RepositoryError> {
+        write_track(&self.connection, track)
+    }
+
+    fn track(&self, id: TrackId) -> Result<Option<Track>, RepositoryError> {
+        let sql = format!("SELECT {TRACK_COLUMNS} FROM tracks WHERE id = ?1");
+        let track = self.connection.query_row(&sql, [id.0 as i64], read_track).optional().map_err(backend)?;
+        track.transpose()
+    }
+
+    fn tracks(&self) -> Result<Vec<Track>, RepositoryError> {
+        // Unstored ids are kept as negatives (see `remember_track`) but
+        // still sort after the stored ones.
+        let sql = format!("SELECT {TRACK_COLUMNS} FROM tracks ORDER BY id < 0, id");
+        let mut statement = self.connection.prepare(&sql).map_err(backend)?;
+        let rows = statement.query_map([], read_track).map_err(backend)?;
+        rows.map(|row| row.map_err(backend)?).collect()
+    }
+
+    fn remember_track(&mut self, track: &Track) -> Result<Track, RepositoryError> {
+        let sql = format!("SELECT {TRACK_COLUMNS} FROM tracks WHERE source = ?1 AND id > 0 ORDER BY id LIMIT 1");
+        let stored = self.connection.query_row(&sql, [&track.source], read_track).optional().map_err(backend)?;
+        if let Some(stored) = stored {
+            return stored;
+        }
+        // Unstored ids are past `i64::MAX`, so they're kept as negatives.
+        let last: Option<i64> = self.connection.query_row("SELECT max(id) FROM tracks WHERE id > 0", [], |row| row.get(0)).map_err(backend)?;
+        let stored = Track { id: TrackId(last.map_or(1, |last| last as u64 + 1)), ..track.clone() };
+        write_track(&self.connection, &stored)?;
+        Ok(stored)
+    }
+
+    fn delete_track(&mut self, id: TrackId) -> Result<(), RepositoryError> {
+        // Foreign keys take it out of the playlists.
+        self.connection.execute("DELETE FROM tracks WHERE id = ?1", [id.0 as i64]).map_err(backend)?;
+        Ok(())
+    }
+
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError> {
+        let transaction = self.connection.transaction().map_err(backend)?;
+        transaction.execute(
+            "INSERT INTO playlists (id, owner, name, description, tags) VALUES (?1, ?2, ?3, ?4, ?5) \
+             ON CONFLICT (id) DO UPDATE SET owner = ?2, name = ?3, description = ?4, tags = ?5",
+            params![playlist.id.0 as i64, playlist.owner.0 as i64, playlist.name, playlist.description, join_tags(&playlist.tags)],
+        ).map_err(backend)?;
+        transaction.execute("DELETE FROM playlist_tracks WHERE playlist = ?1", [playlist.id.0 as i64]).map_err(backend)?;
+        for (position, track) in playlist.tracks().iter().enumerate() {
+            write_track(&transaction, track)?;
+            transaction.execute(
+                "INSERT INTO playlist_tracks (playlist, position, track) VALUES (?1, ?2, ?3)",
+                params![playlist.id.0 as i64, position as i64, track.id.0 as i64],
+            ).map_err(backend)?;
+        }
+        transaction.commit().map_err(backend)
+    }
+
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError> {
+        let sql = format!("SELECT {PLAYLIST_COLUMNS} FROM playlists WHERE id = ?1");
+        let shell = self.connection.query_row(&sql, [id.0 as i64], read_playlist).optional().map_err(backend)?;
+        shell.map(|shell| self.assemble(shell)).transpose()
+    }
+
+    fn playlists(&self) -> Result<Vec<Playlist>, RepositoryError> {
+        let mut statement = self.connection.prepare(&format!("SELECT {PLAYLIST_COLUMNS} FROM playlists ORDER BY id")).map_err(backend)?;
+        let shells: Vec<_> = statement.query_map([], read_playlist).map_err(backend)?.collect::<Result<_, _>>().map_err(backend)?;
+        shells.into_iter().map(|shell| self.assemble(shell)).collect()
+    }
+
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError> {
+        self.connection.execute("DELETE FROM playlists WHERE id = ?1", [id.0 as i64]).map_err(backend)?;
+        Ok(())
+    }
+
+    fn save_session(&mut self, session: &Session) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "INSERT INTO sessions (id, user, kind, playlist, started_at, planned_ms, elapsed_ms, tags) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+             ON CONFLICT (id) DO UPDATE SET user = ?2, kind = ?3, playlist = ?4, started_at = ?5, \
+             planned_ms = ?6, elapsed_ms = ?7, tags = ?8",
+            params![
+                session.id.0 as i64,
+                session.user.0 as i64,
+                kind_name(session.kind),
+                session.playlist.map(|playlist| playlist.0 as i64),
+                session.started_at as i64,
+                millis(session.planned),
+                millis(session.elapsed),
+                join_tags(&session.tags),
+            ],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+        let sql = format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE id = ?1");
+        let session = self.connection.query_row(&sql, [id.0 as i64], read_session).optional().map_err(backend)?;
+        session.transpose()
+    }
+
+    fn sessions(&self) -> Result<Vec<Session>, RepositoryError> {
+        let sql = format!("SELECT {SESSION_COLUMNS} FROM sessions ORDER BY started_at, id");
+        let mut statement = self.connection.prepare(&sql).map_err(backend)?;
+        let rows = statement.query_map([], read_session).map_err(backend)?;
+        rows.map(|row| row.map_err(backend)?).collect()
+    }
+
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError> {
+        self.connection.execute("DELETE FROM sessions WHERE id = ?1", [id.0 as i64]).map_err(backend)?;
+        Ok(())
+    }
+
+    fn record_play(&mut self, play: &Play) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "INSERT INTO plays (track, played_at, listened_ms) VALUES (?1, ?2, ?3)",
+            params![play.track.0 as i64, play.played_at as i64, millis(play.listened)],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn history(&self, limit: usize) -> Result<Vec<Play>, RepositoryError> {
+        let mut statement = self.connection
+            .prepare("SELECT track, played_at, listened_ms FROM plays ORDER BY played_at DESC, id DESC LIMIT ?1")
+            .map_err(backend)?;
+        let rows = statement.query_map([limit.min(i64::MAX as usize) as i64], |row| {
+            Ok(Play {
+                track: TrackId(row.get::<_, i64>(0)? as u64),
+                played_at: row.get::<_, i64>(1)? as u64,
+                listened: Duration::from_millis(row.get::<_, i64>(2)? as u64),
+            })
+        }).map_err(backend)?;
+        rows.collect::<Result<_, _>>().map_err(backend)
+    }
+}
+
+/// Updates in place rather than `INSERT OR REPLACE`, which would delete the
+/// row first and take it out of every playlist.
+fn write_track(connection: &Connection, track: &Track) -> Result<(), RepositoryError> {
+    connection.execute(
+        "INSERT INTO tracks (id, title, artist, duration_ms, source, tags) VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+         ON CONFLICT (id) DO UPDATE SET title = ?2, artist = ?3, duration_ms = ?4, source = ?5, tags = ?6",
+        params![
+            track.id.0 as i64,
+            track.title,
+            track.artist,
+            track.duration.map(millis),
+            track.source,
+            join_tags(&track.tags),
+        ],
+    ).map_err(backend)?;
+    Ok(())
+}
+
+/// Rows come back as `Ok(Err(_))` when SQLite read them fine but the
+/// values don't make sense.
+fn read_track(row: &Row) -> rusqlite::Result<Result<Track, RepositoryError>> {
+    let mut track = Track::new(TrackId(row.get::<_, i64>(0)? as u64), row.get::<_, String>(1)?, row.get::<_, String>(4)?);
+    track.artist = row.get(2)?;
+    track.duration = row.get::<_, Option<i64>>(3)?.map(|ms| Duration::from_millis(ms as u64));
+    Ok(split_tags(&row.get::<_, String>(5)?).map(|tags| Track { tags, ..track }))
+}
+
+fn read_playlist(row: &Row) -> rusqlite::Result<Result<Playlist, RepositoryError>> {
+    let id = PlaylistId(row.get::<_, i64>(0)? as u64);
+    let mut playlist = Playlist::new(id, UserId(row.get::<_, i64>(1)? as u64), row.get::<_, String>(2)?);
+    playlist.description = row.get(3)?;
+    Ok(split_tags(&row.get::<_, String>(4)?).map(|tags| {
+        playlist.tags = tags;
+        playlist
+    }))
+}
+
+fn read_session(row: &Row) -> rusqlite::Result<Result<Session, RepositoryError>> {
+    let kind: String = row.get(2)?;
+    let Some(kind) = [SessionKind::Study, SessionKind::Meditation].into_iter().find(|k| kind_name(*k) == kind) else {
+        return Ok(Err(RepositoryError::Corrupt(format!("unknown session kind {kind:?}"))));
+    };
+    let mut session = Session::new(
+        SessionId(row.get::<_, i64>(0)? as u64),
+        UserId(row.get::<_, i64>(1)? as u64),
+        kind,
+        row.get::<_, i64>(4)? as u64,
+        Duration::from_millis(row.get::<_, i64>(5)? as u64),
+    );
+    session.playlist = row.get::<_, Option<i64>>(3)?.map(|id| PlaylistId(id as u64));
+    session.elapsed = Duration::from_millis(row.get::<_, i64>(6)? as u64);
+    Ok(split_tags(&row.get::<_, String>(7)?).map(|tags| Session { tags, ..session }))
+}
+
+fn kind_name(kind: SessionKind) -> &'static str {
+    match kind {
+        SessionKind::Study => "study",
+        SessionKind::Meditation => "meditation",
+    }
+}
+
+/// Tags never hold spaces, so a space-separated column is enough.
+fn join_tags(tags: &[Tag]) -> String {
+    tags.iter().map(Tag::as_str).collect::<Vec<_>>().join(" ")
+}
+
+fn split_tags(text: &str) -> Result<Vec<Tag>, RepositoryError> {
+    text.split_whitespace()
+        .map(|tag| Tag::new(tag).map_err(|e| RepositoryError::Corrupt(format!("tag {tag:?}: {e}"))))
+        .collect()
+}
+
+fn millis(duration: Duration) -> i64 {
+    duration.as_millis().min(i64::MAX as u128) as i64
+}
+
+fn backend(e: rusqlite::Error) -> RepositoryError {
+    RepositoryError::Backend(e.to_string())
+}
+
+
+/// Each entry moves the schema on one version. Only ever add to the end:
+/// files already out there have run the ones before.
+const MIGRATIONS: [&str; 1] = ["
+    CREATE TABLE tracks (
+        id INTEGER PRIMARY KEY,
+        title TEXT NOT NULL,
+        artist TEXT,
+        duration_ms INTEGER,
+        source TEXT NOT NULL,
+        tags TEXT NOT NULL
+    );
+    CREATE INDEX tracks_by_source ON tracks (source);
+    CREATE TABLE playlists (
+        id INTEGER PRIMARY KEY,
+        owner INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        tags TEXT NOT NULL
+    );
+    CREATE TABLE playlist_tracks (
+        playlist INTEGER NOT NULL REFERENCES playlists (id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        track INTEGER NOT NULL REFERENCES tracks (id) ON DELETE CASCADE,
+        PRIMARY KEY (playlist, position)
+    );
+    CREATE TABLE sessions (
+        id INTEGER PRIMARY KEY,
+        user INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        playlist INTEGER,
+        started_at INTEGER NOT NULL,
+        planned_ms INTEGER NOT NULL,
+        elapsed_ms INTEGER NOT NULL,
+        tags TEXT NOT NULL
+    );
+    CREATE TABLE plays (
+        id INTEGER PRIMARY KEY,
+        track INTEGER NOT NULL,
+        played_at INTEGER NOT NULL,
+        listened_ms INTEGER NOT NULL
+    );
+    CREATE INDEX plays_by_time ON plays (played_at);
+"];
+const TRACK_COLUMNS: &str = "tracks.id, tracks.title, tracks.artist, tracks.duration_ms, tracks.source, tracks.tags";
+const PLAYLIST_COLUMNS: &str = "id, owner, name, description, tags";
+const SESSION_COLUMNS: &str = "id, user, kind, playlist, started_at, planned_ms, elapsed_ms, tags";
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(connection: &Connection) -> u32 {
+        connection.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn migrates_a_new_file_from_version_zero() {
+        let connection = Connection::open_in_memory().unwrap();
+        assert_eq!(user_version(&connection), 0);
+        let mut repository = SqliteRepository::migrate(connection).unwrap();
+        assert_eq!(user_version(&repository.connection), MIGRATIONS.len() as u32);
+        let track = Track::new(TrackId(1), "Rain", "/music/rain.ogg");
+        repository.save_track(&track).unwrap();
+        assert_eq!(repository.tracks().unwrap(), [track]);
+    }
+
+    #[test]
+    fn reopening_keeps_what_was_saved() {
+        let path = std::env::temp_dir().join(format!("plistr-core-test-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let track = Track::new(TrackId(1), "Rain", "/music/rain.ogg");
+        SqliteRepository::open(&path).unwrap().save_track(&track).unwrap();
+        let reopened = SqliteRepository::open(&path).unwrap();
+        assert_eq!(user_version(&reopened.connection), MIGRATIONS.len() as u32);
+        assert_eq!(reopened.tracks().unwrap(), [track]);
+        drop(reopened);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn refuses_files_from_a_newer_build() {
+        let connection = Connection::open_in_memory().unwrap();
+        let newer = MIGRATIONS.len() as u32 + 1;
+        connection.pragma_update(None, "user_version", newer).unwrap();
+        match SqliteRepository::migrate(connection) {
+            Err(RepositoryError::TooNew { version, supported }) => {
+                assert_eq!((version, supported), (newer, MIGRATIONS.len() as u32));
+            }
+            other => panic!("expected TooNew, got {:?}", other.err()),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! What every `Repository` has to do, written once and run against each
+//! backend.
+
+use std::time::Duration;
+
+use super::{MemoryRepository, Play, Repository};
+#[cfg(feature = "sqlite")]
+use super::SqliteRepository;
+use crate::{Playlist, PlaylistId, Session, SessionId, SessionKind, Tag, Track, TrackId, UserId};
+
+
+/// One test per behavior, for each backend `open` makes.
+macro_rules! behaves_like_a_repository {
+    ($backend:ident, $open:expr) => {
+        mod $backend {
+            use super::*;
+
+            #[test]
+            fn saves_and_replaces_tracks() {
+                saves_and_replaces_tracks_in(&mut $open);
+            }
+
+            #[test]
+            fn remembers_tracks_by_source() {
+                remembers_tracks_by_source_in(&mut $open);
+            }
+
+            #[test]
+            fn remembered_ids_skip_unstored_ones() {
+                remembered_ids_skip_unstored_ones_in(&mut $open);
+            }
+
+            #[test]
+            fn keeps_unstored_tracks_after_stored_ones() {
+                keeps_unstored_tracks_after_stored_ones_in(&mut $open);
+            }
+
+            #[test]
+            fn deleting_a_track_takes_it_out_of_playlists() {
+                deleting_a_track_takes_it_out_of_playlists_in(&mut $open);
+            }
+
+            #[test]
+            fn saves_playlists_with_their_tracks() {
+                saves_playlists_with_their_tracks_in(&mut $open);
+            }
+
+            #[test]
+            fn deleting_a_playlist_leaves_its_tracks() {
+                deleting_a_playlist_leaves_its_tracks_in(&mut $open);
+            }
+
+            #[test]
+            fn keeps_sessions_earliest_first() {
+                keeps_sessions_earliest_first_in(&mut $open);
+            }
+
+            #[test]
+            fn gives_history_latest_first() {
+                gives_history_latest_first_in(&mut $open);
+            }
+        }
+    };
+}
+
+behaves_like_a_repository!(memory, MemoryRepository::new());
+#[cfg(feature = "sqlite")]
+behaves_like_a_repository!(sqlite, SqliteRepository::open_in_memory().unwrap());
+
+fn track(id: u64) -> Track {
+    let mut track = Track::new(TrackId(id), format!("Track {id}"), format!("/music/{id}.flac"));
+    track.artist = Some("Nobody".to_owned());
+    track.duration = Some(Duration::from_millis(183_250));
+    track.tags = vec![Tag::new("lofi").unwrap()];
+    track
+}
+
+fn playlist(id: u64, tracks: &[u64]) -> Playlist {
+    let mut playlist = Playlist::new(PlaylistId(id), UserId(1), format!("Playlist {id}"));
+    playlist.description = "For reading".to_owned();
+    playlist.tags = vec![Tag::new("deep focus").unwrap(), Tag::new("rain").unwrap()];
+    for &id in tracks {
+        playlist.add(track(id));
+    }
+    playlist
+}
+
+fn session(id: u64, started_at: u64) -> Session {
+    let mut session = Session::new(SessionId(id), UserId(1), SessionKind::Meditation, started_at, Duration::from_secs(600));
+    session.playlist = Some(PlaylistId(3));
+    session.elapsed = Duration::from_millis(412_500);
+    session.tags = vec![Tag::new("morning").unwrap()];
+    session
+}
+
+fn ids(playlist: &Playlist) -> Vec<u64> {
+    playlist.tracks().iter().map(|track| track.id.0).collect()
+}
+
+fn saves_and_replaces_tracks_in(repository: &mut dyn Repository) {
+    assert_eq!(repository.track(TrackId(1)).unwrap(), None);
+    repository.save_track(&track(2)).unwrap();
+    repository.save_track(&track(1)).unwrap();
+    assert_eq!(repository.track(TrackId(1)).unwrap(), Some(track(1)));
+
+    let mut renamed = track(2);
+    renamed.title = "Renamed".to_owned();
+    renamed.artist = None;
+    renamed.duration = None;
+    renamed.tags.clear();
+    repository.save_track(&renamed).unwrap();
+    assert_eq!(repository.tracks().unwrap(), [track(1), renamed]);
+}
+
+fn remembers_tracks_by_source_in(repository: &mut dyn Repository) {
+    let first = repository.remember_track(&track(40)).unwrap();
+    assert_eq!(first, Track { id: TrackId(1), ..track(40) });
+    // The same file under another session's id is the same track.
+    let again = repository.remember_track(&Track { id: TrackId(77), ..track(40) }).unwrap();
+    assert_eq!(again, first);
+
+    repository.save_track(&track(9)).unwrap();
+    let next = repository.remember_track(&track(41)).unwrap();
+    assert_eq!(next.id, TrackId(10));
+    assert_eq!(repository.tracks().unwrap().len(), 3);
+}
+
+fn remembered_ids_skip_unstored_ones_in(repository: &mut dyn Repository) {
+    let unstored = Track { id: TrackId::unstored(), ..track(5) };
+    repository.save_track(&unstored).unwrap();
+    assert_eq!(repository.track(unstored.id).unwrap(), Some(unstored));
+    assert_eq!(repository.remember_track(&track(41)).unwrap().id, TrackId(1));
+    assert_eq!(repository.remember_track(&track(42)).unwrap().id, TrackId(2));
+}
+
+fn keeps_unstored_tracks_after_stored_ones_in(repository: &mut dyn Repository) {
+    let first = Track { id: TrackId::unstored(), ..track(5) };
+    let second = Track { id: TrackId::unstored(), ..track(6) };
+    repository.save_track(&second).unwrap();
+    repository.save_track(&first).unwrap();
+    repository.save_track(&track(3)).unwrap();
+    repository.save_track(&Track { source: first.source.clone(), ..track(2) }).unwrap();
+
+    let ids: Vec<TrackId> = repository.tracks().unwrap().iter().map(|track| track.id).collect();
+    assert_eq!(ids, [TrackId(2), TrackId(3), first.id, second.id]);
+    // Only stored copies are remembered; an unstored one gets stored.
+    assert_eq!(repository.remember_track(&first).unwrap().id, TrackId(2));
+    assert_eq!(repository.remember_track(&second).unwrap(), Track { id: TrackId(4), ..second });
+}
+
+fn deleting_a_track_takes_it_out_of_playlists_in(repository: &mut dyn Repository) {
+    repository.save_playlist(&playlist(1, &[1, 2, 3])).unwrap();
+    repository.record_play(&Play { track: TrackId(2), played_at: 100, listened: Duration::from_secs(30) }).unwrap();
+    repository.delete_track(TrackId(2)).unwrap();
+    assert_eq!(repository.track(TrackId(2)).unwrap(), None);
+    assert_eq!(ids(&repository.playlist(PlaylistId(1)).unwrap().unwrap()), [1, 3]);
+    assert_eq!(repository.history(10).unwrap()[0].track, TrackId(2));
+    // Deleting what isn't there is fine.
+    repository.delete_track(TrackId(2)).unwrap();
+}
+
+fn saves_playlists_with_their_tracks_in(repository: &mut dyn Repository) {
+    assert_eq!(repository.playlist(PlaylistId(1)).unwrap(), None);
+    // Order and repeats are kept.
+    let repeated = playlist(2, &[3, 1, 3, 2]);
+    repository.save_playlist(&repeated).unwrap();
+    repository.save_playlist(&playlist(1, &[])).unwrap();
+    assert_eq!(repository.playlist(PlaylistId(2)).unwrap(), Some(repeated.clone()));
+    assert_eq!(repository.track(TrackId(3)).unwrap(), Some(track(3)));
+    assert_eq!(repository.playlists().unwrap(), [playlist(1, &[]), repeated]);
+
+    let mut shorter = playlist(2, &[2]);
+    shorter.name = "Shorter".to_owned();
+    shorter.tags.clear();
+    repository.save_playlist(&shorter).unwrap();
+    assert_eq!(repository.playlist(PlaylistId(2)).unwrap(), Some(shorter));
+}
+
+fn deleting_a_playlist_leaves_its_tracks_in(repository: &mut dyn Repository) {
+    repository.save_playlist(&playlist(1, &[1, 2])).unwrap();
+    repository.save_playlist(&playlist(2, &[2])).unwrap();
+    repository.delete_playlist(PlaylistId(1)).unwrap();
+    assert_eq!(repository.playlist(PlaylistId(1)).unwrap(), None);
+    assert_eq!(repository.playlists().unwrap(), [playlist(2, &[2])]);
+    assert_eq!(repository.tracks().unwrap(), [track(1), track(2)]);
+}
+
+fn keeps_sessions_earliest_first_in(repository: &mut dyn Repository) {
+    repository.save_session(&session(1, 300)).unwrap();
+    repository.save_session(&session(2, 100)).unwrap();
+    repository.save_session(&session(3, 200)).unwrap();
+    assert_eq!(repository.session(SessionId(2)).unwrap(), Some(session(2, 100)));
+    let order: Vec<u64> = repository.sessions().unwrap().iter().map(|session| session.id.0).collect();
+    assert_eq!(order, [2, 3, 1]);
+
+    let mut finished = session(1, 300);
+    finished.kind = SessionKind::Study;
+    finished.playlist = None;
+    finished.elapsed = finished.planned;
+    repository.save_session(&finished).unwrap();
+    assert_eq!(repository.session(SessionId(1)).unwrap(), Some(finished));
+
+    repository.delete_session(SessionId(3)).unwrap();
+    assert_eq!(repository.session(SessionId(3)).unwrap(), None);
+    assert_eq!(repository.sessions().unwrap().len(), 2);
+}
+
+fn gives_history_latest_first_in(repository: &mut dyn Repository) {
+    assert!(repository.history(10).unwrap().is_empty());
+    let play = |track, played_at| Play { track: TrackId(track), played_at, listened: Duration::from_millis(1500) };
+    for recorded in [play(1, 100), play(2, 300), play(3, 200), play(4, 300)] {
+        repository.record_play(&recorded).unwrap();
+    }
+    // Plays in the same second come out newest first too.
+    assert_eq!(repository.history(10).unwrap(), [play(4, 300), play(2, 300), play(3, 200), play(1, 100)]);
+    assert_eq!(repository.history(2).unwrap(), [play(4, 300), play(2, 300)]);
+    assert!(repository.history(0).unwrap().is_empty());
+}
@@ -0,0 +1,71 @@
+use super::{ClientMessage, ClockSync, MemberId, RoomState, ServerMessage, Transport};
+use crate::{FocusTimer, ManualClock};
+
+
+/// A member's side of a room: the latest state from the relay, and the
+/// clock offset to read it by. Local times are milliseconds on whatever
+/// monotonic clock the caller keeps.
+#[derive(Clone, Debug, Default)]
+pub struct RoomClient {
+    me: Option<MemberId>,
+    state: Option<RoomState>,
+    clock: ClockSync,
+}
+
+impl RoomClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes in a message from the relay, returning why a command was
+    /// turned down if that's what it says.
+    pub fn handle(&mut self, message: ServerMessage, now: u64) -> Option<String> {
+        match message {
+            ServerMessage::Welcome { you, state } => {
+                self.me = Some(you);
+                self.state = Some(state);
+            }
+            ServerMessage::State(state) => self.state = Some(state),
+            ServerMessage::Pong { sent, server_time } => self.clock.sample(sent, server_time, now),
+            ServerMessage::Rejected(reason) => return Some(reason),
+        }
+        None
+    }
+
+    /// A ping to send now, to keep the clock offset fresh.
+    pub fn ping(now: u64) -> ClientMessage {
+        ClientMessage::Ping { sent: now }
+    }
+
+    /// Forgets the room, for after a `Leave` or a dropped connection.
+    pub fn left(&mut self) {
+        self.me = None;
+        self.state = None;
+    }
+
+    pub fn me(&self) -> Option<MemberId> {
+        self.me
+    }
+
+    pub fn state(&self) -> Option<&RoomState> {
+        self.state.as_ref()
+    }
+
+    pub fn clock(&self) -> &ClockSync {
+        &self.clock
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.me.is_some() && self.state.as_ref().map(|state| state.host) == self.me
+    }
+
+    /// Where the room's music should be at local time `now`.
+    pub fn playing_at(&self, now: u64) -> Option<Transport> {
+        Some(self.state.as_ref()?.playing_at(self.clock.to_server(now)))
+    }
+
+    /// The room's timer at local time `now`.
+    pub fn timer_at(&self, now: u64) -> Option<FocusTimer<ManualClock>> {
+        Some(self.state.as_ref()?.timer.timer_at(self.clock.to_server(now)))
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+
+
+/// Works out the relay's clock from pings. A ping's answer left the relay
+/// somewhere in the round trip, taken to be halfway; the sample with the
+/// quickest round trip has the least room for error, so it wins.
+#[derive(Clone, Debug, Default)]
+pub struct ClockSync {
+    /// Offset to add to local time, and the round trip it was measured
+    /// over, both in milliseconds. Latest last.
+    samples: VecDeque<(i64, u64)>,
+}
+
+impl ClockSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A `Pong` for a ping `sent` at local time, which came back at
+    /// `received`.
+    pub fn sample(&mut self, sent: u64, server_time: u64, received: u64) {
+        let round_trip = received.saturating_sub(sent);
+        let offset = server_time as i64 + (round_trip / 2) as i64 - received as i64;
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((offset, round_trip));
+    }
+
+    fn best(&self) -> Option<(i64, u64)> {
+        self.samples.iter().copied().min_by_key(|&(_, round_trip)| round_trip)
+    }
+
+    /// Milliseconds to add to local time to get the relay's, or `None`
+    /// before the first `Pong`.
+    pub fn offset(&self) -> Option<i64> {
+        self.best().map(|(offset, _)| offset)
+    }
+
+    /// Of the sample the offset comes from.
+    pub fn round_trip(&self) -> Option<u64> {
+        self.best().map(|(_, round_trip)| round_trip)
+    }
+
+    /// The relay's time at `local`. Until there's a sample, the clocks are
+    /// taken to agree.
+    pub fn to_server(&self, local: u64) -> u64 {
+        local.saturating_add_signed(self.offset().unwrap_or(0))
+    }
+}
+
+
+/// Enough to ride out a few slow round trips, and few enough that the
+/// offset follows a clock that drifts.
+const SAMPLES: usize = 8;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_quickest_round_trip_wins() {
+        let mut clock = ClockSync::new();
+        assert_eq!((clock.offset(), clock.round_trip()), (None, None));
+        assert_eq!(clock.to_server(500), 500);
+
+        // The relay is 1000 ms ahead. This pong took 40 ms out and 160 back.
+        clock.sample(0, 1_040, 200);
+        assert_eq!((clock.offset(), clock.round_trip()), (Some(940), Some(200)));
+        // 10 ms each way: much closer.
+        clock.sample(300, 1_310, 320);
+        assert_eq!((clock.offset(), clock.round_trip()), (Some(1_000), Some(20)));
+        clock.sample(400, 1_450, 500);
+        assert_eq!(clock.offset(), Some(1_000));
+        assert_eq!(clock.to_server(2_000), 3_000);
+    }
+
+    #[test]
+    fn old_samples_are_forgotten() {
+        let mut clock = ClockSync::new();
+        clock.sample(0, 1_000, 0);
+        for i in 0..SAMPLES as u64 {
+            clock.sample(i * 100, i * 100 + 2_050, i * 100 + 100);
+        }
+        assert_eq!((clock.offset(), clock.round_trip()), (Some(2_000), Some(100)));
+    }
+
+    #[test]
+    fn a_relay_behind_gives_a_negative_offset() {
+        let mut clock = ClockSync::new();
+        clock.sample(5_000, 1_010, 5_020);
+        assert_eq!(clock.offset(), Some(-4_000));
+        assert_eq!(clock.to_server(5_000), 1_000);
+        assert_eq!(clock.to_server(10), 0);
+    }
+}
@@ -0,0 +1,292 @@
+//! Study rooms: a few people listening to one queue in step while a shared
+//! focus timer runs. A `Relay` holds each room and is the only one to
+//! change it; everyone else sends it `Command`s and gets the whole
+//! `RoomState` back whenever it changes, which is also all a late joiner
+//! needs to catch up. Times in the state are the relay's, in milliseconds,
+//! and `RoomClient` works out what they mean on the local clock.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FocusSettings, FocusSnapshot, FocusTimer, ManualClock, Track};
+
+mod client;
+mod clock;
+mod relay;
+
+pub use client::RoomClient;
+pub use clock::ClockSync;
+pub use relay::{ConnectionId, Relay};
+
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct MemberId(pub u64);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub id: MemberId,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoomState {
+    pub name: String,
+    /// The one member whose commands are followed.
+    pub host: MemberId,
+    /// In the order they joined.
+    pub members: Vec<Member>,
+    pub queue: Vec<Track>,
+    pub transport: Transport,
+    pub timer: SharedTimer,
+}
+
+/// Where the room's music is, as of `at`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transport {
+    /// Index into the queue.
+    pub current: Option<usize>,
+    pub playing: bool,
+    pub position: Duration,
+    /// Relay time the position was taken at.
+    pub at: u64,
+}
+
+/// The room's focus timer, as of `at`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SharedTimer {
+    pub snapshot: FocusSnapshot,
+    /// Relay time the snapshot was taken at.
+    pub at: u64,
+}
+
+/// What members ask the relay to do. Everything but `Join`, `Leave` and
+/// `Ping` is for the host only.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Makes the room if there isn't one by that name, with the joiner as
+    /// host. Joining another room leaves the first.
+    Join { room: String, name: String },
+    Leave,
+    /// `sent` is the sender's own clock, handed back in the `Pong`.
+    Ping { sent: u64 },
+    Command(Command),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Command {
+    /// Replaces the queue and starts it from the top.
+    SetQueue(Vec<Track>),
+    Play,
+    Pause,
+    Seek(Duration),
+    Jump(usize),
+    Next,
+    Timer(TimerCommand),
+    /// Hands the controls to another member.
+    MakeHost(MemberId),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TimerCommand {
+    Start,
+    Pause,
+    Skip,
+    Reset,
+    Settings(FocusSettings),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// The answer to `Join`.
+    Welcome { you: MemberId, state: RoomState },
+    /// Sent to everyone in the room after any change.
+    State(RoomState),
+    Pong { sent: u64, server_time: u64 },
+    Rejected(String),
+}
+
+#[derive(Debug)]
+pub enum RoomError {
+    /// A message that isn't one of the above.
+    Malformed(String),
+}
+
+impl fmt::Display for RoomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoomError::Malformed(message) => write!(f, "unreadable room message: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RoomError {}
+
+impl ClientMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("room messages always serialize")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, RoomError> {
+        serde_json::from_str(text).map_err(|e| RoomError::Malformed(e.to_string()))
+    }
+}
+
+impl ServerMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("room messages always serialize")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, RoomError> {
+        serde_json::from_str(text).map_err(|e| RoomError::Malformed(e.to_string()))
+    }
+}
+
+impl RoomState {
+    /// The track and position `now`, relay time. Playback runs on through
+    /// the queue for as long as track lengths are known, and stops at the
+    /// end.
+    pub fn playing_at(&self, now: u64) -> Transport {
+        let mut transport = self.transport;
+        transport.at = now;
+        if !transport.playing {
+            return transport;
+        }
+        transport.position += Duration::from_millis(now.saturating_sub(self.transport.at));
+        while let Some(index) = transport.current {
+            let Some(length) = self.queue.get(index).and_then(|track| track.duration) else {
+                break;
+            };
+            let Some(over) = transport.position.checked_sub(length) else {
+                break;
+            };
+            if index + 1 < self.queue.len() {
+                transport.current = Some(index + 1);
+                transport.position = over;
+            } else {
+                transport.playing = false;
+                transport.position = length;
+                break;
+            }
+        }
+        transport
+    }
+
+    pub fn member(&self, id: MemberId) -> Option<&Member> {
+        self.members.iter().find(|member| member.id == id)
+    }
+}
+
+impl SharedTimer {
+    pub fn new(settings: FocusSettings, now: u64) -> Self {
+        let timer = FocusTimer::new(settings, ManualClock::default());
+        Self { snapshot: timer.snapshot(), at: now }
+    }
+
+    /// The timer as it stands `now`, relay time, with any phases that ran
+    /// out since `at` moved past.
+    pub fn timer_at(&self, now: u64) -> FocusTimer<ManualClock> {
+        let clock = ManualClock::default();
+        let mut timer = FocusTimer::restore(self.snapshot, clock.clone());
+        clock.advance(Duration::from_millis(now.saturating_sub(self.at)));
+        for _ in 0..MAX_CATCH_UP {
+            if timer.poll().is_none() {
+                break;
+            }
+        }
+        timer
+    }
+
+    fn change(&mut self, now: u64, change: impl FnOnce(&mut FocusTimer<ManualClock>)) {
+        let mut timer = self.timer_at(now);
+        change(&mut timer);
+        *self = Self { snapshot: timer.snapshot(), at: now };
+    }
+}
+
+
+/// Phase changes worked through in one go; a day of one-minute phases.
+const MAX_CATCH_UP: usize = 2880;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackId;
+
+    fn state(lengths: &[Option<u64>], transport: Transport) -> RoomState {
+        let queue = lengths.iter()
+            .enumerate()
+            .map(|(i, length)| {
+                let mut track = Track::new(TrackId(i as u64 + 1), format!("Track {i}"), format!("{i}.ogg"));
+                track.duration = length.map(Duration::from_secs);
+                track
+            })
+            .collect();
+        RoomState {
+            name: "study".to_owned(),
+            host: MemberId(1),
+            members: vec![Member { id: MemberId(1), name: "Ada".to_owned() }],
+            queue,
+            transport,
+            timer: SharedTimer::new(FocusSettings::default(), 0),
+        }
+    }
+
+    fn playing(current: usize, seconds: u64, at: u64) -> Transport {
+        Transport { current: Some(current), playing: true, position: Duration::from_secs(seconds), at }
+    }
+
+    fn where_at(state: &RoomState, now: u64) -> (Option<usize>, bool, Duration) {
+        let transport = state.playing_at(now);
+        assert_eq!(transport.at, now);
+        (transport.current, transport.playing, transport.position)
+    }
+
+    #[test]
+    fn playing_runs_on_through_the_queue() {
+        let room = state(&[Some(30), Some(10), Some(20)], playing(0, 20, 1_000));
+        assert_eq!(where_at(&room, 1_000), (Some(0), true, Duration::from_secs(20)));
+        assert_eq!(where_at(&room, 6_500), (Some(0), true, Duration::from_millis(25_500)));
+        assert_eq!(where_at(&room, 11_000), (Some(1), true, Duration::ZERO));
+        // Past the whole of the second track into the third.
+        assert_eq!(where_at(&room, 26_000), (Some(2), true, Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn playing_stops_at_the_end_of_the_queue() {
+        let room = state(&[Some(30), Some(10)], playing(0, 20, 0));
+        assert_eq!(where_at(&room, 20_000), (Some(1), false, Duration::from_secs(10)));
+        assert_eq!(where_at(&room, 500_000), (Some(1), false, Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn an_unknown_length_plays_on() {
+        let room = state(&[None, Some(10)], playing(0, 20, 0));
+        assert_eq!(where_at(&room, 600_000), (Some(0), true, Duration::from_secs(620)));
+    }
+
+    #[test]
+    fn a_paused_room_stays_put() {
+        let paused = Transport { playing: false, ..playing(1, 4, 0) };
+        let room = state(&[Some(30), Some(10)], paused);
+        assert_eq!(where_at(&room, 60_000), (Some(1), false, Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn a_client_reads_the_state_on_its_own_clock() {
+        let mut client = RoomClient::new();
+        assert_eq!(client.playing_at(0), None);
+        let room = state(&[Some(30)], playing(0, 0, 50_000));
+        client.handle(ServerMessage::Welcome { you: MemberId(1), state: room }, 100);
+        assert!(client.is_host());
+        // The relay's clock is 49 900 ms ahead of ours.
+        client.handle(ServerMessage::Pong { sent: 90, server_time: 50_000 }, 110);
+        assert_eq!(client.playing_at(5_100).unwrap().position, Duration::from_secs(5));
+
+        assert_eq!(client.handle(ServerMessage::Rejected("no".to_owned()), 200), Some("no".to_owned()));
+        client.left();
+        assert_eq!((client.me(), client.playing_at(200)), (None, None));
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::{ClientMessage, Command, Member, MemberId, RoomState, ServerMessage, SharedTimer, TimerCommand, Transport};
+use crate::FocusSettings;
+
+
+/// One connection to the relay, however it's carried.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ConnectionId(pub u64);
+
+/// Every room, and who's in which. It only turns messages into answers;
+/// the transport reads them off sockets and sends the answers back, and
+/// says what time it is, in milliseconds on a clock of its choosing.
+#[derive(Debug, Default)]
+pub struct Relay {
+    rooms: BTreeMap<String, RoomState>,
+    /// The room and member each connection has joined as.
+    joined: BTreeMap<ConnectionId, (String, MemberId)>,
+    last_member: u64,
+}
+
+/// Answers to send, in order.
+type Outbox = Vec<(ConnectionId, ServerMessage)>;
+
+impl Relay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle(&mut self, from: ConnectionId, message: ClientMessage, now: u64) -> Outbox {
+        match message {
+            ClientMessage::Join { room, name } => self.join(from, room, name, now),
+            ClientMessage::Leave => self.disconnect(from),
+            ClientMessage::Ping { sent } => vec![(from, ServerMessage::Pong { sent, server_time: now })],
+            ClientMessage::Command(command) => self.command(from, command, now),
+        }
+    }
+
+    /// Takes the connection's member out of their room. The host's
+    /// controls pass to whoever has been there longest.
+    pub fn disconnect(&mut self, from: ConnectionId) -> Outbox {
+        let Some((name, member)) = self.joined.remove(&from) else {
+            return Vec::new();
+        };
+        let Some(room) = self.rooms.get_mut(&name) else {
+            return Vec::new();
+        };
+        room.members.retain(|other| other.id != member);
+        let Some(first) = room.members.first() else {
+            self.rooms.remove(&name);
+            return Vec::new();
+        };
+        if room.host == member {
+            room.host = first.id;
+        }
+        self.broadcast(&name)
+    }
+
+    pub fn rooms(&self) -> impl Iterator<Item = &RoomState> {
+        self.rooms.values()
+    }
+
+    fn join(&mut self, from: ConnectionId, name: String, member_name: String, now: u64) -> Outbox {
+        let name = name.trim().to_owned();
+        if name.is_empty() {
+            return vec![(from, ServerMessage::Rejected("rooms need a name".to_owned()))];
+        }
+        let mut outbox = self.disconnect(from);
+        self.last_member += 1;
+        let member = MemberId(self.last_member);
+        let room = self.rooms.entry(name.clone()).or_insert_with(|| RoomState {
+            name: name.clone(),
+            host: member,
+            members: Vec::new(),
+            queue: Vec::new(),
+            transport: Transport { current: None, playing: false, position: Duration::ZERO, at: now },
+            timer: SharedTimer::new(FocusSettings::default(), now),
+        });
+        let member_name = match member_name.trim() {
+            "" => format!("Guest {}", member.0),
+            trimmed => trimmed.to_owned(),
+        };
+        room.members.push(Member { id: member, name: member_name });
+        self.joined.insert(from, (name.clone(), member));
+        let welcome = ServerMessage::Welcome { you: member, state: room.clone() };
+        outbox.extend(self.broadcast(&name).into_iter().filter(|(to, _)| *to != from));
+        outbox.push((from, welcome));
+        outbox
+    }
+
+    fn command(&mut self, from: ConnectionId, command: Command, now: u64) -> Outbox {
+        let Some((name, member)) = self.joined.get(&from).cloned() else {
+            return vec![(from, ServerMessage::Rejected("join a room first".to_owned()))];
+        };
+        let Some(room) = self.rooms.get_mut(&name) else {
+            return Vec::new();
+        };
+        if room.host != member {
+            return vec![(from, ServerMessage::Rejected("only the host can do that".to_owned()))];
+        }
+        if let Err(reason) = apply(room, command, now) {
+            return vec![(from, ServerMessage::Rejected(reason))];
+        }
+        self.broadcast(&name)
+    }
+
+    fn broadcast(&self, name: &str) -> Outbox {
+        let Some(room) = self.rooms.get(name) else {
+            return Vec::new();
+        };
+        self.joined.iter()
+            .filter(|(_, (joined, _))| joined == name)
+            .map(|(&connection, _)| (connection, ServerMessage::State(room.clone())))
+            .collect()
+    }
+}
+
+fn apply(room: &mut RoomState, command: Command, now: u64) -> Result<(), String> {
+    // Everything starts from where the music has got to by now.
+    room.transport = room.playing_at(now);
+    let transport = &mut room.transport;
+    match command {
+        Command::SetQueue(tracks) => {
+            transport.current = (!tracks.is_empty()).then_some(0);
+            transport.position = Duration::ZERO;
+            transport.playing &= !tracks.is_empty();
+            room.queue = tracks;
+        }
+        Command::Play => {
+            if room.queue.is_empty() {
+                return Err("the queue is empty".to_owned());
+            }
+            transport.current = transport.current.or(Some(0));
+            transport.playing = true;
+        }
+        Command::Pause => transport.playing = false,
+        Command::Seek(position) => {
+            if transport.current.is_none() {
+                return Err("nothing is playing".to_owned());
+            }
+            transport.position = position;
+        }
+        Command::Jump(index) => {
+            if index >= room.queue.len() {
+                return Err(format!("the queue has no track {}", index + 1));
+            }
+            transport.current = Some(index);
+            transport.position = Duration::ZERO;
+        }
+        Command::Next => {
+            match transport.current {
+                Some(index) if index + 1 < room.queue.len() => transport.current = Some(index + 1),
+                _ => transport.playing = false,
+            }
+            transport.position = Duration::ZERO;
+        }
+        Command::Timer(command) => timer(&mut room.timer, command, now)?,
+        Command::MakeHost(member) => {
+            if room.member(member).is_none() {
+                return Err("they've left the room".to_owned());
+            }
+            room.host = member;
+        }
+    }
+    Ok(())
+}
+
+fn timer(timer: &mut SharedTimer, command: TimerCommand, now: u64) -> Result<(), String> {
+    if let TimerCommand::Settings(settings) = command {
+        if [settings.focus, settings.short_break, settings.long_break].contains(&Duration::ZERO) {
+            return Err("phases can't be empty".to_owned());
+        }
+    }
+    timer.change(now, |timer| match command {
+        TimerCommand::Start => timer.resume(),
+        TimerCommand::Pause => timer.pause(),
+        TimerCommand::Skip => {
+            timer.skip();
+        }
+        TimerCommand::Reset => timer.reset(),
+        TimerCommand::Settings(settings) => timer.set_settings(settings),
+    });
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RoomClient, Track, TrackId};
+
+    fn join(relay: &mut Relay, connection: u64, name: &str, now: u64) -> Outbox {
+        relay.handle(ConnectionId(connection), ClientMessage::Join { room: "study".to_owned(), name: name.to_owned() }, now)
+    }
+
+    fn command(relay: &mut Relay, connection: u64, command: Command, now: u64) -> Outbox {
+        relay.handle(ConnectionId(connection), ClientMessage::Command(command), now)
+    }
+
+    fn room(relay: &Relay) -> &RoomState {
+        relay.rooms().next().expect("no room")
+    }
+
+    fn tracks() -> Vec<Track> {
+        (1..=2)
+            .map(|n| {
+                let mut track = Track::new(TrackId(n), format!("Track {n}"), format!("{n}.ogg"));
+                track.duration = Some(Duration::from_secs(30));
+                track
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_the_host_is_followed() {
+        let mut relay = Relay::new();
+        join(&mut relay, 1, "Ada", 0);
+        join(&mut relay, 2, "Bo", 0);
+        assert_eq!(room(&relay).host, MemberId(1));
+
+        let refused = command(&mut relay, 2, Command::SetQueue(tracks()), 10);
+        assert_eq!(refused, [(ConnectionId(2), ServerMessage::Rejected("only the host can do that".to_owned()))]);
+        assert!(room(&relay).queue.is_empty());
+        let refused = command(&mut relay, 3, Command::Play, 10);
+        assert_eq!(refused, [(ConnectionId(3), ServerMessage::Rejected("join a room first".to_owned()))]);
+
+        let sent = command(&mut relay, 1, Command::SetQueue(tracks()), 10);
+        let to: Vec<_> = sent.iter().map(|(to, _)| *to).collect();
+        assert_eq!(to, [ConnectionId(1), ConnectionId(2)]);
+        assert_eq!(room(&relay).queue, tracks());
+    }
+
+    #[test]
+    fn a_late_joiner_is_welcomed_with_the_room() {
+        let mut relay = Relay::new();
+        join(&mut relay, 1, "Ada", 0);
+        command(&mut relay, 1, Command::SetQueue(tracks()), 0);
+        command(&mut relay, 1, Command::Play, 1_000);
+
+        let sent = join(&mut relay, 2, " ", 6_000);
+        let [(ConnectionId(1), ServerMessage::State(_)), (ConnectionId(2), welcome)] = sent.as_slice() else {
+            panic!("{sent:?}");
+        };
+        let mut client = RoomClient::new();
+        client.handle(welcome.clone(), 6_000);
+        assert_eq!(client.me(), Some(MemberId(2)));
+        assert_eq!(client.state().unwrap().member(MemberId(2)).unwrap().name, "Guest 2");
+        assert!(!client.is_host());
+        let transport = client.playing_at(6_000).unwrap();
+        assert_eq!((transport.current, transport.position), (Some(0), Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn the_longest_there_takes_over_and_an_empty_room_goes() {
+        let mut relay = Relay::new();
+        for (connection, name) in [(1, "Ada"), (2, "Bo"), (3, "Cy")] {
+            join(&mut relay, connection, name, 0);
+        }
+        let sent = relay.disconnect(ConnectionId(1));
+        assert_eq!(sent.len(), 2);
+        assert_eq!(room(&relay).host, MemberId(2));
+
+        // A member who isn't host leaving changes nothing else.
+        relay.handle(ConnectionId(3), ClientMessage::Leave, 0);
+        assert_eq!(room(&relay).host, MemberId(2));
+        assert_eq!(room(&relay).members.len(), 1);
+
+        assert!(relay.disconnect(ConnectionId(2)).is_empty());
+        assert_eq!(relay.rooms().count(), 0);
+        assert!(relay.disconnect(ConnectionId(2)).is_empty());
+    }
+
+    #[test]
+    fn the_host_hands_over_only_to_someone_still_there() {
+        let mut relay = Relay::new();
+        join(&mut relay, 1, "Ada", 0);
+        join(&mut relay, 2, "Bo", 0);
+        join(&mut relay, 3, "Cy", 0);
+        relay.disconnect(ConnectionId(3));
+
+        let refused = command(&mut relay, 1, Command::MakeHost(MemberId(3)), 0);
+        assert_eq!(refused, [(ConnectionId(1), ServerMessage::Rejected("they've left the room".to_owned()))]);
+        assert_eq!(room(&relay).host, MemberId(1));
+
+        command(&mut relay, 1, Command::MakeHost(MemberId(2)), 0);
+        assert_eq!(room(&relay).host, MemberId(2));
+        assert!(matches!(command(&mut relay, 1, Command::Pause, 0).as_slice(), [(_, ServerMessage::Rejected(_))]));
+    }
+
+    #[test]
+    fn commands_start_from_where_the_music_has_got_to() {
+        let mut relay = Relay::new();
+        join(&mut relay, 1, "Ada", 0);
+        command(&mut relay, 1, Command::SetQueue(tracks()), 0);
+        command(&mut relay, 1, Command::Play, 0);
+        command(&mut relay, 1, Command::Pause, 40_000);
+        let transport = room(&relay).transport;
+        assert_eq!((transport.current, transport.playing), (Some(1), false));
+        assert_eq!((transport.position, transport.at), (Duration::from_secs(10), 40_000));
+
+        command(&mut relay, 1, Command::Next, 50_000);
+        assert_eq!(room(&relay).transport.current, Some(1));
+        assert!(!room(&relay).transport.playing);
+        let refused = command(&mut relay, 1, Command::Jump(2), 50_000);
+        assert_eq!(refused, [(ConnectionId(1), ServerMessage::Rejected("the queue has no track 3".to_owned()))]);
+    }
+}
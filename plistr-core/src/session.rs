@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PlaylistId, Tag, UserId};
+
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct SessionId(pub u64);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SessionKind {
+    Study,
+    Meditation,
+}
+
+/// A block of time someone sets aside to study or meditate, usually with a
+/// playlist running.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub id: SessionId,
+    pub user: UserId,
+    pub kind: SessionKind,
+    #[serde(default)]
+    pub playlist: Option<PlaylistId>,
+    /// Seconds since the Unix epoch.
+    pub started_at: u64,
+    pub planned: Duration,
+    /// Time actually spent, which pauses don't count towards.
+    #[serde(default)]
+    pub elapsed: Duration,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+impl Session {
+    pub fn new(id: SessionId, user: UserId, kind: SessionKind, started_at: u64, planned: Duration) -> Self {
+        Self {
+            id,
+            user,
+            kind,
+            playlist: None,
+            started_at,
+            planned,
+            elapsed: Duration::ZERO,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, time: Duration) {
+        self.elapsed += time;
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.planned.saturating_sub(self.elapsed)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.planned
+    }
+
+    /// How far through the planned time, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.planned.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed.as_secs_f32() / self.planned.as_secs_f32()).min(1.0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(minutes: u64) -> Session {
+        Session::new(SessionId(1), UserId(1), SessionKind::Study, 1_700_000_000, Duration::from_secs(minutes * 60))
+    }
+
+    #[test]
+    fn records_towards_the_plan() {
+        let mut session = session(25);
+        assert_eq!(session.progress(), 0.0);
+        session.record(Duration::from_secs(10 * 60));
+        assert_eq!(session.remaining(), Duration::from_secs(15 * 60));
+        assert!((session.progress() - 0.4).abs() < 1e-6);
+        assert!(!session.is_finished());
+        session.record(Duration::from_secs(20 * 60));
+        assert_eq!(session.remaining(), Duration::ZERO);
+        assert_eq!(session.progress(), 1.0);
+        assert!(session.is_finished());
+    }
+
+    #[test]
+    fn an_unplanned_session_is_done() {
+        let session = session(0);
+        assert!(session.is_finished());
+        assert_eq!(session.progress(), 1.0);
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let mut session = session(25);
+        session.playlist = Some(PlaylistId(3));
+        session.record(Duration::from_millis(1500));
+        session.tags = vec![Tag::new("exam").unwrap()];
+        let json = serde_json::to_string(&session).unwrap();
+        assert_eq!(serde_json::from_str::<Session>(&json).unwrap(), session);
+    }
+}
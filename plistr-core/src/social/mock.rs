@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::{valid_handle, Comment, Post, PostId, Profile, Reply, Shared, SocialClient, SocialError, MAX_TEXT};
+use crate::{User, UserId};
+
+
+/// A whole social server in memory, for working offline and in tests.
+/// Clones share the same state, and each `MockClient` is one user signed
+/// in to it. Replies come back before the call returns.
+#[derive(Clone)]
+pub struct MockServer {
+    state: Arc<Mutex<State>>,
+    /// Seconds since the Unix epoch, for stamping posts and comments.
+    now: Arc<dyn Fn() -> u64 + Send + Sync>,
+}
+
+#[derive(Default)]
+struct State {
+    users: BTreeMap<UserId, (User, String)>,
+    posts: BTreeMap<PostId, Post>,
+}
+
+pub struct MockClient {
+    server: MockServer,
+    user: UserId,
+}
+
+impl MockServer {
+    /// `now` gives the time in seconds since the Unix epoch, which the
+    /// browser build can't get from `std`.
+    pub fn new(now: impl Fn() -> u64 + Send + Sync + 'static) -> Self {
+        Self { state: Arc::default(), now: Arc::new(now) }
+    }
+
+    /// Adds a user. Handles are unique, ignoring case.
+    pub fn sign_up(&self, handle: &str, display_name: &str) -> Result<UserId, SocialError> {
+        if !valid_handle(handle) {
+            return Err(SocialError::Rejected(format!("\"{handle}\" isn't a valid handle")));
+        }
+        let mut state = self.state();
+        if state.users.values().any(|(user, _)| user.handle.eq_ignore_ascii_case(handle)) {
+            return Err(SocialError::Rejected(format!("@{handle} is taken")));
+        }
+        let id = state.users.keys().next_back().map_or(UserId(1), |last| UserId(last.0 + 1));
+        state.users.insert(id, (User::new(id, handle, display_name), String::new()));
+        Ok(id)
+    }
+
+    /// A client signed in as `user`, who should have signed up first.
+    pub fn client(&self, user: UserId) -> MockClient {
+        MockClient { server: self.clone(), user }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MockClient {
+    /// Runs `change` on the server's state and answers with what it gives.
+    fn answer<T>(&self, reply: Reply<T>, change: impl FnOnce(&mut State, u64) -> Result<T, SocialError>) {
+        let now = (self.server.now)();
+        let result = change(&mut self.server.state(), now);
+        reply(result);
+    }
+}
+
+impl SocialClient for MockClient {
+    fn me(&self) -> UserId {
+        self.user
+    }
+
+    fn profile(&self, user: UserId, reply: Reply<Profile>) {
+        self.answer(reply, |state, _| state.profile(user))
+    }
+
+    fn update_profile(&self, display_name: String, bio: String, reply: Reply<Profile>) {
+        let me = self.user;
+        self.answer(reply, |state, _| {
+            if display_name.trim().is_empty() {
+                return Err(SocialError::Rejected("a name is needed".to_owned()));
+            }
+            check_length(&bio)?;
+            let (user, stored) = state.users.get_mut(&me).ok_or(SocialError::NotFound)?;
+            user.display_name = display_name.trim().to_owned();
+            *stored = bio;
+            state.profile(me)
+        })
+    }
+
+    fn people(&self, query: &str, reply: Reply<Vec<Profile>>) {
+        let query = query.to_lowercase();
+        self.answer(reply, |state, _| {
+            let mut people = state.users.values()
+                .filter(|(user, _)| {
+                    user.handle.to_lowercase().contains(&query) || user.display_name.to_lowercase().contains(&query)
+                })
+                .map(|(user, _)| state.profile(user.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            people.sort_by(|a, b| a.user.handle.cmp(&b.user.handle));
+            Ok(people)
+        })
+    }
+
+    fn follow(&self, user: UserId, reply: Reply<Profile>) {
+        let me = self.user;
+        self.answer(reply, |state, _| {
+            if user == me {
+                return Err(SocialError::Rejected("you can't follow yourself".to_owned()));
+            }
+            if !state.users.contains_key(&user) {
+                return Err(SocialError::NotFound);
+            }
+            state.users.get_mut(&me).ok_or(SocialError::NotFound)?.0.follow(user);
+            state.profile(me)
+        })
+    }
+
+    fn unfollow(&self, user: UserId, reply: Reply<Profile>) {
+        let me = self.user;
+        self.answer(reply, |state, _| {
+            state.users.get_mut(&me).ok_or(SocialError::NotFound)?.0.unfollow(user);
+            state.profile(me)
+        })
+    }
+
+    fn feed(&self, limit: usize, reply: Reply<Vec<Post>>) {
+        let me = self.user;
+        self.answer(reply, |state, _| {
+            let (user, _) = state.users.get(&me).ok_or(SocialError::NotFound)?;
+            let mut posts: Vec<Post> = state.posts.values()
+                .rev()
+                .filter(|post| post.author == me || user.following.contains(&post.author))
+                .cloned()
+                .collect();
+            // Later ids break ties, since the map was walked newest first.
+            posts.sort_by_key(|post| std::cmp::Reverse(post.posted_at));
+            posts.truncate(limit);
+            Ok(posts)
+        })
+    }
+
+    fn share(&self, shared: Shared, caption: String, reply: Reply<Post>) {
+        let me = self.user;
+        self.answer(reply, |state, now| {
+            if !state.users.contains_key(&me) {
+                return Err(SocialError::NotFound);
+            }
+            check_length(&caption)?;
+            let shared = match shared {
+                Shared::Session(session) if !session.is_finished() => {
+                    return Err(SocialError::Rejected("only finished sessions can be shared".to_owned()));
+                }
+                Shared::Session(session) => Shared::Session(crate::Session { user: me, ..session }),
+                playlist => playlist,
+            };
+            let id = state.posts.keys().next_back().map_or(PostId(1), |last| PostId(last.0 + 1));
+            let post = Post {
+                id,
+                author: me,
+                posted_at: now,
+                shared,
+                caption: caption.trim().to_owned(),
+                liked_by: Vec::new(),
+                comments: Vec::new(),
+            };
+            state.posts.insert(id, post.clone());
+            Ok(post)
+        })
+    }
+
+    fn like(&self, post: PostId, reply: Reply<Post>) {
+        let me = self.user;
+        self.answer(reply, |state, _| {
+            let post = state.posts.get_mut(&post).ok_or(SocialError::NotFound)?;
+            if !post.is_liked_by(me) {
+                post.liked_by.push(me);
+            }
+            Ok(post.clone())
+        })
+    }
+
+    fn unlike(&self, post: PostId, reply: Reply<Post>) {
+        let me = self.user;
+        self.answer(reply, |state, _| {
+            let post = state.posts.get_mut(&post).ok_or(SocialError::NotFound)?;
+            post.liked_by.retain(|&user| user != me);
+            Ok(post.clone())
+        })
+    }
+
+    fn comment(&self, post: PostId, text: String, reply: Reply<Post>) {
+        let me = self.user;
+        self.answer(reply, |state, now| {
+            let text = text.trim();
+            if text.is_empty() {
+                return Err(SocialError::Rejected("the comment is empty".to_owned()));
+            }
+            check_length(text)?;
+            let post = state.posts.get_mut(&post).ok_or(SocialError::NotFound)?;
+            post.comments.push(Comment { author: me, posted_at: now, text: text.to_owned() });
+            Ok(post.clone())
+        })
+    }
+}
+
+impl State {
+    fn profile(&self, id: UserId) -> Result<Profile, SocialError> {
+        let (user, bio) = self.users.get(&id).ok_or(SocialError::NotFound)?;
+        let followers = self.users.values().filter(|(other, _)| other.following.contains(&id)).count();
+        Ok(Profile { user: user.clone(), bio: bio.clone(), followers })
+    }
+}
+
+fn check_length(text: &str) -> Result<(), SocialError> {
+    if text.chars().count() > MAX_TEXT {
+        return Err(SocialError::Rejected(format!("longer than {MAX_TEXT} characters")));
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{Playlist, PlaylistId, Session, SessionId, SessionKind};
+
+    /// A server whose clock only moves when the test says so.
+    fn server() -> (MockServer, Arc<AtomicU64>) {
+        let clock = Arc::new(AtomicU64::new(1_700_000_000));
+        let now = clock.clone();
+        (MockServer::new(move || now.load(Ordering::SeqCst)), clock)
+    }
+
+    /// The answer to a call, which the mock gives before the call returns.
+    fn ask<T: Send + 'static>(call: impl FnOnce(Reply<T>)) -> Result<T, SocialError> {
+        let (send, receive) = mpsc::channel();
+        call(Box::new(move |result| send.send(result).unwrap()));
+        receive.try_recv().expect("no reply by the time the call returned")
+    }
+
+    fn playlist(name: &str) -> Shared {
+        Shared::Playlist(Playlist::new(PlaylistId(1), UserId(1), name))
+    }
+
+    fn feed(client: &MockClient, limit: usize) -> Vec<String> {
+        ask(|reply| client.feed(limit, reply)).unwrap().into_iter().map(|post| post.caption).collect()
+    }
+
+    #[test]
+    fn following_counts_and_can_be_undone() {
+        let (server, _) = server();
+        let (ada, bo) = (server.sign_up("ada", "Ada").unwrap(), server.sign_up("bo", "Bo").unwrap());
+        let client = server.client(ada);
+
+        let me = ask(|reply| client.follow(bo, reply)).unwrap();
+        assert_eq!(me.user.following, [bo]);
+        assert_eq!(ask(|reply| client.follow(bo, reply)).unwrap(), me);
+        assert_eq!(ask(|reply| client.profile(bo, reply)).unwrap().followers, 1);
+
+        let me = ask(|reply| client.unfollow(bo, reply)).unwrap();
+        assert!(me.user.following.is_empty());
+        assert_eq!(ask(|reply| client.profile(bo, reply)).unwrap().followers, 0);
+        assert!(ask(|reply| client.unfollow(bo, reply)).is_ok());
+
+        assert!(matches!(ask(|reply| client.follow(ada, reply)), Err(SocialError::Rejected(_))));
+        assert_eq!(ask(|reply| client.follow(UserId(99), reply)), Err(SocialError::NotFound));
+    }
+
+    #[test]
+    fn the_feed_is_mine_and_who_i_follow_latest_first() {
+        let (server, clock) = server();
+        let [ada, bo, cy] = ["ada", "bo", "cy"].map(|handle| server.sign_up(handle, handle).unwrap());
+        let share = |user, caption: &str| {
+            ask(|reply| server.client(user).share(playlist(caption), caption.to_owned(), reply)).unwrap();
+        };
+        share(bo, "bo early");
+        clock.fetch_add(60, Ordering::SeqCst);
+        share(ada, "ada");
+        share(cy, "cy");
+        clock.fetch_add(60, Ordering::SeqCst);
+        share(bo, "bo late");
+
+        let client = server.client(ada);
+        assert_eq!(feed(&client, 10), ["ada"]);
+        ask(|reply| client.follow(bo, reply)).unwrap();
+        ask(|reply| client.follow(cy, reply)).unwrap();
+        // "ada" and "cy" went up in the same second, so the later one leads.
+        assert_eq!(feed(&client, 10), ["bo late", "cy", "ada", "bo early"]);
+        assert_eq!(feed(&client, 2), ["bo late", "cy"]);
+        ask(|reply| client.unfollow(bo, reply)).unwrap();
+        assert_eq!(feed(&client, 10), ["cy", "ada"]);
+    }
+
+    #[test]
+    fn likes_count_each_person_once() {
+        let (server, _) = server();
+        let (ada, bo) = (server.sign_up("ada", "Ada").unwrap(), server.sign_up("bo", "Bo").unwrap());
+        let post = ask(|reply| server.client(ada).share(playlist("Reading"), String::new(), reply)).unwrap();
+
+        let liked = ask(|reply| server.client(bo).like(post.id, reply)).unwrap();
+        assert_eq!(ask(|reply| server.client(bo).like(post.id, reply)).unwrap(), liked);
+        let liked = ask(|reply| server.client(ada).like(post.id, reply)).unwrap();
+        assert_eq!(liked.likes(), 2);
+        assert!(liked.is_liked_by(ada) && liked.is_liked_by(bo));
+
+        let unliked = ask(|reply| server.client(bo).unlike(post.id, reply)).unwrap();
+        assert_eq!(unliked.liked_by, [ada]);
+        assert_eq!(ask(|reply| server.client(bo).like(PostId(99), reply)), Err(SocialError::NotFound));
+    }
+
+    #[test]
+    fn comments_are_kept_in_order_and_checked() {
+        let (server, clock) = server();
+        let (ada, bo) = (server.sign_up("ada", "Ada").unwrap(), server.sign_up("bo", "Bo").unwrap());
+        let post = ask(|reply| server.client(ada).share(playlist("Reading"), String::new(), reply)).unwrap();
+
+        ask(|reply| server.client(bo).comment(post.id, "  nice  ".to_owned(), reply)).unwrap();
+        clock.fetch_add(5, Ordering::SeqCst);
+        let post = ask(|reply| server.client(ada).comment(post.id, "thanks".to_owned(), reply)).unwrap();
+        let comments: Vec<_> = post.comments.iter().map(|c| (c.author, c.posted_at, c.text.as_str())).collect();
+        assert_eq!(comments, [(bo, 1_700_000_000, "nice"), (ada, 1_700_000_005, "thanks")]);
+
+        let client = server.client(bo);
+        assert!(matches!(ask(|reply| client.comment(post.id, " ".to_owned(), reply)), Err(SocialError::Rejected(_))));
+        let long = "a".repeat(MAX_TEXT + 1);
+        assert!(matches!(ask(|reply| client.comment(post.id, long, reply)), Err(SocialError::Rejected(_))));
+        assert_eq!(ask(|reply| client.comment(PostId(99), "hi".to_owned(), reply)), Err(SocialError::NotFound));
+    }
+
+    #[test]
+    fn only_finished_sessions_are_shared_and_as_the_sharer() {
+        let (server, _) = server();
+        let ada = server.sign_up("ada", "Ada").unwrap();
+        let client = server.client(ada);
+        let mut session = Session::new(SessionId(4), UserId(99), SessionKind::Study, 0, Duration::from_secs(25 * 60));
+        session.record(Duration::from_secs(10 * 60));
+
+        let unfinished = ask(|reply| client.share(Shared::Session(session.clone()), String::new(), reply));
+        assert!(matches!(unfinished, Err(SocialError::Rejected(_))));
+        assert!(feed(&client, 10).is_empty());
+
+        session.record(Duration::from_secs(15 * 60));
+        let post = ask(|reply| client.share(Shared::Session(session.clone()), " done! ".to_owned(), reply)).unwrap();
+        assert_eq!((post.author, post.caption.as_str()), (ada, "done!"));
+        assert_eq!(post.shared, Shared::Session(Session { user: ada, ..session }));
+        assert_eq!(ask(|reply| client.feed(10, reply)).unwrap(), [post]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Profiles, following and a feed of what people share: playlists and
+//! finished study sessions, which others can like and comment on. The app
+//! only talks to a `SocialClient`; `MockServer` plays the server in-process
+//! so all of it works offline.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Playlist, Session, User, UserId};
+
+mod mock;
+
+pub use mock::{MockClient, MockServer};
+
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct PostId(pub u64);
+
+/// What others see of a user.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub user: User,
+    #[serde(default)]
+    pub bio: String,
+    /// How many people follow this user.
+    #[serde(default)]
+    pub followers: usize,
+}
+
+/// Something put in the feed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Shared {
+    Playlist(Playlist),
+    /// Only finished sessions can be shared.
+    Session(Session),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Post {
+    pub id: PostId,
+    pub author: UserId,
+    /// Seconds since the Unix epoch.
+    pub posted_at: u64,
+    pub shared: Shared,
+    #[serde(default)]
+    pub caption: String,
+    /// Each user at most once.
+    #[serde(default)]
+    pub liked_by: Vec<UserId>,
+    /// Oldest first.
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+}
+
+impl Post {
+    pub fn likes(&self) -> usize {
+        self.liked_by.len()
+    }
+
+    pub fn is_liked_by(&self, user: UserId) -> bool {
+        self.liked_by.contains(&user)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub author: UserId,
+    /// Seconds since the Unix epoch.
+    pub posted_at: u64,
+    pub text: String,
+}
+
+/// Gets a request's answer. It may be called on another thread, and later
+/// than the call that took it, but always exactly once.
+pub type Reply<T> = Box<dyn FnOnce(Result<T, SocialError>) + Send>;
+
+/// The social side of the server, as one signed-in user sees it. Every
+/// call returns straight away and answers through its `Reply`, so the
+/// browser build can sit on an HTTP client as easily as on `MockClient`.
+pub trait SocialClient {
+    /// Who this client is signed in as.
+    fn me(&self) -> UserId;
+
+    fn profile(&self, user: UserId, reply: Reply<Profile>);
+    /// Changes the signed-in user's name and bio.
+    fn update_profile(&self, display_name: String, bio: String, reply: Reply<Profile>);
+    /// Everyone whose handle or name contains `query`, ignoring case; an
+    /// empty query finds everyone. Ordered by handle.
+    fn people(&self, query: &str, reply: Reply<Vec<Profile>>);
+
+    /// Answers with the signed-in user's profile as it is after the change.
+    /// Following someone already followed isn't an error.
+    fn follow(&self, user: UserId, reply: Reply<Profile>);
+    fn unfollow(&self, user: UserId, reply: Reply<Profile>);
+
+    /// Up to `limit` posts by the signed-in user and the people they
+    /// follow, latest first.
+    fn feed(&self, limit: usize, reply: Reply<Vec<Post>>);
+    fn share(&self, shared: Shared, caption: String, reply: Reply<Post>);
+    /// Answer with the post as it is after the change. Liking twice counts
+    /// once.
+    fn like(&self, post: PostId, reply: Reply<Post>);
+    fn unlike(&self, post: PostId, reply: Reply<Post>);
+    fn comment(&self, post: PostId, text: String, reply: Reply<Post>);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SocialError {
+    /// No such user or post.
+    NotFound,
+    /// The server turned the request down, and says why.
+    Rejected(String),
+    /// The server couldn't be reached or answered with nonsense.
+    Network(String),
+}
+
+impl fmt::Display for SocialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SocialError::NotFound => write!(f, "not found"),
+            SocialError::Rejected(reason) => write!(f, "{reason}"),
+            SocialError::Network(message) => write!(f, "couldn't reach the server: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SocialError {}
+
+/// Handles are letters, digits and underscores.
+pub fn valid_handle(handle: &str) -> bool {
+    !handle.is_empty() && handle.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+
+/// Longest comment or caption, in characters.
+pub const MAX_TEXT: usize = 500;
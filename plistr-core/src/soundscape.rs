@@ -0,0 +1,484 @@
+use std::f64::consts::TAU;
+use std::io::{Seek, Write};
+
+use serde::{Deserialize, Serialize};
+
+
+/// Background sound made on the spot instead of downloaded: layers of noise,
+/// weather and binaural beats mixed at their own volumes. The same seed
+/// always gives the same samples.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Soundscape {
+    pub layers: Vec<Layer>,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub sound: Sound,
+    /// From 0 to 1.
+    pub volume: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Sound {
+    /// Equal energy at every frequency; a bright hiss.
+    White,
+    /// Falls off 3 dB an octave; softer, like a waterfall.
+    Pink,
+    /// Falls off 6 dB an octave; a low rumble.
+    Brown,
+    /// A wash of falling water with drops landing here and there.
+    Rain,
+    /// Low noise that swells and fades in slow gusts.
+    Wind,
+    /// A tone in each ear, `beat` hertz apart around `carrier`, heard as a
+    /// slow pulse on headphones.
+    Binaural { carrier: f32, beat: f32 },
+}
+
+/// Renders a `Soundscape` a buffer at a time, as interleaved stereo.
+pub struct Generator {
+    rate: f64,
+    layers: Vec<(f32, Voice)>,
+}
+
+enum Voice {
+    White(Random),
+    Pink(Random, [Pink; 2]),
+    Brown(Random, [f32; 2]),
+    Rain(Rain),
+    Wind(Wind),
+    Binaural { left: f64, right: f64, phase: [f64; 2] },
+}
+
+#[derive(Default)]
+struct Pink([f32; 7]);
+
+struct Rain {
+    random: Random,
+    /// Band-limits the wash: everything above `low` and below `high`.
+    low: [f32; 2],
+    high: [f32; 2],
+    drops: Vec<Drop>,
+}
+
+struct Drop {
+    left: f32,
+    right: f32,
+    /// Multiplied into the level every sample.
+    decay: f32,
+    level: f32,
+    filtered: f32,
+}
+
+struct Wind {
+    random: Random,
+    filtered: [f32; 2],
+    /// How strong the gust is, drifting between 0 and 1.
+    gust: f32,
+    target: f32,
+}
+
+/// xorshift64*: small, fast, and the same on every platform.
+struct Random(u64);
+
+impl Soundscape {
+    /// Everything is rendered in stereo, for binaural beats.
+    pub const CHANNELS: u16 = 2;
+
+    pub fn generator(&self, sample_rate: u32) -> Generator {
+        Generator::new(self, sample_rate)
+    }
+
+    /// `seconds` of interleaved stereo.
+    pub fn render(&self, sample_rate: u32, seconds: f32) -> Vec<f32> {
+        let mut out = vec![0.0; frames(sample_rate, seconds) * Self::CHANNELS as usize];
+        self.generator(sample_rate).fill(&mut out);
+        out
+    }
+
+    /// Like `render`, but the end runs smoothly back into the start, for
+    /// players that loop a fixed buffer.
+    pub fn render_loop(&self, sample_rate: u32, seconds: f32) -> Vec<f32> {
+        let overlap = frames(sample_rate, LOOP_OVERLAP.min(seconds / 2.0)) * Self::CHANNELS as usize;
+        let mut out = self.render(sample_rate, seconds + LOOP_OVERLAP.min(seconds / 2.0));
+        let tail = out.split_off(out.len() - overlap);
+        for (i, (head, tail)) in out.iter_mut().zip(tail).enumerate() {
+            // Equal power, so the level doesn't dip in the middle.
+            let t = (i / Self::CHANNELS as usize) as f32 / (overlap / Self::CHANNELS as usize) as f32;
+            let angle = t * std::f32::consts::FRAC_PI_2;
+            *head = *head * angle.sin() + tail * angle.cos();
+        }
+        out
+    }
+
+    /// `seconds` as a 16-bit stereo WAV file.
+    pub fn write_wav<W: Write + Seek>(&self, out: W, sample_rate: u32, seconds: f32) -> Result<(), hound::Error> {
+        let spec = hound::WavSpec {
+            channels: Self::CHANNELS,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(out, spec)?;
+        let mut generator = self.generator(sample_rate);
+        let mut buffer = vec![0.0; WAV_CHUNK * Self::CHANNELS as usize];
+        let mut left = frames(sample_rate, seconds) * Self::CHANNELS as usize;
+        while left > 0 {
+            let chunk = &mut buffer[..left.min(WAV_CHUNK * Self::CHANNELS as usize)];
+            generator.fill(chunk);
+            for sample in chunk.iter() {
+                writer.write_sample((sample * i16::MAX as f32) as i16)?;
+            }
+            left -= chunk.len();
+        }
+        writer.finalize()
+    }
+}
+
+impl Sound {
+    /// Tells the kinds apart, whatever a binaural tone is set to.
+    fn key(self) -> u64 {
+        match self {
+            Sound::White => 0,
+            Sound::Pink => 1,
+            Sound::Brown => 2,
+            Sound::Rain => 3,
+            Sound::Wind => 4,
+            Sound::Binaural { .. } => 5,
+        }
+    }
+}
+
+impl Generator {
+    pub fn new(soundscape: &Soundscape, sample_rate: u32) -> Self {
+        let rate = f64::from(sample_rate.max(1));
+        let layers = soundscape.layers.iter().enumerate()
+            .filter(|(_, layer)| layer.volume > 0.0)
+            .map(|(i, layer)| {
+                // Each layer gets its own stream, picked by what it plays
+                // rather than where it sits, so adding, removing or muting
+                // one doesn't change how the others sound. A second layer
+                // of the same kind gets the next stream along.
+                let repeat = soundscape.layers[..i].iter().filter(|other| other.sound.key() == layer.sound.key()).count();
+                let stream = (repeat as u64) << 8 | layer.sound.key();
+                let random = Random::new(soundscape.seed.wrapping_add(stream));
+                (layer.volume.clamp(0.0, 1.0), Voice::new(layer.sound, random, rate))
+            })
+            .collect();
+        Self { rate, layers }
+    }
+
+    /// Overwrites `out`, interleaved stereo, with what comes next.
+    pub fn fill(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        for (volume, voice) in &mut self.layers {
+            for frame in out.chunks_exact_mut(Soundscape::CHANNELS as usize) {
+                let [left, right] = voice.next(self.rate);
+                frame[0] += left * *volume;
+                frame[1] += right * *volume;
+            }
+        }
+        out.iter_mut().for_each(|sample| *sample = sample.clamp(-1.0, 1.0));
+    }
+}
+
+impl Voice {
+    fn new(sound: Sound, random: Random, rate: f64) -> Self {
+        match sound {
+            Sound::White => Voice::White(random),
+            Sound::Pink => Voice::Pink(random, Default::default()),
+            Sound::Brown => Voice::Brown(random, [0.0; 2]),
+            Sound::Rain => Voice::Rain(Rain { random, low: [0.0; 2], high: [0.0; 2], drops: Vec::new() }),
+            Sound::Wind => Voice::Wind(Wind { random, filtered: [0.0; 2], gust: 0.5, target: 0.5 }),
+            Sound::Binaural { carrier, beat } => {
+                let carrier = f64::from(carrier.max(0.0));
+                let half = f64::from(beat.abs()) / 2.0;
+                Voice::Binaural {
+                    left: (carrier - half).max(0.0) / rate,
+                    right: (carrier + half) / rate,
+                    phase: [0.0; 2],
+                }
+            }
+        }
+    }
+
+    /// One stereo frame, at roughly the same loudness whatever the sound.
+    fn next(&mut self, rate: f64) -> [f32; 2] {
+        match self {
+            Voice::White(random) => [random.signed() * 0.25, random.signed() * 0.25],
+            Voice::Pink(random, pink) => pink.each_mut().map(|pink| pink.next(random.signed()) * 0.75),
+            Voice::Brown(random, brown) => brown.each_mut().map(|last| {
+                *last = (*last + 0.02 * random.signed()) / 1.02;
+                *last * 2.5
+            }),
+            Voice::Rain(rain) => rain.next(rate),
+            Voice::Wind(wind) => wind.next(rate),
+            Voice::Binaural { left, right, phase } => {
+                let out = [(TAU * phase[0]).sin() as f32 * 0.2, (TAU * phase[1]).sin() as f32 * 0.2];
+                phase[0] = (phase[0] + *left).fract();
+                phase[1] = (phase[1] + *right).fract();
+                out
+            }
+        }
+    }
+}
+
+impl Pink {
+    /// Paul Kellett's filter: white noise through seven one-pole stages.
+    fn next(&mut self, white: f32) -> f32 {
+        let b = &mut self.0;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let out = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+        out * 0.11
+    }
+}
+
+impl Rain {
+    fn next(&mut self, rate: f64) -> [f32; 2] {
+        let low = one_pole(RAIN_LOW_HZ, rate);
+        let high = one_pole(RAIN_HIGH_HZ, rate);
+        let mut out = [0.0; 2];
+        for (channel, out) in out.iter_mut().enumerate() {
+            let white = self.random.signed();
+            self.low[channel] += low * (white - self.low[channel]);
+            self.high[channel] += high * (white - self.high[channel]);
+            *out = (self.high[channel] - self.low[channel]) * 0.45;
+        }
+        if f64::from(self.random.unit()) < DROPS_PER_SECOND / rate {
+            let pan = self.random.unit();
+            // Each drop is a tick of noise dying away in a few milliseconds.
+            let length = 0.002 + 0.006 * self.random.unit();
+            self.drops.push(Drop {
+                left: (1.0 - pan).sqrt(),
+                right: pan.sqrt(),
+                decay: (-1.0 / (length * rate as f32)).exp(),
+                level: 0.15 + 0.35 * self.random.unit(),
+                filtered: 0.0,
+            });
+        }
+        for drop in &mut self.drops {
+            drop.filtered += 0.5 * (self.random.signed() - drop.filtered);
+            let sample = drop.filtered * drop.level;
+            out[0] += sample * drop.left;
+            out[1] += sample * drop.right;
+            drop.level *= drop.decay;
+        }
+        self.drops.retain(|drop| drop.level > 0.001);
+        out
+    }
+}
+
+impl Wind {
+    fn next(&mut self, rate: f64) -> [f32; 2] {
+        // Picks a new strength every so often and drifts towards it.
+        if f64::from(self.random.unit()) < 1.0 / (WIND_GUST_SECONDS * rate) {
+            self.target = self.random.unit();
+        }
+        self.gust += one_pole(1.0 / WIND_GUST_SECONDS, rate) * (self.target - self.gust);
+        // Stronger gusts sound brighter as well as louder.
+        let cutoff = one_pole(WIND_LOW_HZ + (WIND_HIGH_HZ - WIND_LOW_HZ) * f64::from(self.gust), rate);
+        let level = 0.4 + 1.2 * self.gust;
+        let mut out = [0.0; 2];
+        for (channel, out) in out.iter_mut().enumerate() {
+            let white = self.random.signed();
+            self.filtered[channel] += cutoff * (white - self.filtered[channel]);
+            *out = self.filtered[channel] * level * 1.1;
+        }
+        out
+    }
+}
+
+impl Random {
+    /// Spreads nearby seeds apart (SplitMix64), so seeds 1 and 2 don't start
+    /// out alike.
+    fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        // xorshift gets stuck at zero.
+        Self((z ^ (z >> 31)).max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// From 0 up to 1.
+    fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// From -1 to 1.
+    fn signed(&mut self) -> f32 {
+        self.unit() * 2.0 - 1.0
+    }
+}
+
+/// The coefficient for a one-pole low-pass at `hz`.
+fn one_pole(hz: f64, rate: f64) -> f32 {
+    (1.0 - (-TAU * hz / rate).exp()) as f32
+}
+
+fn frames(sample_rate: u32, seconds: f32) -> usize {
+    (f64::from(sample_rate) * f64::from(seconds.max(0.0))) as usize
+}
+
+
+/// Seconds of a looped render spent fading its end into its start.
+const LOOP_OVERLAP: f32 = 2.0;
+
+/// Frames generated at a time when writing a WAV file.
+const WAV_CHUNK: usize = 4096;
+
+const RAIN_LOW_HZ: f64 = 400.0;
+const RAIN_HIGH_HZ: f64 = 5000.0;
+const DROPS_PER_SECOND: f64 = 40.0;
+
+const WIND_LOW_HZ: f64 = 150.0;
+const WIND_HIGH_HZ: f64 = 900.0;
+/// Roughly how long a gust lasts.
+const WIND_GUST_SECONDS: f64 = 3.0;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(seed: u64) -> Soundscape {
+        let layer = |sound, volume| Layer { sound, volume };
+        Soundscape { layers: vec![layer(Sound::White, 0.3), layer(Sound::Pink, 0.5), layer(Sound::Brown, 0.4)], seed }
+    }
+
+    fn everything(seed: u64) -> Soundscape {
+        let mut soundscape = noise(seed);
+        soundscape.layers.extend([
+            Layer { sound: Sound::Rain, volume: 0.6 },
+            Layer { sound: Sound::Wind, volume: 0.5 },
+            Layer { sound: Sound::Binaural { carrier: 200.0, beat: 10.0 }, volume: 0.3 },
+        ]);
+        soundscape
+    }
+
+    /// FNV-1a over the samples' bits, so any change at all shows.
+    fn fingerprint(samples: &[f32]) -> u64 {
+        samples.iter().flat_map(|sample| sample.to_bits().to_le_bytes()).fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_samples() {
+        let soundscape = everything(7);
+        let whole = soundscape.render(22_050, 1.0);
+        assert_eq!(whole, soundscape.render(22_050, 1.0));
+        // However it's split into buffers.
+        let mut generator = soundscape.generator(22_050);
+        let mut pieces = Vec::new();
+        for frames in [1, 100, 4096].into_iter().cycle() {
+            let left = whole.len() - pieces.len();
+            if left == 0 {
+                break;
+            }
+            let mut buffer = vec![0.0; (frames * 2).min(left)];
+            generator.fill(&mut buffer);
+            pieces.extend(buffer);
+        }
+        assert_eq!(pieces, whole);
+    }
+
+    #[test]
+    fn different_seeds_sound_different() {
+        let first = everything(1).render(22_050, 0.5);
+        let second = everything(2).render(22_050, 0.5);
+        assert_ne!(first, second);
+        assert!(first.iter().zip(&second).filter(|(a, b)| a != b).count() > first.len() / 2);
+    }
+
+    #[test]
+    fn noise_matches_its_snapshot() {
+        // Nothing but integer hashing and float arithmetic, so the bits are
+        // the same on every platform.
+        assert_eq!(fingerprint(&noise(42).render(44_100, 2.0)), NOISE_42);
+    }
+
+    #[test]
+    fn everything_matches_its_snapshot() {
+        // Rain, wind and binaural use `exp` and `sin`, which may differ in
+        // the last bit between platforms, so these are compared loosely.
+        let samples = everything(42).render(44_100, 0.5);
+        let start = &samples[..EVERYTHING_42_START.len()];
+        for (i, (sample, expected)) in start.iter().zip(EVERYTHING_42_START).enumerate() {
+            assert!((sample - expected).abs() < 1e-5, "sample {i}: {sample} isn't {expected}");
+        }
+        let end = &samples[samples.len() - EVERYTHING_42_END.len()..];
+        for (i, (sample, expected)) in end.iter().zip(EVERYTHING_42_END).enumerate() {
+            assert!((sample - expected).abs() < 1e-4, "sample {i} from the end: {sample} isn't {expected}");
+        }
+    }
+
+    #[test]
+    fn silent_layers_are_left_out() {
+        let mut soundscape = noise(3);
+        soundscape.layers[1].volume = 0.0;
+        let generator = soundscape.generator(8000);
+        assert_eq!(generator.layers.len(), 2);
+        assert!(Soundscape::default().render(8000, 0.1).iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn layers_sound_the_same_whatever_else_plays() {
+        let rain = |seed| Soundscape { layers: vec![Layer { sound: Sound::Rain, volume: 0.6 }], seed };
+        let alone = rain(9).render(8000, 0.5);
+        let mut mix = everything(9);
+        for layer in &mut mix.layers {
+            if layer.sound != Sound::Rain {
+                layer.volume = 0.0;
+            }
+        }
+        assert_eq!(mix.render(8000, 0.5), alone);
+        mix.layers.retain(|layer| layer.sound == Sound::Rain);
+        assert_eq!(mix.render(8000, 0.5), alone);
+        // A second rain layer doesn't just double the first.
+        mix.layers.push(Layer { sound: Sound::Rain, volume: 0.6 });
+        let doubled: Vec<_> = alone.iter().map(|sample| (sample * 2.0).clamp(-1.0, 1.0)).collect();
+        assert_ne!(mix.render(8000, 0.5), doubled);
+    }
+
+    #[test]
+    fn wav_files_have_the_right_header_and_length() {
+        let mut file = std::io::Cursor::new(Vec::new());
+        noise(5).write_wav(&mut file, 8000, 1.5).unwrap();
+        file.set_position(0);
+        let reader = hound::WavReader::new(file).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.channels, Soundscape::CHANNELS);
+        assert_eq!(spec.sample_rate, 8000);
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(spec.sample_format, hound::SampleFormat::Int);
+        // Longer than one chunk, and not a whole number of them.
+        assert_eq!(reader.duration(), 12_000);
+        let samples: Vec<i16> = reader.into_samples().map(Result::unwrap).collect();
+        let expected: Vec<i16> = noise(5).render(8000, 1.5).iter().map(|sample| (sample * i16::MAX as f32) as i16).collect();
+        assert_eq!(samples, expected);
+    }
+
+    /// Two seconds at 44.1 kHz of `noise(42)`.
+    const NOISE_42: u64 = 0x4090_D8BD_D0F6_EBC4;
+    /// The first and last four frames of half a second of `everything(42)`.
+    const EVERYTHING_42_START: [f32; 8] =
+        [-0.06066219, -0.16604517, -0.03345084, -0.24337322, 0.039234616, -0.037882138, -0.08681385, 0.12553187];
+    const EVERYTHING_42_END: [f32; 8] =
+        [-0.0019779042, -0.07541505, -0.259406, -0.072246395, -0.037829034, 0.025147252, -0.18512033, -0.12324297];
+}
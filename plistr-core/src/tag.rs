@@ -0,0 +1,106 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+
+/// A lowercase label like `lofi` or `deep-focus`. Spaces become dashes so
+/// "Deep Focus" and "deep focus" are the same tag.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Tag(String);
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum TagError {
+    Empty,
+    TooLong(usize),
+    /// Only letters, digits and dashes are kept.
+    InvalidChar(char),
+}
+
+impl Tag {
+    pub fn new(text: &str) -> Result<Self, TagError> {
+        let normalized = text.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+        if normalized.is_empty() {
+            return Err(TagError::Empty);
+        }
+        let length = normalized.chars().count();
+        if length > MAX_LENGTH {
+            return Err(TagError::TooLong(length));
+        }
+        if let Some(c) = normalized.chars().find(|&c| !(c.is_alphanumeric() || c == '-')) {
+            return Err(TagError::InvalidChar(c));
+        }
+        Ok(Tag(normalized))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Tag {
+    type Error = TagError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Tag::new(&text)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl fmt::Display for TagError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TagError::Empty => write!(f, "tags can't be empty"),
+            TagError::TooLong(length) => write!(f, "tags are at most {MAX_LENGTH} characters, not {length}"),
+            TagError::InvalidChar(c) => write!(f, "tags can't contain {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+
+const MAX_LENGTH: usize = 32;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_case_and_spaces() {
+        assert_eq!(Tag::new("  Deep   Focus ").unwrap().as_str(), "deep-focus");
+        assert_eq!(Tag::new("LoFi").unwrap(), Tag::new("lofi").unwrap());
+        assert_eq!(Tag::new("café").unwrap().as_str(), "café");
+        assert_eq!(Tag::new("deep focus").unwrap().to_string(), "#deep-focus");
+    }
+
+    #[test]
+    fn rejects_empty_long_and_odd_tags() {
+        assert_eq!(Tag::new("   "), Err(TagError::Empty));
+        assert_eq!(Tag::new(&"a".repeat(33)), Err(TagError::TooLong(33)));
+        assert!(Tag::new(&"a".repeat(32)).is_ok());
+        assert_eq!(Tag::new("lo#fi"), Err(TagError::InvalidChar('#')));
+    }
+
+    #[test]
+    fn serde_round_trip_normalizes_and_validates() {
+        let tag = Tag::new("Deep Focus").unwrap();
+        let json = serde_json::to_string(&tag).unwrap();
+        assert_eq!(json, r#""deep-focus""#);
+        assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), tag);
+        assert_eq!(serde_json::from_str::<Tag>(r#""Deep Focus""#).unwrap(), tag);
+        assert!(serde_json::from_str::<Tag>(r#""lo#fi""#).is_err());
+    }
+}
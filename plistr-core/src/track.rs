@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Tag;
+
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct TrackId(pub u64);
+
+/// One piece of audio. `source` is whatever the player needs to find it: a
+/// file path for the native app or a URL for the web.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    pub id: TrackId,
+    pub title: String,
+    #[serde(default)]
+    pub artist: Option<String>,
+    /// Unknown until the file has been read.
+    #[serde(default)]
+    pub duration: Option<Duration>,
+    pub source: String,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+impl TrackId {
+    /// An id for a track that isn't in any repository, like a dropped file
+    /// or a line of an imported playlist. These come from the top half of
+    /// the range, so they never meet a repository's, which count up from 1.
+    pub fn unstored() -> Self {
+        TrackId(NEXT_UNSTORED.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn is_stored(self) -> bool {
+        self.0 < UNSTORED
+    }
+}
+
+impl Track {
+    pub fn new(id: TrackId, title: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            id,
+            title: title.into(),
+            artist: None,
+            duration: None,
+            source: source.into(),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn has_tag(&self, tag: &Tag) -> bool {
+        self.tags.contains(tag)
+    }
+}
+
+
+static NEXT_UNSTORED: AtomicU64 = AtomicU64::new(UNSTORED);
+
+const UNSTORED: u64 = 1 << 63;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unstored_ids_are_unique_and_clear_of_stored_ones() {
+        let (first, second) = (TrackId::unstored(), TrackId::unstored());
+        assert_ne!(first, second);
+        assert!(!first.is_stored() && !second.is_stored());
+        assert!(TrackId(1).is_stored());
+        assert!(TrackId(u64::from(u32::MAX)).is_stored());
+    }
+}
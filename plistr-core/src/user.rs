@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct UserId(pub u64);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: UserId,
+    /// Unique, shown as `@handle`.
+    pub handle: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub following: Vec<UserId>,
+}
+
+impl User {
+    pub fn new(id: UserId, handle: impl Into<String>, display_name: impl Into<String>) -> Self {
+        Self {
+            id,
+            handle: handle.into(),
+            display_name: display_name.into(),
+            following: Vec::new(),
+        }
+    }
+
+    /// Returns false if `other` was already followed, or is this user.
+    pub fn follow(&mut self, other: UserId) -> bool {
+        if other == self.id || self.following.contains(&other) {
+            return false;
+        }
+        self.following.push(other);
+        true
+    }
+
+    pub fn unfollow(&mut self, other: UserId) -> bool {
+        let before = self.following.len();
+        self.following.retain(|&id| id != other);
+        self.following.len() != before
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_each_user_once_and_never_itself() {
+        let mut user = User::new(UserId(1), "ana", "Ana");
+        assert!(user.follow(UserId(2)));
+        assert!(!user.follow(UserId(2)));
+        assert!(!user.follow(UserId(1)));
+        assert_eq!(user.following, [UserId(2)]);
+        assert!(user.unfollow(UserId(2)));
+        assert!(!user.unfollow(UserId(2)));
+        assert!(user.following.is_empty());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let mut user = User::new(UserId(1), "ana", "Ana");
+        user.follow(UserId(7));
+        let json = serde_json::to_string(&user).unwrap();
+        assert_eq!(serde_json::from_str::<User>(&json).unwrap(), user);
+        let bare: User = serde_json::from_str(r#"{"id":2,"handle":"bo","display_name":"Bo"}"#).unwrap();
+        assert!(bare.following.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! `plistr-relay`, the server study rooms meet on, and a way to try it out:
+//!
+//! ```text
+//! plistr-relay serve [--listen 127.0.0.1:9001]
+//! plistr-relay simulate [--url ws://127.0.0.1:9001] [--clients 4] [--latency 80]
+//!                       [--jitter 30] [--seconds 20] [--tolerance 100] [--seed 1]
+//! ```
+//!
+//! `serve` speaks the room protocol as JSON over WebSocket, one text
+//! message per `ClientMessage` or `ServerMessage`. `simulate` connects
+//! made-up members to a running relay, delays everything they send and
+//! receive, and checks they still hear the same thing at the same time.
+
+use std::collections::HashMap;
+use std::net::{TcpListener, TcpStream};
+use std::process::ExitCode;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use plistr_core::{ClientMessage, ConnectionId, Relay, ServerMessage};
+use tungstenite::Message;
+
+mod simulate;
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["serve", options @ ..] => Options::parse(options, &["--listen"]).and_then(|options| {
+            let address = options.get("--listen").unwrap_or(DEFAULT_ADDRESS);
+            let listener = TcpListener::bind(address).map_err(|e| format!("{address}: {e}"))?;
+            serve(listener)
+        }),
+        ["simulate", options @ ..] => Options::parse(options, simulate::OPTIONS).and_then(|options| {
+            let outcome = simulate::run(&simulate::Settings::from_options(&options)?)?;
+            println!("worst disagreement {} ms", outcome.worst);
+            if let Some(catch_up) = outcome.catch_up {
+                println!("the last member caught up {} ms after joining", catch_up.as_millis());
+            }
+            if let Some(end) = outcome.end.and_then(|end| end.current) {
+                println!("the host ended on track {}", end + 1);
+            }
+            Ok(())
+        }),
+        ["help" | "--help" | "-h"] => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprint!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `--name value` pairs, each name at most once.
+pub struct Options<'a>(HashMap<&'a str, &'a str>);
+
+impl<'a> Options<'a> {
+    fn parse(args: &[&'a str], known: &[&str]) -> Result<Self, String> {
+        let mut options = HashMap::new();
+        for pair in args.chunks(2) {
+            match pair {
+                [name, value] if known.contains(name) => {
+                    if options.insert(*name, *value).is_some() {
+                        return Err(format!("{name} given twice"));
+                    }
+                }
+                [name] if known.contains(name) => return Err(format!("{name} needs a value")),
+                [name, ..] => return Err(format!("unknown option {name}\n{USAGE}")),
+                [] => {}
+            }
+        }
+        Ok(Self(options))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.0.get(name).copied()
+    }
+
+    /// The option as a number, or `default` if it wasn't given.
+    pub fn number(&self, name: &str, default: u64) -> Result<u64, String> {
+        match self.get(name) {
+            Some(value) => value.parse().map_err(|_| format!("{name} should be a whole number, not \"{value}\"")),
+            None => Ok(default),
+        }
+    }
+}
+
+/// Everyone connected: the rooms, and a way to reach each connection's
+/// thread with messages for it.
+struct Hub {
+    relay: Relay,
+    outboxes: HashMap<ConnectionId, mpsc::Sender<String>>,
+    last_connection: u64,
+    /// Relay time is milliseconds since this.
+    started: Instant,
+}
+
+impl Hub {
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn deliver(&self, outbox: Vec<(ConnectionId, ServerMessage)>) {
+        for (to, message) in outbox {
+            if let Some(sender) = self.outboxes.get(&to) {
+                let _ = sender.send(message.to_json());
+            }
+        }
+    }
+}
+
+/// Port 0 in `--listen` picks a free port, and the address printed is the
+/// one it got.
+fn serve(listener: TcpListener) -> Result<(), String> {
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+    println!("relay listening on ws://{address}");
+    let hub = Arc::new(Mutex::new(Hub {
+        relay: Relay::new(),
+        outboxes: HashMap::new(),
+        last_connection: 0,
+        started: Instant::now(),
+    }));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let hub = hub.clone();
+                std::thread::spawn(move || connection(hub, stream));
+            }
+            Err(e) => eprintln!("accepting a connection: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Runs one connection until it closes: messages from it go through the
+/// relay, and anything the relay has for it goes out between reads.
+fn connection(hub: Arc<Mutex<Hub>>, stream: TcpStream) {
+    let peer = stream.peer_addr().map_or_else(|_| "?".to_owned(), |address| address.to_string());
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("{peer}: handshake failed: {e}");
+            return;
+        }
+    };
+    if let Err(e) = socket.get_mut().set_read_timeout(Some(POLL)) {
+        eprintln!("{peer}: {e}");
+        return;
+    }
+    let (sender, outbox) = mpsc::channel();
+    let id = {
+        let mut hub = lock(&hub);
+        hub.last_connection += 1;
+        let id = ConnectionId(hub.last_connection);
+        hub.outboxes.insert(id, sender);
+        id
+    };
+    println!("{peer}: connected");
+    loop {
+        let sent = outbox.try_iter().try_for_each(|text: String| socket.send(Message::text(text)).map_err(|e| e.to_string()));
+        if let Err(e) = sent {
+            eprintln!("{peer}: {e}");
+            break;
+        }
+        match socket.read() {
+            Ok(message) if message.is_text() => {
+                let Ok(text) = message.into_text() else {
+                    continue;
+                };
+                let mut hub = lock(&hub);
+                let now = hub.now();
+                let outbox = match ClientMessage::from_json(&text) {
+                    Ok(message) => hub.relay.handle(id, message, now),
+                    Err(e) => vec![(id, ServerMessage::Rejected(e.to_string()))],
+                };
+                hub.deliver(outbox);
+            }
+            Ok(message) if message.is_close() => break,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => break,
+            Err(e) => {
+                eprintln!("{peer}: {e}");
+                break;
+            }
+        }
+    }
+    let mut hub = lock(&hub);
+    hub.outboxes.remove(&id);
+    let outbox = hub.relay.disconnect(id);
+    hub.deliver(outbox);
+    println!("{peer}: disconnected");
+}
+
+fn lock(hub: &Mutex<Hub>) -> MutexGuard<'_, Hub> {
+    hub.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:9001";
+/// How long a connection waits for a message before checking whether the
+/// relay has any for it.
+const POLL: Duration = Duration::from_millis(10);
+const USAGE: &str = "\
+usage: plistr-relay serve [--listen <address:port>]
+       plistr-relay simulate [--url <ws://...>] [--clients <n>] [--latency <ms>]
+                             [--jitter <ms>] [--seconds <n>] [--tolerance <ms>] [--seed <n>]
+";
@@ -0,0 +1,352 @@
+//! Made-up members of one room, each behind a slow, uneven link and with a
+//! clock set to its own time. Whoever's join reaches the relay first is
+//! host and runs a short script, spread over the run however long it is:
+//! queue some tracks, play, start the timer, seek, pause, skip. The last
+//! member joins halfway through. Once a second
+//! everyone who has both the room and the relay's clock is asked where the
+//! music is right then, and the answers should agree to within the
+//! tolerance.
+
+use std::collections::VecDeque;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use plistr_core::{ClientMessage, Command, RoomClient, ServerMessage, TimerCommand, Track, TrackId, Transport};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::Options;
+
+
+pub const OPTIONS: &[&str] =
+    &["--url", "--clients", "--latency", "--jitter", "--seconds", "--tolerance", "--seed"];
+
+/// How a run went.
+#[derive(Debug)]
+pub struct Outcome {
+    /// The furthest apart members were at any report, in milliseconds.
+    pub worst: u64,
+    /// How long after the last member joined it had both the room and the
+    /// relay's clock, or `None` if it never did.
+    pub catch_up: Option<Duration>,
+    /// Where the host had the music at the end.
+    pub end: Option<Transport>,
+}
+
+pub struct Settings {
+    url: String,
+    clients: u64,
+    /// Each way, in milliseconds.
+    latency: u64,
+    /// Added to each message's latency, up to this many milliseconds.
+    jitter: u64,
+    seconds: u64,
+    /// The most members may disagree about the position, in milliseconds.
+    tolerance: u64,
+    seed: u64,
+}
+
+impl Settings {
+    pub fn from_options(options: &Options) -> Result<Self, String> {
+        let settings = Self {
+            url: options.get("--url").unwrap_or(DEFAULT_URL).to_owned(),
+            clients: options.number("--clients", 4)?,
+            latency: options.number("--latency", 80)?,
+            jitter: options.number("--jitter", 30)?,
+            seconds: options.number("--seconds", 20)?,
+            tolerance: options.number("--tolerance", 100)?,
+            seed: options.number("--seed", 1)?,
+        };
+        if settings.clients < 2 {
+            return Err("--clients should be at least 2".to_owned());
+        }
+        Ok(settings)
+    }
+}
+
+/// One member and its link to the relay.
+struct Member {
+    name: String,
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    room: RoomClient,
+    /// How far this member's clock is ahead of the simulation's, in
+    /// milliseconds; negative is behind.
+    skew: i64,
+    /// Messages on their way, and when they arrive.
+    outgoing: VecDeque<(Instant, String)>,
+    incoming: VecDeque<(Instant, String)>,
+    next_ping: Instant,
+    rejected: Vec<String>,
+}
+
+/// A small, seedable source of numbers, so a run can be repeated.
+struct Random(u64);
+
+impl Random {
+    /// Up to and including `max`.
+    fn below(&mut self, max: u64) -> u64 {
+        // xorshift64*
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) % (max + 1)
+    }
+}
+
+pub fn run(settings: &Settings) -> Result<Outcome, String> {
+    let started = Instant::now();
+    let mut random = Random(settings.seed.max(1));
+    let delay = |random: &mut Random| Duration::from_millis(settings.latency + random.below(settings.jitter));
+    let late = settings.clients - 1;
+    let mut members: Vec<Member> = Vec::new();
+    let mut worst = 0;
+    let mut late_joined = None;
+    let mut catch_up = None;
+    let mut next_report = started + Duration::from_secs(1);
+    let length = Duration::from_secs(settings.seconds);
+    let mut script = script(length);
+    println!(
+        "{} members, {} ms each way plus up to {} ms, for {} s",
+        settings.clients, settings.latency, settings.jitter, settings.seconds,
+    );
+    while started.elapsed() < length {
+        let now = Instant::now();
+        let joined = if now - started < length / 2 { late } else { settings.clients };
+        while (members.len() as u64) < joined {
+            let index = members.len();
+            let skew = random.below(10_000) as i64 - 5_000;
+            let name = format!("member {}", index + 1);
+            let mut member = connect(&settings.url, name.clone(), skew)?;
+            member.send(ClientMessage::Join { room: ROOM.to_owned(), name }, now, delay(&mut random));
+            println!("{:>5.1}s  {} joins, clock {:+} ms", seconds(started, now), member.name, skew);
+            if index as u64 == late {
+                late_joined = Some(now);
+            }
+            members.push(member);
+        }
+        while let Some((at, command)) = script.front() {
+            if now - started < *at {
+                break;
+            }
+            // Held back until someone has heard they're host.
+            let Some(host) = members.iter_mut().find(|member| member.room.is_host()) else {
+                break;
+            };
+            println!("{:>5.1}s  {} sends {}", seconds(started, now), host.name, describe(command));
+            host.send(ClientMessage::Command(command.clone()), now, delay(&mut random));
+            script.pop_front();
+        }
+        for member in &mut members {
+            member.exchange(started, now, || delay(&mut random))?;
+        }
+        if let (Some(joined), None, Some(member)) = (late_joined, catch_up, members.get(late as usize)) {
+            if member.caught_up() {
+                catch_up = Some(now - joined);
+            }
+        }
+        if now >= next_report {
+            next_report += Duration::from_secs(1);
+            let spread = report(&members, started, now);
+            worst = worst.max(spread.unwrap_or(0));
+        }
+        std::thread::sleep(TICK);
+    }
+    let end = Instant::now();
+    let host = members.iter().find(|member| member.room.is_host());
+    let end = host.and_then(|host| host.room.playing_at(host.local(started, end)));
+    for member in &mut members {
+        let _ = member.socket.close(None);
+        let _ = member.socket.flush();
+        for reason in &member.rejected {
+            println!("{} was turned down: {reason}", member.name);
+        }
+    }
+    if let Some((_, command)) = script.front() {
+        return Err(format!("the run ended before the host could send {}", describe(command)));
+    }
+    if members.iter().any(|member| !member.rejected.is_empty()) {
+        return Err("the relay turned down some of the script".to_owned());
+    }
+    if !members.iter().all(Member::caught_up) {
+        return Err("some members never heard from the room".to_owned());
+    }
+    if worst > settings.tolerance {
+        return Err(format!("members drifted {worst} ms apart, more than the {} ms tolerance", settings.tolerance));
+    }
+    Ok(Outcome { worst, catch_up, end })
+}
+
+fn connect(url: &str, name: String, skew: i64) -> Result<Member, String> {
+    let (socket, _) = tungstenite::connect(url).map_err(|e| format!("{url}: {e}"))?;
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream.set_nonblocking(true).map_err(|e| format!("{url}: {e}"))?;
+    }
+    Ok(Member {
+        name,
+        socket,
+        room: RoomClient::new(),
+        skew,
+        outgoing: VecDeque::new(),
+        incoming: VecDeque::new(),
+        next_ping: Instant::now(),
+        rejected: Vec::new(),
+    })
+}
+
+impl Member {
+    /// Whether it has the room and knows the relay's clock to read it by;
+    /// until then its idea of the position can be seconds out.
+    fn caught_up(&self) -> bool {
+        self.room.state().is_some() && self.room.clock().offset().is_some()
+    }
+
+    /// Milliseconds on this member's own clock.
+    fn local(&self, started: Instant, now: Instant) -> u64 {
+        ((now - started).as_millis() as i64 + self.skew + CLOCK_START) as u64
+    }
+
+    fn send(&mut self, message: ClientMessage, now: Instant, delay: Duration) {
+        self.outgoing.push_back((now + delay, message.to_json()));
+    }
+
+    /// Pings when due, and moves messages along whichever way they're
+    /// going once their delay is up.
+    fn exchange(&mut self, started: Instant, now: Instant, mut delay: impl FnMut() -> Duration) -> Result<(), String> {
+        if now >= self.next_ping {
+            self.next_ping = now + PING_EVERY;
+            let ping = RoomClient::ping(self.local(started, now));
+            self.send(ping, now, delay());
+        }
+        while self.outgoing.front().is_some_and(|(at, _)| *at <= now) {
+            let (_, text) = self.outgoing.pop_front().expect("just checked");
+            match self.socket.send(Message::text(text)) {
+                Ok(()) => {}
+                Err(tungstenite::Error::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(format!("{}: {e}", self.name)),
+            }
+        }
+        loop {
+            match self.socket.read() {
+                Ok(message) if message.is_text() => {
+                    let text = message.into_text().map_err(|e| e.to_string())?;
+                    self.incoming.push_back((now + delay(), text));
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(format!("{}: {e}", self.name)),
+            }
+        }
+        while self.incoming.front().is_some_and(|(at, _)| *at <= now) {
+            let (_, text) = self.incoming.pop_front().expect("just checked");
+            let message = ServerMessage::from_json(&text).map_err(|e| format!("{}: {e}", self.name))?;
+            if let Some(reason) = self.room.handle(message, self.local(started, now)) {
+                self.rejected.push(reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints where each member thinks the music is, and returns how far
+/// apart the answers are.
+fn report(members: &[Member], started: Instant, now: Instant) -> Option<u64> {
+    let heard: Vec<(&Member, Transport)> = members.iter()
+        .filter(|member| member.caught_up())
+        .filter_map(|member| Some((member, member.room.playing_at(member.local(started, now))?)))
+        .collect();
+    let (_, first) = heard.first()?;
+    let queue = &members.iter().find_map(|member| member.room.state())?.queue;
+    let into_queue = |transport: &Transport| -> u64 {
+        let before: Duration = queue.iter().take(transport.current.unwrap_or(0)).filter_map(|track| track.duration).sum();
+        (before + transport.position).as_millis() as u64
+    };
+    let positions: Vec<u64> = heard.iter().map(|(_, transport)| into_queue(transport)).collect();
+    let spread = positions.iter().max()? - positions.iter().min()?;
+    let round_trips: Vec<String> = heard.iter()
+        .map(|(member, _)| member.room.clock().round_trip().map_or_else(|| "?".to_owned(), |ms| ms.to_string()))
+        .collect();
+    let timer = members[0].room.timer_at(members[0].local(started, now));
+    println!(
+        "{:>5.1}s  track {} at {:.1}s{}, apart by {spread} ms, round trips {} ms, timer {}",
+        seconds(started, now),
+        first.current.map_or_else(|| "-".to_owned(), |index| (index + 1).to_string()),
+        first.position.as_secs_f32(),
+        if first.playing { "" } else { " (paused)" },
+        round_trips.join("/"),
+        timer.map_or_else(|| "-".to_owned(), |timer| format!("{:?} {}s left", timer.phase(), timer.remaining().as_secs())),
+    );
+    Some(spread)
+}
+
+/// What the host does, and when, over a run of `length`.
+fn script(length: Duration) -> VecDeque<(Duration, Command)> {
+    let tracks = (1..=3)
+        .map(|n| {
+            let mut track = Track::new(TrackId(n), format!("Track {n}"), format!("track-{n}.ogg"));
+            track.duration = Some(Duration::from_secs(30));
+            track
+        })
+        .collect();
+    VecDeque::from([
+        (0.05, Command::SetQueue(tracks)),
+        (0.075, Command::Play),
+        (0.1, Command::Timer(TimerCommand::Start)),
+        (0.2, Command::Seek(Duration::from_secs(25))),
+        (0.35, Command::Pause),
+        (0.4, Command::Play),
+        (0.6, Command::Next),
+    ].map(|(through, command)| (length.mul_f32(through), command)))
+}
+
+fn describe(command: &Command) -> String {
+    match command {
+        Command::SetQueue(tracks) => format!("SetQueue({} tracks)", tracks.len()),
+        command => format!("{command:?}"),
+    }
+}
+
+fn seconds(started: Instant, now: Instant) -> f32 {
+    (now - started).as_secs_f32()
+}
+
+
+const DEFAULT_URL: &str = "ws://127.0.0.1:9001";
+const ROOM: &str = "simulation";
+const TICK: Duration = Duration::from_millis(2);
+const PING_EVERY: Duration = Duration::from_millis(500);
+/// Keeps skewed clocks from going below zero.
+const CLOCK_START: i64 = 1_000_000;
+
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn members_agree_through_the_whole_script_and_a_late_joiner_catches_up() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || crate::serve(listener));
+
+        let settings = Settings {
+            url: format!("ws://{address}"),
+            clients: 4,
+            latency: 40,
+            jitter: 20,
+            seconds: 6,
+            tolerance: 100,
+            seed: 7,
+        };
+        // Every command in the script has been sent and none turned down,
+        // or this fails.
+        let outcome = run(&settings).unwrap();
+        assert!(outcome.worst <= settings.tolerance, "{outcome:?}");
+        assert!(outcome.catch_up.is_some(), "{outcome:?}");
+        // Seeking 25 s into the first track, pausing, playing on and
+        // skipping leaves the second track playing.
+        let end = outcome.end.unwrap();
+        assert_eq!((end.current, end.playing), (Some(1), true), "{outcome:?}");
+    }
+}
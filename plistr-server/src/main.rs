@@ -0,0 +1,176 @@
+//! `plistr-server`, where accounts, follows, playlists, sessions and the
+//! shared feed live:
+//!
+//! ```text
+//! plistr-server serve [--listen 127.0.0.1:8080] [--store sqlite|memory]
+//!                     [--database plistr-server.sqlite]
+//! plistr-server openapi
+//! ```
+//!
+//! `serve` answers the REST API over HTTP, with JSON bodies; port 0 picks
+//! a free one and prints it. `openapi` prints the API's OpenAPI
+//! description, which the server also gives at `/openapi.json`.
+
+use std::collections::HashMap;
+use std::io::Read as _;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+use service::Service;
+use store::{MemoryStore, SqliteStore, Store};
+
+mod routes;
+mod service;
+mod store;
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["serve", options @ ..] => Options::parse(options, &["--listen", "--store", "--database"]).and_then(|options| {
+            serve(options.get("--listen").unwrap_or(DEFAULT_ADDRESS), open_store(&options)?)
+        }),
+        ["openapi"] => {
+            println!("{}", routes::OPENAPI);
+            Ok(())
+        }
+        ["help" | "--help" | "-h"] => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprint!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `--name value` pairs, each name at most once.
+struct Options<'a>(HashMap<&'a str, &'a str>);
+
+impl<'a> Options<'a> {
+    fn parse(args: &[&'a str], known: &[&str]) -> Result<Self, String> {
+        let mut options = HashMap::new();
+        for pair in args.chunks(2) {
+            match pair {
+                [name, value] if known.contains(name) => {
+                    if options.insert(*name, *value).is_some() {
+                        return Err(format!("{name} given twice"));
+                    }
+                }
+                [name] if known.contains(name) => return Err(format!("{name} needs a value")),
+                [name, ..] => return Err(format!("unknown option {name}\n{USAGE}")),
+                [] => {}
+            }
+        }
+        Ok(Self(options))
+    }
+
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.0.get(name).copied()
+    }
+}
+
+fn open_store(options: &Options) -> Result<Box<dyn Store>, String> {
+    match (options.get("--store").unwrap_or("sqlite"), options.get("--database")) {
+        ("sqlite", database) => {
+            let path = database.unwrap_or(DEFAULT_DATABASE);
+            let store = SqliteStore::open(path).map_err(|e| format!("{path}: {e}"))?;
+            Ok(Box::new(store))
+        }
+        ("memory", None) => Ok(Box::new(MemoryStore::new())),
+        ("memory", Some(_)) => Err("--database is only for --store sqlite".to_owned()),
+        (other, _) => Err(format!("--store should be sqlite or memory, not \"{other}\"")),
+    }
+}
+
+fn serve(address: &str, store: Box<dyn Store>) -> Result<(), String> {
+    let server = Arc::new(tiny_http::Server::http(address).map_err(|e| format!("{address}: {e}"))?);
+    println!("server listening on http://{}", server.server_addr());
+    let service = Arc::new(Mutex::new(Service::new(store, now)));
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let server = server.clone();
+            let service = service.clone();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    respond(&service, request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+/// Reads the request, runs it past the routes and answers. Browsers asking
+/// whether they may send from another origin are told they may.
+fn respond(service: &Mutex<Service>, mut request: tiny_http::Request) {
+    let method = request.method().as_str().to_owned();
+    let url = request.url().to_owned();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let response = if method == "OPTIONS" {
+        routes::Response { status: 204, body: String::new() }
+    } else {
+        let token = request.headers().iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+            .map(str::to_owned);
+        let mut body = Vec::new();
+        let read = request.as_reader().take(MAX_BODY + 1).read_to_end(&mut body);
+        match read {
+            Err(e) => routes::Response { status: 400, body: error_body(&e.to_string()) },
+            Ok(_) if body.len() as u64 > MAX_BODY => routes::Response { status: 413, body: error_body("the body is too big") },
+            Ok(_) => {
+                let request = routes::Request { method: &method, path, query, token: token.as_deref(), body: &body };
+                routes::handle(&mut service.lock().unwrap_or_else(|e| e.into_inner()), &request)
+            }
+        }
+    };
+    println!("{method} {path} {}", response.status);
+    let mut answer = tiny_http::Response::from_string(response.body).with_status_code(response.status);
+    for (field, value) in [
+        ("Content-Type", "application/json"),
+        ("Access-Control-Allow-Origin", "*"),
+        ("Access-Control-Allow-Methods", "GET, POST, PUT, DELETE"),
+        ("Access-Control-Allow-Headers", "Authorization, Content-Type"),
+    ] {
+        answer.add_header(tiny_http::Header::from_bytes(field, value).expect("the headers are valid"));
+    }
+    if let Err(e) = request.respond(answer) {
+        eprintln!("{method} {path}: {e}");
+    }
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::to_string(&plistr_core::ErrorBody { error: message.to_owned() }).expect("API types always serialize")
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_DATABASE: &str = "plistr-server.sqlite";
+/// Threads answering requests. The store is behind one lock, so more
+/// mostly helps with slow clients.
+const WORKERS: usize = 4;
+/// Largest request body, in bytes.
+const MAX_BODY: u64 = 4 * 1024 * 1024;
+const USAGE: &str = "\
+usage: plistr-server serve [--listen <address:port>] [--store sqlite|memory] [--database <path>]
+       plistr-server openapi
+";
@@ -0,0 +1,157 @@
+//! The REST paths, each turned into a `Service` call. Bodies are JSON both
+//! ways, and `openapi.json` says what each path takes and gives.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use plistr_core::{decode_query, ErrorBody, NewComment, PlaylistId, PostId, SessionId, UserId};
+
+use crate::service::{ApiError, Service};
+
+
+/// An HTTP request, as far as the API cares.
+pub struct Request<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    /// Without the `?`.
+    pub query: &'a str,
+    /// From `Authorization: Bearer`.
+    pub token: Option<&'a str>,
+    pub body: &'a [u8],
+}
+
+pub struct Response {
+    pub status: u16,
+    /// JSON, or empty for 204.
+    pub body: String,
+}
+
+pub fn handle(service: &mut Service, request: &Request) -> Response {
+    match route(service, request) {
+        Ok(response) => response,
+        Err(e) => Response { status: e.status(), body: to_json(&ErrorBody { error: e.to_string() }) },
+    }
+}
+
+fn route(service: &mut Service, request: &Request) -> Result<Response, ApiError> {
+    let segments: Vec<&str> = request.path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let me = |service: &Service| service.authenticate(request.token);
+    match (request.method, segments.as_slice()) {
+        ("GET", ["openapi.json"]) => Ok(Response { status: 200, body: OPENAPI.to_owned() }),
+
+        ("POST", ["users"]) => created(service.sign_up(body(request)?)),
+        ("GET", ["users"]) => ok(service.people(&query(request, "query").unwrap_or_default())),
+        ("GET", ["users", user]) => ok(service.profile(UserId(id(user)?))),
+        ("GET", ["users", user, "followers"]) => ok(service.followers(UserId(id(user)?))),
+        ("GET", ["users", user, "playlists"]) => ok(service.playlists_of(UserId(id(user)?))),
+        ("GET", ["users", user, "sessions"]) => ok(service.sessions_of(UserId(id(user)?))),
+
+        ("GET", ["me"]) => {
+            let me = me(service)?;
+            ok(service.profile(me))
+        }
+        ("PUT", ["me"]) => {
+            let me = me(service)?;
+            ok(service.update_profile(me, body(request)?))
+        }
+        ("PUT", ["me", "following", user]) => {
+            let me = me(service)?;
+            ok(service.follow(me, UserId(id(user)?)))
+        }
+        ("DELETE", ["me", "following", user]) => {
+            let me = me(service)?;
+            ok(service.unfollow(me, UserId(id(user)?)))
+        }
+        ("GET", ["me", "feed"]) => {
+            let me = me(service)?;
+            let limit = match query(request, "limit") {
+                Some(limit) => limit.parse().map_err(|_| ApiError::Rejected("limit should be a whole number".to_owned()))?,
+                None => DEFAULT_FEED,
+            };
+            ok(service.feed(me, limit))
+        }
+
+        ("POST", ["playlists"]) => {
+            let me = me(service)?;
+            created(service.create_playlist(me, body(request)?))
+        }
+        ("GET", ["playlists", playlist]) => ok(service.playlist(PlaylistId(id(playlist)?))),
+        ("PUT", ["playlists", playlist]) => {
+            let me = me(service)?;
+            ok(service.update_playlist(me, PlaylistId(id(playlist)?), body(request)?))
+        }
+        ("DELETE", ["playlists", playlist]) => {
+            let me = me(service)?;
+            deleted(service.delete_playlist(me, PlaylistId(id(playlist)?)))
+        }
+
+        ("POST", ["sessions"]) => {
+            let me = me(service)?;
+            created(service.create_session(me, body(request)?))
+        }
+        ("GET", ["sessions", session]) => ok(service.session(SessionId(id(session)?))),
+        ("DELETE", ["sessions", session]) => {
+            let me = me(service)?;
+            deleted(service.delete_session(me, SessionId(id(session)?)))
+        }
+
+        ("POST", ["posts"]) => {
+            let me = me(service)?;
+            created(service.share(me, body(request)?))
+        }
+        ("GET", ["posts", post]) => ok(service.post(PostId(id(post)?))),
+        ("PUT", ["posts", post, "like"]) => {
+            let me = me(service)?;
+            ok(service.like(me, PostId(id(post)?)))
+        }
+        ("DELETE", ["posts", post, "like"]) => {
+            let me = me(service)?;
+            ok(service.unlike(me, PostId(id(post)?)))
+        }
+        ("POST", ["posts", post, "comments"]) => {
+            let me = me(service)?;
+            let comment: NewComment = body(request)?;
+            ok(service.comment(me, PostId(id(post)?), &comment.text))
+        }
+
+        _ => Err(ApiError::NotFound),
+    }
+}
+
+fn ok<T: Serialize>(result: Result<T, ApiError>) -> Result<Response, ApiError> {
+    Ok(Response { status: 200, body: to_json(&result?) })
+}
+
+fn created<T: Serialize>(result: Result<T, ApiError>) -> Result<Response, ApiError> {
+    Ok(Response { status: 201, body: to_json(&result?) })
+}
+
+fn deleted(result: Result<(), ApiError>) -> Result<Response, ApiError> {
+    result?;
+    Ok(Response { status: 204, body: String::new() })
+}
+
+fn body<T: DeserializeOwned>(request: &Request) -> Result<T, ApiError> {
+    serde_json::from_slice(request.body).map_err(|e| ApiError::Rejected(format!("unreadable body: {e}")))
+}
+
+/// Ids that aren't numbers can't name anything.
+fn id(segment: &str) -> Result<u64, ApiError> {
+    segment.parse().map_err(|_| ApiError::NotFound)
+}
+
+fn query(request: &Request, name: &str) -> Option<String> {
+    request.query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| decode_query(value))
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("API types always serialize")
+}
+
+
+pub const OPENAPI: &str = include_str!("../openapi.json");
+/// Posts in a feed when the request doesn't say.
+const DEFAULT_FEED: usize = 50;
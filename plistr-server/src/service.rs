@@ -0,0 +1,296 @@
+//! What the API does, whatever carries it: who may see and change what,
+//! and the same rules `MockServer` plays by, over any `Store`.
+
+use std::fmt;
+
+use plistr_core::{
+    valid_handle, Account, Comment, NewPost, Playlist, PlaylistId, Post, PostId, Profile, ProfileUpdate,
+    RepositoryError, Session, SessionId, Shared, SignUp, UserId, MAX_TEXT,
+};
+
+use crate::store::Store;
+
+
+pub struct Service {
+    store: Box<dyn Store>,
+    /// Seconds since the Unix epoch, for stamping posts and comments.
+    now: fn() -> u64,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound,
+    /// No token, or one nobody signed up with.
+    Unauthorized,
+    /// Someone else's.
+    Forbidden,
+    /// The request doesn't make sense, and this says why.
+    Rejected(String),
+    Storage(RepositoryError),
+}
+
+impl ApiError {
+    pub fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound => 404,
+            ApiError::Unauthorized => 401,
+            ApiError::Forbidden => 403,
+            ApiError::Rejected(_) => 400,
+            ApiError::Storage(_) => 500,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::NotFound => write!(f, "not found"),
+            ApiError::Unauthorized => write!(f, "sign in first"),
+            ApiError::Forbidden => write!(f, "that belongs to someone else"),
+            ApiError::Rejected(reason) => write!(f, "{reason}"),
+            ApiError::Storage(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<RepositoryError> for ApiError {
+    fn from(e: RepositoryError) -> Self {
+        ApiError::Storage(e)
+    }
+}
+
+impl Service {
+    pub fn new(store: Box<dyn Store>, now: fn() -> u64) -> Self {
+        Self { store, now }
+    }
+
+    /// The user a bearer token belongs to.
+    pub fn authenticate(&self, token: Option<&str>) -> Result<UserId, ApiError> {
+        let token = token.ok_or(ApiError::Unauthorized)?;
+        self.store.user_for_token(token)?.ok_or(ApiError::Unauthorized)
+    }
+
+    /// Handles are unique, ignoring case.
+    pub fn sign_up(&mut self, request: SignUp) -> Result<Account, ApiError> {
+        let handle = request.handle.trim();
+        if !valid_handle(handle) {
+            return Err(ApiError::Rejected(format!("\"{handle}\" isn't a valid handle")));
+        }
+        if self.store.profiles()?.iter().any(|profile| profile.user.handle.eq_ignore_ascii_case(handle)) {
+            return Err(ApiError::Rejected(format!("@{handle} is taken")));
+        }
+        let display_name = match request.display_name.trim() {
+            "" => handle,
+            name => name,
+        };
+        check_length(display_name)?;
+        let token = new_token()?;
+        let user = self.store.add_user(handle, display_name, &token)?;
+        Ok(Account { profile: self.profile(user)?, token })
+    }
+
+    pub fn profile(&self, user: UserId) -> Result<Profile, ApiError> {
+        self.store.profile(user)?.ok_or(ApiError::NotFound)
+    }
+
+    /// Everyone whose handle or name contains `query`, ignoring case.
+    pub fn people(&self, query: &str) -> Result<Vec<Profile>, ApiError> {
+        let query = query.to_lowercase();
+        let mut people = self.store.profiles()?;
+        people.retain(|profile| {
+            profile.user.handle.to_lowercase().contains(&query) || profile.user.display_name.to_lowercase().contains(&query)
+        });
+        Ok(people)
+    }
+
+    /// Everyone who follows `user`.
+    pub fn followers(&self, user: UserId) -> Result<Vec<Profile>, ApiError> {
+        self.profile(user)?;
+        let mut people = self.store.profiles()?;
+        people.retain(|profile| profile.user.following.contains(&user));
+        Ok(people)
+    }
+
+    pub fn update_profile(&mut self, me: UserId, update: ProfileUpdate) -> Result<Profile, ApiError> {
+        let display_name = update.display_name.trim();
+        if display_name.is_empty() {
+            return Err(ApiError::Rejected("a name is needed".to_owned()));
+        }
+        check_length(display_name)?;
+        check_length(&update.bio)?;
+        self.store.update_profile(me, display_name, &update.bio)?;
+        self.profile(me)
+    }
+
+    pub fn follow(&mut self, me: UserId, user: UserId) -> Result<Profile, ApiError> {
+        if user == me {
+            return Err(ApiError::Rejected("you can't follow yourself".to_owned()));
+        }
+        self.profile(user)?;
+        self.store.follow(me, user)?;
+        self.profile(me)
+    }
+
+    pub fn unfollow(&mut self, me: UserId, user: UserId) -> Result<Profile, ApiError> {
+        self.store.unfollow(me, user)?;
+        self.profile(me)
+    }
+
+    /// Saved as the user's, under a new id.
+    pub fn create_playlist(&mut self, me: UserId, mut playlist: Playlist) -> Result<Playlist, ApiError> {
+        check_playlist(&playlist)?;
+        playlist.owner = me;
+        Ok(self.store.add_playlist(playlist)?)
+    }
+
+    pub fn update_playlist(&mut self, me: UserId, id: PlaylistId, mut playlist: Playlist) -> Result<Playlist, ApiError> {
+        self.own_playlist(me, id)?;
+        check_playlist(&playlist)?;
+        playlist.id = id;
+        playlist.owner = me;
+        self.store.save_playlist(&playlist)?;
+        Ok(playlist)
+    }
+
+    pub fn playlist(&self, id: PlaylistId) -> Result<Playlist, ApiError> {
+        self.store.playlist(id)?.ok_or(ApiError::NotFound)
+    }
+
+    pub fn playlists_of(&self, user: UserId) -> Result<Vec<Playlist>, ApiError> {
+        self.profile(user)?;
+        Ok(self.store.playlists_of(user)?)
+    }
+
+    pub fn delete_playlist(&mut self, me: UserId, id: PlaylistId) -> Result<(), ApiError> {
+        self.own_playlist(me, id)?;
+        Ok(self.store.delete_playlist(id)?)
+    }
+
+    fn own_playlist(&self, me: UserId, id: PlaylistId) -> Result<(), ApiError> {
+        match self.playlist(id)? {
+            playlist if playlist.owner == me => Ok(()),
+            _ => Err(ApiError::Forbidden),
+        }
+    }
+
+    /// Saved as the user's, under a new id.
+    pub fn create_session(&mut self, me: UserId, session: Session) -> Result<Session, ApiError> {
+        if session.planned.is_zero() {
+            return Err(ApiError::Rejected("sessions need a planned length".to_owned()));
+        }
+        Ok(self.store.add_session(Session { user: me, ..session })?)
+    }
+
+    pub fn session(&self, id: SessionId) -> Result<Session, ApiError> {
+        self.store.session(id)?.ok_or(ApiError::NotFound)
+    }
+
+    pub fn sessions_of(&self, user: UserId) -> Result<Vec<Session>, ApiError> {
+        self.profile(user)?;
+        Ok(self.store.sessions_of(user)?)
+    }
+
+    pub fn delete_session(&mut self, me: UserId, id: SessionId) -> Result<(), ApiError> {
+        if self.session(id)?.user != me {
+            return Err(ApiError::Forbidden);
+        }
+        Ok(self.store.delete_session(id)?)
+    }
+
+    /// Up to `limit` posts by the user and the people they follow, latest
+    /// first.
+    pub fn feed(&self, me: UserId, limit: usize) -> Result<Vec<Post>, ApiError> {
+        let mut authors = self.profile(me)?.user.following;
+        authors.push(me);
+        Ok(self.store.posts_by(&authors, limit.min(MAX_FEED))?)
+    }
+
+    pub fn share(&mut self, me: UserId, request: NewPost) -> Result<Post, ApiError> {
+        check_length(&request.caption)?;
+        let shared = match request.shared {
+            Shared::Session(session) if !session.is_finished() => {
+                return Err(ApiError::Rejected("only finished sessions can be shared".to_owned()));
+            }
+            Shared::Session(session) => Shared::Session(Session { user: me, ..session }),
+            Shared::Playlist(playlist) => {
+                check_playlist(&playlist)?;
+                Shared::Playlist(playlist)
+            }
+        };
+        let post = Post {
+            id: PostId(0),
+            author: me,
+            posted_at: (self.now)(),
+            shared,
+            caption: request.caption.trim().to_owned(),
+            liked_by: Vec::new(),
+            comments: Vec::new(),
+        };
+        Ok(self.store.add_post(post)?)
+    }
+
+    pub fn post(&self, id: PostId) -> Result<Post, ApiError> {
+        self.store.post(id)?.ok_or(ApiError::NotFound)
+    }
+
+    /// Liking twice counts once.
+    pub fn like(&mut self, me: UserId, id: PostId) -> Result<Post, ApiError> {
+        let mut post = self.post(id)?;
+        if !post.is_liked_by(me) {
+            post.liked_by.push(me);
+            self.store.save_post(&post)?;
+        }
+        Ok(post)
+    }
+
+    pub fn unlike(&mut self, me: UserId, id: PostId) -> Result<Post, ApiError> {
+        let mut post = self.post(id)?;
+        post.liked_by.retain(|&user| user != me);
+        self.store.save_post(&post)?;
+        Ok(post)
+    }
+
+    pub fn comment(&mut self, me: UserId, id: PostId, text: &str) -> Result<Post, ApiError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ApiError::Rejected("the comment is empty".to_owned()));
+        }
+        check_length(text)?;
+        let mut post = self.post(id)?;
+        post.comments.push(Comment { author: me, posted_at: (self.now)(), text: text.to_owned() });
+        self.store.save_post(&post)?;
+        Ok(post)
+    }
+}
+
+fn check_length(text: &str) -> Result<(), ApiError> {
+    if text.chars().count() > MAX_TEXT {
+        return Err(ApiError::Rejected(format!("longer than {MAX_TEXT} characters")));
+    }
+    Ok(())
+}
+
+fn check_playlist(playlist: &Playlist) -> Result<(), ApiError> {
+    if playlist.name.trim().is_empty() {
+        return Err(ApiError::Rejected("playlists need a name".to_owned()));
+    }
+    if playlist.len() > MAX_TRACKS {
+        return Err(ApiError::Rejected(format!("playlists hold at most {MAX_TRACKS} tracks")));
+    }
+    check_length(&playlist.name)?;
+    check_length(&playlist.description)
+}
+
+/// 32 random bytes, in hex.
+fn new_token() -> Result<String, ApiError> {
+    let mut bytes = [0; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| ApiError::Storage(RepositoryError::Backend(e.to_string())))?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+
+/// Most posts one feed request gets.
+const MAX_FEED: usize = 200;
+const MAX_TRACKS: usize = 10_000;
@@ -0,0 +1,145 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use plistr_core::{Playlist, PlaylistId, Post, PostId, Profile, RepositoryError, Session, SessionId, User, UserId};
+
+use super::Store;
+
+
+/// Everything in maps, gone when the server stops. For trying the API out.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    /// Each user with their bio and token.
+    users: BTreeMap<UserId, (User, String, String)>,
+    playlists: BTreeMap<PlaylistId, Playlist>,
+    sessions: BTreeMap<SessionId, Session>,
+    posts: BTreeMap<PostId, Post>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn user_mut(&mut self, user: UserId) -> Result<&mut User, RepositoryError> {
+        let (user, _, _) = self.users.get_mut(&user)
+            .ok_or_else(|| RepositoryError::Backend(format!("no user {}", user.0)))?;
+        Ok(user)
+    }
+}
+
+impl Store for MemoryStore {
+    fn add_user(&mut self, handle: &str, display_name: &str, token: &str) -> Result<UserId, RepositoryError> {
+        let id = self.users.keys().next_back().map_or(UserId(1), |last| UserId(last.0 + 1));
+        self.users.insert(id, (User::new(id, handle, display_name), String::new(), token.to_owned()));
+        Ok(id)
+    }
+
+    fn user_for_token(&self, token: &str) -> Result<Option<UserId>, RepositoryError> {
+        Ok(self.users.iter().find(|(_, (_, _, stored))| stored == token).map(|(&id, _)| id))
+    }
+
+    fn profile(&self, user: UserId) -> Result<Option<Profile>, RepositoryError> {
+        let Some((stored, bio, _)) = self.users.get(&user) else {
+            return Ok(None);
+        };
+        let followers = self.users.values().filter(|(other, _, _)| other.following.contains(&user)).count();
+        Ok(Some(Profile { user: stored.clone(), bio: bio.clone(), followers }))
+    }
+
+    fn profiles(&self) -> Result<Vec<Profile>, RepositoryError> {
+        let mut profiles = self.users.keys().filter_map(|&id| self.profile(id).transpose()).collect::<Result<Vec<_>, _>>()?;
+        profiles.sort_by(|a, b| a.user.handle.cmp(&b.user.handle));
+        Ok(profiles)
+    }
+
+    fn update_profile(&mut self, user: UserId, display_name: &str, bio: &str) -> Result<(), RepositoryError> {
+        let (stored, stored_bio, _) = self.users.get_mut(&user)
+            .ok_or_else(|| RepositoryError::Backend(format!("no user {}", user.0)))?;
+        stored.display_name = display_name.to_owned();
+        *stored_bio = bio.to_owned();
+        Ok(())
+    }
+
+    fn follow(&mut self, user: UserId, other: UserId) -> Result<(), RepositoryError> {
+        self.user_mut(user)?.follow(other);
+        Ok(())
+    }
+
+    fn unfollow(&mut self, user: UserId, other: UserId) -> Result<(), RepositoryError> {
+        self.user_mut(user)?.unfollow(other);
+        Ok(())
+    }
+
+    fn add_playlist(&mut self, mut playlist: Playlist) -> Result<Playlist, RepositoryError> {
+        playlist.id = self.playlists.keys().next_back().map_or(PlaylistId(1), |last| PlaylistId(last.0 + 1));
+        self.playlists.insert(playlist.id, playlist.clone());
+        Ok(playlist)
+    }
+
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError> {
+        self.playlists.insert(playlist.id, playlist.clone());
+        Ok(())
+    }
+
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError> {
+        Ok(self.playlists.get(&id).cloned())
+    }
+
+    fn playlists_of(&self, owner: UserId) -> Result<Vec<Playlist>, RepositoryError> {
+        Ok(self.playlists.values().filter(|playlist| playlist.owner == owner).cloned().collect())
+    }
+
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError> {
+        self.playlists.remove(&id);
+        Ok(())
+    }
+
+    fn add_session(&mut self, mut session: Session) -> Result<Session, RepositoryError> {
+        session.id = self.sessions.keys().next_back().map_or(SessionId(1), |last| SessionId(last.0 + 1));
+        self.sessions.insert(session.id, session.clone());
+        Ok(session)
+    }
+
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+        Ok(self.sessions.get(&id).cloned())
+    }
+
+    fn sessions_of(&self, user: UserId) -> Result<Vec<Session>, RepositoryError> {
+        let mut sessions: Vec<Session> = self.sessions.values().filter(|session| session.user == user).cloned().collect();
+        sessions.sort_by_key(|session| session.started_at);
+        Ok(sessions)
+    }
+
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError> {
+        self.sessions.remove(&id);
+        Ok(())
+    }
+
+    fn add_post(&mut self, mut post: Post) -> Result<Post, RepositoryError> {
+        post.id = self.posts.keys().next_back().map_or(PostId(1), |last| PostId(last.0 + 1));
+        self.posts.insert(post.id, post.clone());
+        Ok(post)
+    }
+
+    fn save_post(&mut self, post: &Post) -> Result<(), RepositoryError> {
+        self.posts.insert(post.id, post.clone());
+        Ok(())
+    }
+
+    fn post(&self, id: PostId) -> Result<Option<Post>, RepositoryError> {
+        Ok(self.posts.get(&id).cloned())
+    }
+
+    fn posts_by(&self, authors: &[UserId], limit: usize) -> Result<Vec<Post>, RepositoryError> {
+        // Walked newest first, so later ids win ties.
+        let mut posts: Vec<Post> = self.posts.values()
+            .rev()
+            .filter(|post| authors.contains(&post.author))
+            .cloned()
+            .collect();
+        posts.sort_by_key(|post| Reverse(post.posted_at));
+        posts.truncate(limit);
+        Ok(posts)
+    }
+}
@@ -0,0 +1,53 @@
+//! Where the server keeps its users, follows, playlists, sessions and
+//! posts. Stores only keep things; the rules about who may change what are
+//! the `Service`'s, so every backend behaves the same.
+
+use plistr_core::{Playlist, PlaylistId, Post, PostId, Profile, RepositoryError, Session, SessionId, UserId};
+
+mod memory;
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+
+pub trait Store: Send {
+    /// Gives the user the next free id. The handle should have been
+    /// checked as free first.
+    fn add_user(&mut self, handle: &str, display_name: &str, token: &str) -> Result<UserId, RepositoryError>;
+    fn user_for_token(&self, token: &str) -> Result<Option<UserId>, RepositoryError>;
+    /// With `followers` counted.
+    fn profile(&self, user: UserId) -> Result<Option<Profile>, RepositoryError>;
+    /// Everyone, ordered by handle.
+    fn profiles(&self) -> Result<Vec<Profile>, RepositoryError>;
+    fn update_profile(&mut self, user: UserId, display_name: &str, bio: &str) -> Result<(), RepositoryError>;
+    /// Following someone already followed changes nothing.
+    fn follow(&mut self, user: UserId, other: UserId) -> Result<(), RepositoryError>;
+    fn unfollow(&mut self, user: UserId, other: UserId) -> Result<(), RepositoryError>;
+
+    /// Saves the playlist under the next free id, and returns it with that
+    /// id.
+    fn add_playlist(&mut self, playlist: Playlist) -> Result<Playlist, RepositoryError>;
+    /// Replaces the stored playlist with the same id.
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError>;
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError>;
+    /// In id order.
+    fn playlists_of(&self, owner: UserId) -> Result<Vec<Playlist>, RepositoryError>;
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError>;
+
+    /// Saves the session under the next free id, and returns it with that
+    /// id.
+    fn add_session(&mut self, session: Session) -> Result<Session, RepositoryError>;
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError>;
+    /// Earliest first.
+    fn sessions_of(&self, user: UserId) -> Result<Vec<Session>, RepositoryError>;
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError>;
+
+    /// Saves the post under the next free id, and returns it with that id.
+    fn add_post(&mut self, post: Post) -> Result<Post, RepositoryError>;
+    /// Replaces the stored post with the same id.
+    fn save_post(&mut self, post: &Post) -> Result<(), RepositoryError>;
+    fn post(&self, id: PostId) -> Result<Option<Post>, RepositoryError>;
+    /// Up to `limit` posts by any of `authors`, latest first.
+    fn posts_by(&self, authors: &[UserId], limit: usize) -> Result<Vec<Post>, RepositoryError>;
+}
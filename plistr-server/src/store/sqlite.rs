@@ -0,0 +1,263 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use plistr_core::{Playlist, PlaylistId, Post, PostId, Profile, RepositoryError, Session, SessionId, User, UserId};
+
+use super::Store;
+
+
+/// One SQLite file. Users and follows get columns of their own, since
+/// they're searched and counted; playlists, sessions and posts are kept
+/// whole, as JSON, next to the columns they're looked up by.
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RepositoryError> {
+        Self::migrate(Connection::open(path).map_err(backend)?)
+    }
+
+    /// Runs each migration the file hasn't had yet, in its own transaction,
+    /// and records it in `user_version`.
+    fn migrate(mut connection: Connection) -> Result<Self, RepositoryError> {
+        connection.pragma_update(None, "foreign_keys", true).map_err(backend)?;
+        let version: u32 = connection.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(backend)?;
+        let supported = MIGRATIONS.len() as u32;
+        if version > supported {
+            return Err(RepositoryError::TooNew { version, supported });
+        }
+        for (done, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let transaction = connection.transaction().map_err(backend)?;
+            transaction.execute_batch(migration).map_err(backend)?;
+            transaction.pragma_update(None, "user_version", done as u32 + 1).map_err(backend)?;
+            transaction.commit().map_err(backend)?;
+        }
+        Ok(Self { connection })
+    }
+
+    fn read_profile(&self, row: &Row) -> rusqlite::Result<Profile> {
+        let id = UserId(row.get::<_, i64>(0)? as u64);
+        let mut user = User::new(id, row.get::<_, String>(1)?, row.get::<_, String>(2)?);
+        let mut following = self.connection.prepare_cached("SELECT followed FROM follows WHERE follower = ?1 ORDER BY rowid")?;
+        user.following = following.query_map([id.0 as i64], |row| Ok(UserId(row.get::<_, i64>(0)? as u64)))?
+            .collect::<Result<_, _>>()?;
+        Ok(Profile { user, bio: row.get(3)?, followers: row.get::<_, i64>(4)? as usize })
+    }
+
+    fn next_id(&self, table: &str) -> Result<u64, RepositoryError> {
+        let last: Option<i64> = self.connection
+            .query_row(&format!("SELECT max(id) FROM {table}"), [], |row| row.get(0))
+            .map_err(backend)?;
+        Ok(last.map_or(1, |last| last as u64 + 1))
+    }
+
+    /// The JSON in `body` of the row with `id`.
+    fn body<T: DeserializeOwned>(&self, table: &str, id: u64) -> Result<Option<T>, RepositoryError> {
+        let body: Option<String> = self.connection
+            .query_row(&format!("SELECT body FROM {table} WHERE id = ?1"), [id as i64], |row| row.get(0))
+            .optional()
+            .map_err(backend)?;
+        body.map(|body| from_json(&body)).transpose()
+    }
+
+    fn bodies<T: DeserializeOwned>(&self, sql: &str, key: u64) -> Result<Vec<T>, RepositoryError> {
+        let mut statement = self.connection.prepare(sql).map_err(backend)?;
+        let bodies: Vec<String> = statement.query_map([key as i64], |row| row.get(0))
+            .map_err(backend)?
+            .collect::<Result<_, _>>()
+            .map_err(backend)?;
+        bodies.iter().map(|body| from_json(body)).collect()
+    }
+}
+
+impl Store for SqliteStore {
+    fn add_user(&mut self, handle: &str, display_name: &str, token: &str) -> Result<UserId, RepositoryError> {
+        self.connection.execute(
+            "INSERT INTO users (handle, display_name, bio, token) VALUES (?1, ?2, '', ?3)",
+            params![handle, display_name, token],
+        ).map_err(backend)?;
+        Ok(UserId(self.connection.last_insert_rowid() as u64))
+    }
+
+    fn user_for_token(&self, token: &str) -> Result<Option<UserId>, RepositoryError> {
+        let id: Option<i64> = self.connection
+            .query_row("SELECT id FROM users WHERE token = ?1", [token], |row| row.get(0))
+            .optional()
+            .map_err(backend)?;
+        Ok(id.map(|id| UserId(id as u64)))
+    }
+
+    fn profile(&self, user: UserId) -> Result<Option<Profile>, RepositoryError> {
+        let sql = format!("SELECT {PROFILE_COLUMNS} FROM users WHERE id = ?1");
+        self.connection.query_row(&sql, [user.0 as i64], |row| self.read_profile(row)).optional().map_err(backend)
+    }
+
+    fn profiles(&self) -> Result<Vec<Profile>, RepositoryError> {
+        let mut statement = self.connection.prepare(&format!("SELECT {PROFILE_COLUMNS} FROM users ORDER BY handle")).map_err(backend)?;
+        let rows = statement.query_map([], |row| self.read_profile(row)).map_err(backend)?;
+        rows.collect::<Result<_, _>>().map_err(backend)
+    }
+
+    fn update_profile(&mut self, user: UserId, display_name: &str, bio: &str) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "UPDATE users SET display_name = ?2, bio = ?3 WHERE id = ?1",
+            params![user.0 as i64, display_name, bio],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn follow(&mut self, user: UserId, other: UserId) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "INSERT INTO follows (follower, followed) VALUES (?1, ?2) ON CONFLICT DO NOTHING",
+            params![user.0 as i64, other.0 as i64],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn unfollow(&mut self, user: UserId, other: UserId) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "DELETE FROM follows WHERE follower = ?1 AND followed = ?2",
+            params![user.0 as i64, other.0 as i64],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn add_playlist(&mut self, mut playlist: Playlist) -> Result<Playlist, RepositoryError> {
+        playlist.id = PlaylistId(self.next_id("playlists")?);
+        self.save_playlist(&playlist)?;
+        Ok(playlist)
+    }
+
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "INSERT INTO playlists (id, owner, body) VALUES (?1, ?2, ?3) \
+             ON CONFLICT (id) DO UPDATE SET owner = ?2, body = ?3",
+            params![playlist.id.0 as i64, playlist.owner.0 as i64, to_json(playlist)],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError> {
+        self.body("playlists", id.0)
+    }
+
+    fn playlists_of(&self, owner: UserId) -> Result<Vec<Playlist>, RepositoryError> {
+        self.bodies("SELECT body FROM playlists WHERE owner = ?1 ORDER BY id", owner.0)
+    }
+
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError> {
+        self.connection.execute("DELETE FROM playlists WHERE id = ?1", [id.0 as i64]).map_err(backend)?;
+        Ok(())
+    }
+
+    fn add_session(&mut self, mut session: Session) -> Result<Session, RepositoryError> {
+        session.id = SessionId(self.next_id("sessions")?);
+        self.connection.execute(
+            "INSERT INTO sessions (id, user, started_at, body) VALUES (?1, ?2, ?3, ?4)",
+            params![session.id.0 as i64, session.user.0 as i64, session.started_at as i64, to_json(&session)],
+        ).map_err(backend)?;
+        Ok(session)
+    }
+
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+        self.body("sessions", id.0)
+    }
+
+    fn sessions_of(&self, user: UserId) -> Result<Vec<Session>, RepositoryError> {
+        self.bodies("SELECT body FROM sessions WHERE user = ?1 ORDER BY started_at, id", user.0)
+    }
+
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError> {
+        self.connection.execute("DELETE FROM sessions WHERE id = ?1", [id.0 as i64]).map_err(backend)?;
+        Ok(())
+    }
+
+    fn add_post(&mut self, mut post: Post) -> Result<Post, RepositoryError> {
+        post.id = PostId(self.next_id("posts")?);
+        self.save_post(&post)?;
+        Ok(post)
+    }
+
+    fn save_post(&mut self, post: &Post) -> Result<(), RepositoryError> {
+        self.connection.execute(
+            "INSERT INTO posts (id, author, posted_at, body) VALUES (?1, ?2, ?3, ?4) \
+             ON CONFLICT (id) DO UPDATE SET author = ?2, posted_at = ?3, body = ?4",
+            params![post.id.0 as i64, post.author.0 as i64, post.posted_at as i64, to_json(post)],
+        ).map_err(backend)?;
+        Ok(())
+    }
+
+    fn post(&self, id: PostId) -> Result<Option<Post>, RepositoryError> {
+        self.body("posts", id.0)
+    }
+
+    fn posts_by(&self, authors: &[UserId], limit: usize) -> Result<Vec<Post>, RepositoryError> {
+        // One author at a time keeps the SQL fixed; each gives at most
+        // `limit`, and the merge keeps the latest of those.
+        let mut posts = Vec::new();
+        for author in authors {
+            let sql = format!("SELECT body FROM posts WHERE author = ?1 ORDER BY posted_at DESC, id DESC LIMIT {limit}");
+            posts.extend(self.bodies::<Post>(&sql, author.0)?);
+        }
+        posts.sort_by_key(|post| std::cmp::Reverse((post.posted_at, post.id)));
+        posts.truncate(limit);
+        Ok(posts)
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("stored types always serialize")
+}
+
+fn from_json<T: DeserializeOwned>(text: &str) -> Result<T, RepositoryError> {
+    serde_json::from_str(text).map_err(|e| RepositoryError::Corrupt(e.to_string()))
+}
+
+fn backend(e: rusqlite::Error) -> RepositoryError {
+    RepositoryError::Backend(e.to_string())
+}
+
+
+/// Each entry moves the schema on one version. Only ever add to the end:
+/// files already out there have run the ones before.
+const MIGRATIONS: [&str; 1] = ["
+    CREATE TABLE users (
+        id INTEGER PRIMARY KEY,
+        handle TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        display_name TEXT NOT NULL,
+        bio TEXT NOT NULL,
+        token TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE follows (
+        follower INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        followed INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        PRIMARY KEY (follower, followed)
+    );
+    CREATE INDEX follows_by_followed ON follows (followed);
+    CREATE TABLE playlists (
+        id INTEGER PRIMARY KEY,
+        owner INTEGER NOT NULL REFERENCES users (id),
+        body TEXT NOT NULL
+    );
+    CREATE INDEX playlists_by_owner ON playlists (owner);
+    CREATE TABLE sessions (
+        id INTEGER PRIMARY KEY,
+        user INTEGER NOT NULL REFERENCES users (id),
+        started_at INTEGER NOT NULL,
+        body TEXT NOT NULL
+    );
+    CREATE INDEX sessions_by_user ON sessions (user, started_at);
+    CREATE TABLE posts (
+        id INTEGER PRIMARY KEY,
+        author INTEGER NOT NULL REFERENCES users (id),
+        posted_at INTEGER NOT NULL,
+        body TEXT NOT NULL
+    );
+    CREATE INDEX posts_by_author ON posts (author, posted_at);
+"];
+const PROFILE_COLUMNS: &str =
+    "id, handle, display_name, bio, (SELECT count(*) FROM follows WHERE followed = users.id)";
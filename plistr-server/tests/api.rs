@@ -0,0 +1,210 @@
+//! Starts the real `plistr-server` on a free local port and drives it
+//! through the app's `ApiClient`.
+
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use plistr_core::{
+    ApiClient, Credentials, Playlist, PlaylistId, Reply, Session, SessionId, SessionKind, Shared, SocialClient,
+    SocialError, Track, TrackId, UserId,
+};
+
+
+/// A running server, stopped when dropped.
+struct Server {
+    child: Child,
+    url: String,
+}
+
+impl Server {
+    fn start(store: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_plistr-server"))
+            .args(["serve", "--listen", "127.0.0.1:0"])
+            .args(store)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("couldn't start plistr-server");
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let first = lines.next().expect("the server said nothing").unwrap();
+        let url = first.strip_prefix("server listening on ").expect(&first).to_owned();
+        // The request log goes on, and mustn't fill the pipe.
+        std::thread::spawn(move || lines.for_each(drop));
+        Self { child, url }
+    }
+
+    fn sign_up(&self, handle: &str) -> ApiClient {
+        answer(|reply| ApiClient::sign_up(&self.url, handle, "", reply)).expect("sign up")
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Makes a call and waits for its answer.
+fn answer<T: Send + 'static>(call: impl FnOnce(Reply<T>)) -> Result<T, SocialError> {
+    let (sender, receiver) = mpsc::channel();
+    call(Box::new(move |result| {
+        let _ = sender.send(result);
+    }));
+    receiver.recv_timeout(Duration::from_secs(10)).expect("no answer from the server")
+}
+
+fn playlist(owner: UserId, name: &str, tracks: u64) -> Playlist {
+    let mut playlist = Playlist::new(PlaylistId(0), owner, name);
+    for id in 1..=tracks {
+        playlist.add(Track::new(TrackId(id), format!("Track {id}"), format!("https://example.com/{id}.ogg")));
+    }
+    playlist
+}
+
+fn finished_session(user: UserId, minutes: u64) -> Session {
+    let planned = Duration::from_secs(minutes * 60);
+    let mut session = Session::new(SessionId(0), user, SessionKind::Study, 1_700_000_000, planned);
+    session.record(planned);
+    session
+}
+
+#[test]
+fn signing_up_gives_a_token_for_a_unique_handle() {
+    let server = Server::start(&["--store", "memory"]);
+    let ana = server.sign_up("ana");
+    assert_eq!(ana.credentials().server, server.url);
+    let me = answer(|reply| ana.profile(ana.me(), reply)).unwrap();
+    assert_eq!(me.user.handle, "ana");
+    assert_eq!(me.user.display_name, "ana");
+
+    let taken = answer(|reply| ApiClient::sign_up(&server.url, "ANA", "Someone else", reply));
+    assert!(matches!(taken, Err(SocialError::Rejected(_))), "{taken:?}");
+    let invalid = answer(|reply| ApiClient::sign_up(&server.url, "no spaces", "", reply));
+    assert!(matches!(invalid, Err(SocialError::Rejected(_))), "{invalid:?}");
+}
+
+#[test]
+fn changes_need_a_valid_bearer_token() {
+    let server = Server::start(&["--store", "memory"]);
+    let ana = server.sign_up("ana");
+    let forged = ApiClient::new(Credentials { token: "0".repeat(64), ..ana.credentials().clone() });
+
+    let unauthorized = Err(SocialError::Rejected("sign in first".to_owned()));
+    assert_eq!(answer(|reply| forged.follow(UserId(99), reply)).map(drop), unauthorized);
+    assert_eq!(answer(|reply| forged.create_playlist(&playlist(ana.me(), "Mine", 1), reply)).map(drop), unauthorized);
+    assert_eq!(answer(|reply| forged.feed(10, reply)).map(drop), unauthorized);
+    // Reading isn't signed in.
+    assert_eq!(answer(|reply| forged.profile(ana.me(), reply)).unwrap().user.handle, "ana");
+    assert_eq!(answer(|reply| forged.profile(UserId(99), reply)), Err(SocialError::NotFound));
+}
+
+#[test]
+fn playlists_belong_to_whoever_saved_them() {
+    let server = Server::start(&["--store", "memory"]);
+    let ana = server.sign_up("ana");
+    let bo = server.sign_up("bo");
+
+    // The owner and id sent are replaced.
+    let mut sent = playlist(bo.me(), "Reading", 3);
+    sent.id = PlaylistId(42);
+    let saved = answer(|reply| ana.create_playlist(&sent, reply)).unwrap();
+    assert_eq!(saved.owner, ana.me());
+    assert_ne!(saved.id, PlaylistId(42));
+    assert_eq!(saved.tracks(), sent.tracks());
+    let second = answer(|reply| ana.create_playlist(&playlist(ana.me(), "Writing", 0), reply)).unwrap();
+
+    let listed = answer(|reply| bo.playlists(ana.me(), reply)).unwrap();
+    assert_eq!(listed, [saved.clone(), second.clone()]);
+    assert!(answer(|reply| bo.playlists(bo.me(), reply)).unwrap().is_empty());
+
+    let unnamed = answer(|reply| ana.create_playlist(&playlist(ana.me(), " ", 1), reply));
+    assert!(matches!(unnamed, Err(SocialError::Rejected(_))), "{unnamed:?}");
+
+    let theirs = answer(|reply| bo.delete_playlist(saved.id, reply));
+    assert!(matches!(theirs, Err(SocialError::Rejected(_))), "{theirs:?}");
+    answer(|reply| ana.delete_playlist(saved.id, reply)).unwrap();
+    assert_eq!(answer(|reply| ana.delete_playlist(saved.id, reply)), Err(SocialError::NotFound));
+    assert_eq!(answer(|reply| bo.playlists(ana.me(), reply)).unwrap(), [second]);
+}
+
+#[test]
+fn following_counts_and_fills_the_feed() {
+    let server = Server::start(&["--store", "memory"]);
+    let ana = server.sign_up("ana");
+    let bo = server.sign_up("bo");
+
+    let shared = Shared::Playlist(playlist(bo.me(), "Rain", 2));
+    let post = answer(|reply| bo.share(shared, "for the exam".to_owned(), reply)).unwrap();
+    assert!(answer(|reply| ana.feed(10, reply)).unwrap().is_empty());
+
+    let me = answer(|reply| ana.follow(bo.me(), reply)).unwrap();
+    assert_eq!(me.user.following, [bo.me()]);
+    // Following twice is the same as once.
+    answer(|reply| ana.follow(bo.me(), reply)).unwrap();
+    assert_eq!(answer(|reply| ana.profile(bo.me(), reply)).unwrap().followers, 1);
+    let yourself = answer(|reply| ana.follow(ana.me(), reply));
+    assert!(matches!(yourself, Err(SocialError::Rejected(_))), "{yourself:?}");
+    assert_eq!(answer(|reply| ana.follow(UserId(99), reply)), Err(SocialError::NotFound));
+
+    assert_eq!(answer(|reply| ana.feed(10, reply)).unwrap(), std::slice::from_ref(&post));
+    let liked = answer(|reply| ana.like(post.id, reply)).unwrap();
+    assert!(liked.is_liked_by(ana.me()));
+    let commented = answer(|reply| ana.comment(post.id, "good luck".to_owned(), reply)).unwrap();
+    assert_eq!(commented.comments[0].author, ana.me());
+
+    let found = answer(|reply| ana.people("B", reply)).unwrap();
+    assert_eq!(found.iter().map(|profile| profile.user.id).collect::<Vec<_>>(), [bo.me()]);
+
+    let me = answer(|reply| ana.unfollow(bo.me(), reply)).unwrap();
+    assert!(me.user.following.is_empty());
+    assert_eq!(answer(|reply| ana.profile(bo.me(), reply)).unwrap().followers, 0);
+    assert!(answer(|reply| ana.feed(10, reply)).unwrap().is_empty());
+}
+
+#[test]
+fn sessions_are_kept_in_start_order() {
+    let server = Server::start(&["--store", "memory"]);
+    let ana = server.sign_up("ana");
+    let bo = server.sign_up("bo");
+
+    let mut later = finished_session(bo.me(), 25);
+    later.started_at += 3600;
+    let later = answer(|reply| ana.create_session(&later, reply)).unwrap();
+    let earlier = answer(|reply| ana.create_session(&finished_session(ana.me(), 50), reply)).unwrap();
+    assert_eq!(later.user, ana.me());
+    assert_ne!(later.id, earlier.id);
+    assert_eq!(answer(|reply| bo.sessions(ana.me(), reply)).unwrap(), [earlier, later]);
+
+    let unplanned = answer(|reply| ana.create_session(&finished_session(ana.me(), 0), reply));
+    assert!(matches!(unplanned, Err(SocialError::Rejected(_))), "{unplanned:?}");
+    assert_eq!(answer(|reply| ana.sessions(UserId(99), reply)), Err(SocialError::NotFound));
+}
+
+#[test]
+fn sqlite_keeps_everything_across_restarts() {
+    let path = scratch_database();
+    let database = path.to_str().unwrap();
+    let (credentials, saved) = {
+        let server = Server::start(&["--database", database]);
+        let ana = server.sign_up("ana");
+        let saved = answer(|reply| ana.create_playlist(&playlist(ana.me(), "Reading", 2), reply)).unwrap();
+        (ana.credentials().clone(), saved)
+    };
+    let server = Server::start(&["--database", database]);
+    // The token still works on the new port.
+    let ana = ApiClient::new(Credentials { server: server.url.clone(), ..credentials });
+    assert_eq!(answer(|reply| ana.playlists(ana.me(), reply)).unwrap(), [saved]);
+    answer(|reply| ana.create_session(&finished_session(ana.me(), 25), reply)).unwrap();
+    drop(server);
+    let _ = std::fs::remove_file(&path);
+}
+
+/// A database file nothing else uses.
+fn scratch_database() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("plistr-server-test-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
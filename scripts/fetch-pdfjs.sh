@@ -0,0 +1,26 @@
+#!/bin/sh
+# Vendors the pdf.js build that index.html loads into assets/pdfjs, so the
+# web app doesn't depend on a CDN. Run from the repository root and commit
+# what it writes. The tarball is checked against the registry's integrity
+# hash for this exact version.
+set -eu
+
+VERSION=3.11.174
+TARBALL="https://registry.npmjs.org/pdfjs-dist/-/pdfjs-dist-$VERSION.tgz"
+OUT=assets/pdfjs
+
+work=$(mktemp -d)
+trap 'rm -rf "$work"' EXIT
+
+curl -fsSL "$TARBALL" -o "$work/pdfjs.tgz"
+expected=$(curl -fsSL "https://registry.npmjs.org/pdfjs-dist/$VERSION" | sed -n 's/.*"integrity":"sha512-\([^"]*\)".*/\1/p')
+actual=$(openssl dgst -sha512 -binary "$work/pdfjs.tgz" | base64 | tr -d '\n')
+if [ -z "$expected" ] || [ "$expected" != "$actual" ]; then
+    echo "pdfjs-dist $VERSION doesn't match the registry's integrity hash" >&2
+    exit 1
+fi
+
+tar -xzf "$work/pdfjs.tgz" -C "$work"
+mkdir -p "$OUT"
+cp "$work/package/build/pdf.min.js" "$work/package/build/pdf.worker.min.js" "$work/package/LICENSE" "$OUT/"
+echo "pdf.js $VERSION is in $OUT"
@@ -0,0 +1,168 @@
+use eframe::egui;
+
+use crate::nav::{self, PageInfo};
+use crate::{i18n, Page};
+
+
+/// Jumps straight to a page with its key from `nav::PAGES`.
+pub fn page_shortcut(ctx: &egui::Context) -> Option<Page> {
+    if ctx.wants_keyboard_input() {
+        return None;
+    }
+    ctx.input_mut(|i| {
+        nav::visible()
+            .find(|info| i.consume_key(egui::Modifiers::NONE, info.shortcut))
+            .map(|info| info.page)
+    })
+}
+
+/// Tooltip text naming the key `page_shortcut` uses for a visible page, or
+/// `None` for a hidden one.
+pub fn shortcut_hint(info: &PageInfo) -> Option<String> {
+    info.visible
+        .then(|| i18n::tr_with("nav-shortcut", &[("key", info.shortcut.symbol_or_name().into())]))
+}
+
+/// Names a control whose visible text is only an icon, for screen readers and
+/// as a tooltip for everyone else.
+pub fn label(response: egui::Response, name: &str) -> egui::Response {
+    let enabled = response.enabled();
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, enabled, name));
+    response.on_hover_text(name)
+}
+
+/// Call before any widgets. Scrolls a widget into view when the keyboard moves
+/// focus onto it, since egui leaves it wherever it was.
+pub fn begin_frame(ctx: &egui::Context) {
+    let id = egui::Id::new(STATE);
+    let (mut keyboard, last_focused) = ctx.data(|d| d.get_temp::<(bool, Option<egui::Id>)>(id)).unwrap_or_default();
+    ctx.input(|i| {
+        if i.events.iter().any(|e| matches!(e, egui::Event::Key { pressed: true, .. })) {
+            keyboard = true;
+        } else if i.pointer.any_pressed() {
+            keyboard = false;
+        }
+    });
+    let focused = ctx.memory(|m| m.focused());
+    if keyboard && focused != last_focused {
+        if let Some(response) = focused.and_then(|f| ctx.read_response(f)) {
+            response.scroll_to_me(None);
+        }
+    }
+    ctx.data_mut(|d| d.insert_temp(id, (keyboard, focused)));
+}
+
+/// Call once the panels are laid out, with their rects in the order Tab
+/// should visit them. egui visits widgets in the order they were added, and
+/// panels have to be added from the outside in.
+pub fn set_tab_order(ctx: &egui::Context, panels: Vec<egui::Rect>) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new(TAB_ORDER), panels));
+}
+
+/// For `eframe::App::raw_input_hook`. When Tab or Shift+Tab would take focus
+/// out of a panel, sends it to the next or previous focusable widget in
+/// `set_tab_order`'s order instead, using last frame's widgets. Moves within
+/// a panel are left to egui.
+pub fn follow_tab_order(ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+    let is_tab = |event: &egui::Event| matches!(event, egui::Event::Key { key: egui::Key::Tab, pressed: true, .. });
+    let Some(backwards) = raw_input.events.iter().find_map(|event| match event {
+        egui::Event::Key { key: egui::Key::Tab, pressed: true, modifiers, .. } => Some(modifiers.shift),
+        _ => None,
+    }) else {
+        return;
+    };
+    let Some(focused) = ctx.memory(|m| m.focused()) else {
+        return;
+    };
+    let panels = ctx.data(|d| d.get_temp::<Vec<egui::Rect>>(egui::Id::new(TAB_ORDER))).unwrap_or_default();
+    // Popups and windows are on layers of their own, and keep egui's order.
+    let mut order: Vec<(usize, egui::Id)> = ctx.viewport(|v| {
+        v.prev_pass.widgets
+            .get_layer(egui::LayerId::background())
+            .filter(|widget| widget.enabled && widget.sense.focusable)
+            .filter_map(|widget| Some((panels.iter().position(|panel| panel.contains(widget.rect.center()))?, widget.id)))
+            .collect()
+    });
+    order.sort_by_key(|&(panel, _)| panel);
+    let Some(at) = order.iter().position(|&(_, id)| id == focused) else {
+        return;
+    };
+    let to = if backwards { (at + order.len() - 1) % order.len() } else { (at + 1) % order.len() };
+    if order[to].0 != order[at].0 {
+        raw_input.events.retain(|event| !is_tab(event));
+        ctx.memory_mut(|m| m.request_focus(order[to].1));
+    }
+}
+
+/// Call after every widget. Rings whatever has keyboard focus; egui's own
+/// focus styling doesn't show on frameless buttons, links or custom widgets.
+pub fn focus_ring(ctx: &egui::Context) {
+    let id = egui::Id::new(STATE);
+    let (keyboard, _) = ctx.data(|d| d.get_temp::<(bool, Option<egui::Id>)>(id)).unwrap_or_default();
+    let focused = ctx.memory(|m| m.focused()).and_then(|f| ctx.read_response(f));
+    let Some(response) = focused.filter(|_| keyboard) else {
+        return;
+    };
+    let stroke = egui::Stroke::new(2.0, ctx.style().visuals.selection.bg_fill);
+    ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, id))
+        .rect_stroke(response.rect.expand(3.0), 4.0, stroke);
+}
+
+
+const STATE: &str = "a11y";
+const TAB_ORDER: &str = "tab order";
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A header, footer and page with a button each, added the way the app
+    /// adds them. Returns the buttons' ids in that order.
+    fn frame(ctx: &egui::Context, input: egui::RawInput) -> [egui::Id; 3] {
+        let mut ids = [egui::Id::NULL; 3];
+        let _ = ctx.run(input, |ctx| {
+            let header = egui::TopBottomPanel::top("header").show(ctx, |ui| ids[0] = ui.button("Header").id);
+            let footer = egui::TopBottomPanel::bottom("footer").show(ctx, |ui| ids[1] = ui.button("Footer").id);
+            let page = egui::CentralPanel::default().show(ctx, |ui| ids[2] = ui.button("Page").id);
+            set_tab_order(ctx, vec![header.response.rect, page.response.rect, footer.response.rect]);
+        });
+        ids
+    }
+
+    fn tab(ctx: &egui::Context, shift: bool) -> egui::RawInput {
+        let mut input = egui::RawInput::default();
+        let modifiers = if shift { egui::Modifiers::SHIFT } else { egui::Modifiers::NONE };
+        input.events.push(egui::Event::Key { key: egui::Key::Tab, physical_key: None, pressed: true, repeat: false, modifiers });
+        follow_tab_order(ctx, &mut input);
+        input
+    }
+
+    #[test]
+    fn tab_visits_the_page_before_the_footer() {
+        let ctx = egui::Context::default();
+        let [header, footer, page] = frame(&ctx, egui::RawInput::default());
+        ctx.memory_mut(|m| m.request_focus(header));
+        frame(&ctx, egui::RawInput::default());
+
+        frame(&ctx, tab(&ctx, false));
+        assert_eq!(ctx.memory(|m| m.focused()), Some(page));
+        frame(&ctx, tab(&ctx, false));
+        assert_eq!(ctx.memory(|m| m.focused()), Some(footer));
+        frame(&ctx, tab(&ctx, false));
+        assert_eq!(ctx.memory(|m| m.focused()), Some(header));
+    }
+
+    #[test]
+    fn shift_tab_goes_back_the_same_way() {
+        let ctx = egui::Context::default();
+        let [header, footer, page] = frame(&ctx, egui::RawInput::default());
+        ctx.memory_mut(|m| m.request_focus(footer));
+        frame(&ctx, egui::RawInput::default());
+
+        frame(&ctx, tab(&ctx, true));
+        assert_eq!(ctx.memory(|m| m.focused()), Some(page));
+        frame(&ctx, tab(&ctx, true));
+        assert_eq!(ctx.memory(|m| m.focused()), Some(header));
+    }
+}
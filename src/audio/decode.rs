@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{self, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::{Time, TimeBase};
+
+
+/// Reads one file and hands back interleaved `f32` audio already converted
+/// to the sink's sample rate and channel count, so consecutive tracks can be
+/// written back to back without a gap.
+pub struct Decoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn codecs::Decoder>,
+    track_id: u32,
+    time_base: Option<TimeBase>,
+    duration: Option<Duration>,
+    resampler: Resampler,
+    out_channels: usize,
+}
+
+impl Decoder {
+    pub fn open(path: &Path, out_rate: u32, out_channels: u16) -> Result<Self, String> {
+        let failed = |e: Error| format!("{}: {e}", path.display());
+        let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(extension);
+        }
+        // Gapless trims the encoder's padding from MP3s so tracks join cleanly.
+        let options = FormatOptions { enable_gapless: true, ..Default::default() };
+        let format = symphonia::default::get_probe()
+            .format(&hint, stream, &options, &MetadataOptions::default())
+            .map_err(failed)?
+            .format;
+        let track = format.tracks().iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| format!("{}: no audio in this file", path.display()))?;
+        let params = &track.codec_params;
+        let decoder = symphonia::default::get_codecs()
+            .make(params, &codecs::DecoderOptions::default())
+            .map_err(failed)?;
+        let in_rate = params.sample_rate.unwrap_or(out_rate);
+        let duration = params.time_base.zip(params.n_frames)
+            .map(|(base, frames)| to_duration(base.calc_time(frames)));
+        Ok(Self {
+            track_id: track.id,
+            time_base: params.time_base,
+            duration,
+            decoder,
+            format,
+            resampler: Resampler::new(in_rate, out_rate),
+            out_channels: out_channels as usize,
+        })
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Appends the next stretch of audio to `out`. Returns false at the end
+    /// of the file.
+    pub fn read(&mut self, out: &mut Vec<f32>) -> Result<bool, String> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(false),
+                Err(Error::ResetRequired) => return Ok(false),
+                Err(e) => return Err(e.to_string()),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            match self.decoder.decode(&packet) {
+                Ok(audio) => {
+                    let spec = *audio.spec();
+                    let mut samples = SampleBuffer::<f32>::new(audio.capacity() as u64, spec);
+                    samples.copy_interleaved_ref(audio);
+                    self.resampler.push(samples.samples(), spec.channels.count(), self.out_channels, out);
+                    return Ok(true);
+                }
+                // A corrupt packet is skipped rather than ending the track.
+                Err(Error::DecodeError(_)) => continue,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    /// Jumps to roughly `position` and returns where it actually landed.
+    pub fn seek(&mut self, position: Duration) -> Result<Duration, String> {
+        let to = SeekTo::Time { time: Time::from(position.as_secs_f64()), track_id: Some(self.track_id) };
+        let seeked = self.format.seek(SeekMode::Coarse, to).map_err(|e| e.to_string())?;
+        self.decoder.reset();
+        self.resampler.reset();
+        Ok(self.time_base.map_or(position, |base| to_duration(base.calc_time(seeked.actual_ts))))
+    }
+}
+
+fn to_duration(time: Time) -> Duration {
+    Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac)
+}
+
+/// Linear-interpolating sample rate and channel conversion. Good enough for
+/// speech and background music, and it keeps its place between packets so
+/// there are no clicks at packet boundaries.
+struct Resampler {
+    /// Input frames per output frame.
+    step: f64,
+    /// Where the next output frame falls, in input frames from the start of
+    /// the next packet. Between -1 and 0 it falls after `previous`.
+    position: f64,
+    /// The last input frame of the previous packet.
+    previous: Vec<f32>,
+}
+
+impl Resampler {
+    fn new(in_rate: u32, out_rate: u32) -> Self {
+        Self {
+            step: in_rate as f64 / out_rate as f64,
+            position: 0.0,
+            previous: Vec::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.position = 0.0;
+        self.previous.clear();
+    }
+
+    fn push(&mut self, input: &[f32], channels: usize, out_channels: usize, out: &mut Vec<f32>) {
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return;
+        }
+        if self.previous.len() != channels {
+            self.reset();
+        }
+        let previous = &self.previous;
+        let frame = |i: isize| -> &[f32] {
+            match usize::try_from(i) {
+                Ok(i) => &input[i * channels..(i + 1) * channels],
+                Err(_) => previous,
+            }
+        };
+        let mut position = self.position;
+        while position < (frames - 1) as f64 {
+            let index = position.floor() as isize;
+            let t = (position - index as f64) as f32;
+            let (a, b) = (frame(index), frame(index + 1));
+            // Mono plays on every speaker; extra channels beyond the sink's
+            // are dropped.
+            for c in 0..out_channels {
+                let source = c % channels;
+                out.push(a[source] + (b[source] - a[source]) * t);
+            }
+            position += self.step;
+        }
+        self.position = position - frames as f64;
+        self.previous = input[(frames - 1) * channels..].to_vec();
+    }
+}
@@ -0,0 +1,136 @@
+//! Playback behind one interface for both builds: `NativePlayer` decodes local
+//! files with Symphonia, `WebPlayer` hands URLs to the browser's Web Audio
+//! API. The player panel only sees `Playback`.
+
+use std::time::Duration;
+
+use plistr_core::{Soundscape, Track};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod decode;
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+mod sink;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::NativePlayer;
+#[cfg(all(feature = "audio-output", not(target_arch = "wasm32")))]
+pub use sink::DeviceSink;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sink::{AudioSink, NullSink};
+#[cfg(target_arch = "wasm32")]
+pub use web::WebPlayer;
+
+
+/// A play queue and transport. Every call returns straight away; the
+/// backend catches up in the background and `status` reports where it got to.
+pub trait Playback {
+    fn status(&self) -> Status;
+    /// `track.source` is a file path natively and a URL on the web.
+    fn enqueue(&self, track: Track);
+    fn remove(&self, index: usize);
+    fn clear(&self);
+    /// Resumes, or starts from the top of the queue.
+    fn play(&self);
+    fn pause(&self);
+    fn toggle(&self) {
+        if self.status().playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+    /// Plays the track at `index` from its start.
+    fn jump(&self, index: usize);
+    fn next(&self);
+    /// Restarts the current track, or goes back one if it has only just
+    /// started.
+    fn previous(&self);
+    fn seek(&self, position: Duration);
+    /// From 0 to 1.
+    fn set_volume(&self, volume: f32);
+    /// How long consecutive tracks overlap. Backends that report no
+    /// `Status::crossfade` play them back to back regardless.
+    fn set_crossfade(&self, _crossfade: Duration) {}
+    /// Scales the queue's music under the volume, for scripted fades. Cues
+    /// and chimes aren't affected.
+    fn set_gain(&self, gain: f32);
+    /// Plays a clip once over the queue, like a spoken prompt. `source` is
+    /// a path or URL, as for tracks.
+    fn cue(&self, source: &str);
+    /// Generated sound under everything else, at the volume but not the
+    /// gain, whether or not the queue is playing. `None` stops it.
+    fn set_soundscape(&self, soundscape: Option<Soundscape>);
+    /// A short bell, to mark a change like the end of a focus block.
+    fn chime(&self);
+}
+
+/// What the backend is doing, as of the last time it looked.
+#[derive(Clone, Debug)]
+pub struct Status {
+    pub queue: Vec<Track>,
+    /// Index into `queue` of the track playing or paused.
+    pub current: Option<usize>,
+    pub playing: bool,
+    pub position: Duration,
+    /// Of the current track, when known.
+    pub duration: Option<Duration>,
+    pub volume: f32,
+    /// `None` when the backend can't crossfade.
+    pub crossfade: Option<Duration>,
+    /// The browser won't make sound until the page is clicked or typed in.
+    pub locked: bool,
+    /// The last track that couldn't be played, and why.
+    pub error: Option<String>,
+    pub output: String,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self {
+            queue: Vec::new(),
+            current: None,
+            playing: false,
+            position: Duration::ZERO,
+            duration: None,
+            volume: 1.0,
+            crossfade: None,
+            locked: false,
+            error: None,
+            output: String::new(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_player() -> Option<Box<dyn Playback>> {
+    Some(Box::new(NativePlayer::with_default_output()))
+}
+
+/// `None` in browsers without Web Audio, which then get no player.
+#[cfg(target_arch = "wasm32")]
+pub fn default_player() -> Option<Box<dyn Playback>> {
+    match WebPlayer::new() {
+        Ok(player) => Some(Box::new(player)),
+        Err(e) => {
+            crate::content::warn(&format!("No Web Audio, so no player: {e}"));
+            None
+        }
+    }
+}
+
+
+/// Extensions both backends can decode, for filtering dropped files.
+pub const EXTENSIONS: [&str; 7] = ["flac", "m4a", "mp3", "oga", "ogg", "wav", "wave"];
+
+/// The bell's two partials, in hertz, how long it rings and how loud it
+/// starts, before the volume.
+const CHIME_PARTIALS: [f32; 2] = [880.0, 1320.0];
+const CHIME_SECONDS: f64 = 1.2;
+const CHIME_LEVEL: f32 = 0.4;
+
+/// How far into a track "previous" restarts it instead of going back.
+const RESTART_AFTER: Duration = Duration::from_secs(3);
@@ -0,0 +1,709 @@
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use plistr_core::{Generator, Soundscape, Track};
+
+use super::decode;
+use super::sink::Pace;
+use super::{AudioSink, NullSink, Playback, Status, CHIME_LEVEL, CHIME_PARTIALS, CHIME_SECONDS, RESTART_AFTER};
+
+
+/// Decodes local files on its own thread into an `AudioSink`; the UI only
+/// sends commands and reads back a `Status` snapshot.
+pub struct NativePlayer {
+    commands: mpsc::Sender<Command>,
+    status: Arc<Mutex<Status>>,
+}
+
+enum Command {
+    Enqueue(Track),
+    Remove(usize),
+    Clear,
+    Play,
+    Pause,
+    Jump(usize),
+    Next,
+    Previous,
+    Seek(Duration),
+    Volume(f32),
+    Gain(f32),
+    Cue(String),
+    Soundscape(Option<Soundscape>),
+    Chime,
+}
+
+impl NativePlayer {
+    /// Starts the engine thread. The sink is opened on that thread, because
+    /// device streams can't always be moved between threads.
+    pub fn new(open_sink: impl FnOnce() -> Box<dyn AudioSink> + Send + 'static) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let status = Arc::new(Mutex::new(Status::default()));
+        let shared = status.clone();
+        std::thread::Builder::new()
+            .name("audio".to_owned())
+            .spawn(move || Engine::new(open_sink(), shared).run(receiver))
+            .expect("couldn't start the audio thread");
+        Self { commands, status }
+    }
+
+    /// The default output device, or a silent `NullSink` when there isn't one
+    /// or the build has no `audio-output`, which is the default.
+    pub fn with_default_output() -> Self {
+        Self::new(|| {
+            #[cfg(feature = "audio-output")]
+            match super::DeviceSink::open() {
+                Ok(sink) => return Box::new(sink),
+                Err(e) => crate::content::warn(&format!("No audio output, playing silently: {e}")),
+            }
+            Box::new(NullSink::new(FALLBACK_RATE, 2))
+        })
+    }
+
+    fn send(&self, command: Command) {
+        // The engine only stops when this handle is dropped.
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Playback for NativePlayer {
+    fn status(&self) -> Status {
+        lock(&self.status).clone()
+    }
+
+    /// `track.source` is a file path.
+    fn enqueue(&self, track: Track) {
+        self.send(Command::Enqueue(track));
+    }
+
+    fn remove(&self, index: usize) {
+        self.send(Command::Remove(index));
+    }
+
+    fn clear(&self) {
+        self.send(Command::Clear);
+    }
+
+    fn play(&self) {
+        self.send(Command::Play);
+    }
+
+    fn pause(&self) {
+        self.send(Command::Pause);
+    }
+
+    fn jump(&self, index: usize) {
+        self.send(Command::Jump(index));
+    }
+
+    fn next(&self) {
+        self.send(Command::Next);
+    }
+
+    fn previous(&self) {
+        self.send(Command::Previous);
+    }
+
+    fn seek(&self, position: Duration) {
+        self.send(Command::Seek(position));
+    }
+
+    fn set_volume(&self, volume: f32) {
+        self.send(Command::Volume(volume.clamp(0.0, 1.0)));
+    }
+
+    fn set_gain(&self, gain: f32) {
+        self.send(Command::Gain(gain.clamp(0.0, 1.0)));
+    }
+
+    fn cue(&self, source: &str) {
+        self.send(Command::Cue(source.to_owned()));
+    }
+
+    fn set_soundscape(&self, soundscape: Option<Soundscape>) {
+        self.send(Command::Soundscape(soundscape));
+    }
+
+    fn chime(&self) {
+        self.send(Command::Chime);
+    }
+}
+
+struct Engine {
+    sink: Box<dyn AudioSink>,
+    status: Arc<Mutex<Status>>,
+    decoder: Option<decode::Decoder>,
+    /// Frames of the current track written to the sink, counting from where
+    /// it was last seeked to.
+    written: u64,
+    start: Duration,
+    /// `Playback::set_gain`, on top of the volume.
+    gain: f32,
+    cues: Vec<Cue>,
+    soundscape: Option<Generator>,
+    /// The soundscape's stereo, before it's spread over the sink's channels.
+    generated: Vec<f32>,
+    /// Keeps cues and soundscapes to real time when there's no music, in
+    /// case the sink doesn't block.
+    pace: Pace,
+}
+
+/// A sound mixed over the queue: a clip being decoded, or the chime.
+struct Cue {
+    decoder: Option<decode::Decoder>,
+    /// Decoded but not yet mixed.
+    pending: Vec<f32>,
+}
+
+impl Engine {
+    fn new(sink: Box<dyn AudioSink>, status: Arc<Mutex<Status>>) -> Self {
+        lock(&status).output = sink.describe();
+        Self {
+            pace: Pace::new(sink.sample_rate(), MIX_LEAD),
+            sink,
+            status,
+            decoder: None,
+            written: 0,
+            start: Duration::ZERO,
+            gain: 1.0,
+            cues: Vec::new(),
+            soundscape: None,
+            generated: Vec::new(),
+        }
+    }
+
+    fn run(mut self, commands: mpsc::Receiver<Command>) {
+        let mut samples = Vec::new();
+        loop {
+            let busy = lock(&self.status).playing || self.mixing();
+            let command = if busy {
+                match commands.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(mpsc::TryRecvError::Empty) => None,
+                    Err(mpsc::TryRecvError::Disconnected) => return,
+                }
+            } else {
+                // Nothing to decode, so sleep until told otherwise.
+                match commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                }
+            };
+            match command {
+                Some(command) => self.handle(command),
+                None => {
+                    samples.clear();
+                    let (playing, volume) = {
+                        let status = lock(&self.status);
+                        (status.playing, status.volume)
+                    };
+                    if playing {
+                        self.decode(&mut samples);
+                        let level = volume * self.gain;
+                        if level != 1.0 {
+                            samples.iter_mut().for_each(|sample| *sample *= level);
+                        }
+                    }
+                    let music = samples.len();
+                    self.mix_soundscape(&mut samples, volume);
+                    self.mix_cues(&mut samples, volume);
+                    if !samples.is_empty() {
+                        self.sink.write(&samples);
+                    }
+                    if music == 0 {
+                        self.pace.wait(samples.len() / self.sink.channels().max(1) as usize);
+                    } else {
+                        self.pace.reset();
+                    }
+                    self.advance(music);
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, command: Command) {
+        let mut status = lock(&self.status);
+        match command {
+            Command::Enqueue(track) => status.queue.push(track),
+            Command::Remove(index) if index < status.queue.len() => {
+                status.queue.remove(index);
+                match status.current {
+                    Some(current) if index < current => status.current = Some(current - 1),
+                    // The next track slides into its place and plays from
+                    // the start.
+                    Some(current) if index == current => {
+                        drop(status);
+                        self.load(current);
+                    }
+                    _ => {}
+                }
+            }
+            Command::Remove(_) => {}
+            Command::Clear => {
+                status.queue.clear();
+                drop(status);
+                self.load(usize::MAX);
+            }
+            Command::Play => {
+                if status.current.is_none() && !status.queue.is_empty() {
+                    status.current = Some(0);
+                }
+                status.playing = status.current.is_some();
+                drop(status);
+                self.sink.set_paused(false);
+            }
+            Command::Pause => {
+                status.playing = false;
+                drop(status);
+                // Cues and soundscapes keep sounding, so the sink has to
+                // keep draining.
+                if !self.mixing() {
+                    self.sink.set_paused(true);
+                } else {
+                    self.sink.clear();
+                }
+            }
+            Command::Jump(index) => {
+                drop(status);
+                self.load(index);
+                self.play();
+            }
+            Command::Next => {
+                if let Some(current) = status.current {
+                    drop(status);
+                    self.load(current + 1);
+                }
+            }
+            Command::Previous => {
+                if let Some(current) = status.current {
+                    let restart = status.position > RESTART_AFTER || current == 0;
+                    drop(status);
+                    self.load(if restart { current } else { current - 1 });
+                }
+            }
+            Command::Seek(position) => {
+                drop(status);
+                self.seek(position);
+            }
+            Command::Volume(volume) => status.volume = volume,
+            Command::Gain(gain) => self.gain = gain,
+            Command::Cue(source) => {
+                drop(status);
+                match decode::Decoder::open(Path::new(&source), self.sink.sample_rate(), self.sink.channels()) {
+                    Ok(decoder) => self.add_cue(Cue { decoder: Some(decoder), pending: Vec::new() }),
+                    Err(e) => lock(&self.status).error = Some(e),
+                }
+            }
+            Command::Soundscape(soundscape) => {
+                drop(status);
+                let generator = soundscape.map(|soundscape| soundscape.generator(self.sink.sample_rate()));
+                if generator.is_some() && !self.mixing() {
+                    self.wake_sink();
+                }
+                self.soundscape = generator;
+            }
+            Command::Chime => {
+                drop(status);
+                let pending = chime(self.sink.sample_rate(), self.sink.channels());
+                self.add_cue(Cue { decoder: None, pending });
+            }
+        }
+    }
+
+    /// Makes `index` the current track, from its start. Past the end of the
+    /// queue, playback stops.
+    fn load(&mut self, index: usize) {
+        self.sink.clear();
+        self.decoder = None;
+        self.written = 0;
+        self.start = Duration::ZERO;
+        let mut status = lock(&self.status);
+        status.position = Duration::ZERO;
+        status.duration = None;
+        if index < status.queue.len() {
+            status.current = Some(index);
+        } else {
+            status.current = None;
+            status.playing = false;
+        }
+    }
+
+    fn play(&mut self) {
+        let mut status = lock(&self.status);
+        status.playing = status.current.is_some();
+        drop(status);
+        self.sink.set_paused(false);
+    }
+
+    fn seek(&mut self, position: Duration) {
+        if self.decoder.is_none() {
+            self.open();
+        }
+        let Some(decoder) = &mut self.decoder else {
+            return;
+        };
+        match decoder.seek(position) {
+            Ok(landed) => {
+                self.sink.clear();
+                self.written = 0;
+                self.start = landed;
+                lock(&self.status).position = landed;
+            }
+            Err(e) => lock(&self.status).error = Some(e),
+        }
+    }
+
+    /// Opens the current track, skipping ahead past any that fail.
+    fn open(&mut self) {
+        let mut status = lock(&self.status);
+        while let Some(current) = status.current {
+            let track = &mut status.queue[current];
+            let path = PathBuf::from(&track.source);
+            match decode::Decoder::open(&path, self.sink.sample_rate(), self.sink.channels()) {
+                Ok(decoder) => {
+                    track.duration = track.duration.or(decoder.duration());
+                    status.duration = decoder.duration();
+                    self.decoder = Some(decoder);
+                    return;
+                }
+                Err(e) => {
+                    status.error = Some(e);
+                    status.current = Some(current + 1).filter(|&next| next < status.queue.len());
+                }
+            }
+        }
+        status.playing = false;
+    }
+
+    /// Appends the next stretch of audio. At the end of a track the next one
+    /// is opened straight away and its first packet follows in the same
+    /// buffer, so there is no gap between them.
+    fn decode(&mut self, samples: &mut Vec<f32>) {
+        loop {
+            if self.decoder.is_none() {
+                self.open();
+            }
+            let Some(decoder) = &mut self.decoder else {
+                return;
+            };
+            match decoder.read(samples) {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(e) => lock(&self.status).error = Some(e),
+            }
+            // Finished (or failed) with this track; move on without clearing
+            // what the sink still has to play.
+            self.decoder = None;
+            self.written = 0;
+            self.start = Duration::ZERO;
+            let mut status = lock(&self.status);
+            let next = status.current.map(|current| current + 1);
+            status.current = next.filter(|&next| next < status.queue.len());
+            status.position = Duration::ZERO;
+            status.duration = None;
+        }
+    }
+
+    fn add_cue(&mut self, cue: Cue) {
+        if !self.mixing() {
+            self.wake_sink();
+        }
+        self.cues.push(cue);
+    }
+
+    /// Whether there's anything to write besides the queue.
+    fn mixing(&self) -> bool {
+        !self.cues.is_empty() || self.soundscape.is_some()
+    }
+
+    /// Readies the sink for sound over a stopped queue. It may be holding
+    /// paused music, which shouldn't play out.
+    fn wake_sink(&mut self) {
+        if !lock(&self.status).playing {
+            self.sink.clear();
+            self.sink.set_paused(false);
+        }
+    }
+
+    /// Adds the soundscape into `out`, pacing itself like the cues when
+    /// there's no music.
+    fn mix_soundscape(&mut self, out: &mut Vec<f32>, volume: f32) {
+        let Some(generator) = &mut self.soundscape else {
+            return;
+        };
+        let channels = self.sink.channels().max(1) as usize;
+        if out.is_empty() {
+            out.resize(CUE_CHUNK * channels, 0.0);
+        }
+        let stereo = Soundscape::CHANNELS as usize;
+        self.generated.resize(out.len() / channels * stereo, 0.0);
+        generator.fill(&mut self.generated);
+        for (frame, generated) in out.chunks_exact_mut(channels).zip(self.generated.chunks_exact(stereo)) {
+            if channels == 1 {
+                frame[0] += (generated[0] + generated[1]) / 2.0 * volume;
+            } else {
+                for (channel, sample) in frame.iter_mut().enumerate() {
+                    *sample += generated[channel % stereo] * volume;
+                }
+            }
+        }
+    }
+
+    /// Adds the cues into `out`. With no music to go under them, they pace
+    /// themselves in short chunks.
+    fn mix_cues(&mut self, out: &mut Vec<f32>, volume: f32) {
+        if self.cues.is_empty() {
+            return;
+        }
+        if out.is_empty() {
+            out.resize(CUE_CHUNK * self.sink.channels() as usize, 0.0);
+        }
+        let wanted = out.len();
+        for cue in &mut self.cues {
+            while cue.pending.len() < wanted {
+                let Some(decoder) = &mut cue.decoder else {
+                    break;
+                };
+                match decoder.read(&mut cue.pending) {
+                    Ok(true) => {}
+                    Ok(false) => cue.decoder = None,
+                    Err(e) => {
+                        lock(&self.status).error = Some(e);
+                        cue.decoder = None;
+                    }
+                }
+            }
+            let mixed = wanted.min(cue.pending.len());
+            for (out, sample) in out.iter_mut().zip(cue.pending.drain(..mixed)) {
+                *out += sample * volume;
+            }
+        }
+        self.cues.retain(|cue| cue.decoder.is_some() || !cue.pending.is_empty());
+    }
+
+    /// Counts `samples` of the current track as played.
+    fn advance(&mut self, samples: usize) {
+        if samples == 0 {
+            return;
+        }
+        let channels = self.sink.channels().max(1) as usize;
+        self.written += (samples / channels) as u64;
+        let played = Duration::from_secs_f64(self.written as f64 / self.sink.sample_rate() as f64);
+        lock(&self.status).position = self.start + played;
+    }
+}
+
+/// The bell, ringing straight up and dying away.
+fn chime(rate: u32, channels: u16) -> Vec<f32> {
+    let frames = (rate as f64 * CHIME_SECONDS) as usize;
+    (0..frames)
+        .flat_map(|frame| {
+            let t = frame as f32 / rate as f32;
+            // A 5 ms attack, so it doesn't click.
+            let envelope = (t / 0.005).min(1.0) * (-4.0 * t).exp();
+            let bell: f32 = CHIME_PARTIALS.iter().map(|hz| (std::f32::consts::TAU * hz * t).sin()).sum();
+            let sample = CHIME_LEVEL * envelope * bell / CHIME_PARTIALS.len() as f32;
+            std::iter::repeat_n(sample, channels as usize)
+        })
+        .collect()
+}
+
+/// A panicking engine thread shouldn't take the UI down with it.
+fn lock(status: &Mutex<Status>) -> MutexGuard<'_, Status> {
+    status.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+
+/// Frames per write when only cues are playing.
+const CUE_CHUNK: usize = 1024;
+
+/// How far cues and soundscapes may run ahead of real time with no music.
+/// Longer than any device's buffer, so a sink that blocks sets the pace.
+const MIX_LEAD: Duration = Duration::from_millis(500);
+
+/// The rate a `NullSink` runs at when there's no device to ask.
+const FALLBACK_RATE: u32 = 44_100;
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use plistr_core::{Layer, Sound, TrackId};
+
+    use super::*;
+
+    const RATE: u32 = 8000;
+
+    /// Writes a mono 16-bit WAV holding each `(level, frames)` in turn, and
+    /// gives it back as a track.
+    fn wav(name: &str, parts: &[(f32, usize)]) -> Track {
+        let samples: Vec<i16> = parts.iter()
+            .flat_map(|&(level, frames)| std::iter::repeat_n((level * 32768.0) as i16, frames))
+            .collect();
+        let data = samples.len() as u32 * 2;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&RATE.to_le_bytes());
+        bytes.extend_from_slice(&(RATE * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        let path = std::env::temp_dir().join(format!("plistr-engine-{}-{name}.wav", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        Track::new(TrackId::unstored(), name, path.to_string_lossy())
+    }
+
+    /// A player on a stereo `NullSink`, and what it's been given to play.
+    fn player() -> (NativePlayer, Arc<Mutex<Vec<f32>>>) {
+        let sink = NullSink::new(RATE, 2);
+        let recorded = sink.recorded();
+        (NativePlayer::new(move || Box::new(sink)), recorded)
+    }
+
+    /// Waits for the engine to get somewhere, failing after a generous
+    /// deadline rather than hanging.
+    fn wait_for(player: &NativePlayer, recorded: &Mutex<Vec<f32>>, done: impl Fn(&Status, usize) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let frames = recorded.lock().unwrap().len() / 2;
+            let status = player.status();
+            if done(&status, frames) {
+                return;
+            }
+            assert!(Instant::now() < deadline, "gave up at {frames} frames, {status:?}");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// How many frames in a row at each level, to the nearest 1/1000.
+    fn runs(recorded: &Mutex<Vec<f32>>) -> Vec<(f32, usize)> {
+        let mut runs: Vec<(f32, usize)> = Vec::new();
+        for frame in recorded.lock().unwrap().chunks_exact(2) {
+            assert_eq!(frame[0], frame[1], "mono goes to both speakers");
+            let level = (frame[0] * 1000.0).round() / 1000.0;
+            match runs.last_mut() {
+                Some((last, count)) if *last == level => *count += 1,
+                _ => runs.push((level, 1)),
+            }
+        }
+        runs
+    }
+
+    /// The resampler holds back each file's last frame for the next packet.
+    fn about(frames: usize, expected: usize) -> bool {
+        frames + 1 == expected || frames == expected
+    }
+
+    #[test]
+    fn the_queue_plays_through_without_a_gap() {
+        let (player, recorded) = player();
+        player.enqueue(wav("first", &[(0.25, 800)]));
+        player.enqueue(wav("second", &[(0.5, 1200)]));
+        player.play();
+        wait_for(&player, &recorded, |status, frames| status.current.is_none() && frames >= 1998);
+
+        let runs = runs(&recorded);
+        assert_eq!(runs.len(), 2, "{runs:?}");
+        assert!(runs[0].0 == 0.25 && about(runs[0].1, 800), "{runs:?}");
+        assert!(runs[1].0 == 0.5 && about(runs[1].1, 1200), "{runs:?}");
+        let status = player.status();
+        assert!(!status.playing);
+        assert_eq!(status.queue[1].duration, Some(Duration::from_millis(150)));
+    }
+
+    #[test]
+    fn volume_and_gain_scale_the_music() {
+        let (player, recorded) = player();
+        player.set_volume(0.5);
+        player.set_gain(0.5);
+        player.enqueue(wav("quiet", &[(0.5, 400)]));
+        player.play();
+        wait_for(&player, &recorded, |status, frames| status.current.is_none() && frames >= 399);
+        let runs = runs(&recorded);
+        assert!(runs.len() == 1 && runs[0].0 == 0.125 && about(runs[0].1, 400), "{runs:?}");
+    }
+
+    #[test]
+    fn pausing_stops_the_writes_and_playing_picks_up_where_it_was() {
+        let (player, recorded) = player();
+        // A second, which the sink takes at the speed it would be heard.
+        player.enqueue(wav("long", &[(0.25, RATE as usize)]));
+        player.play();
+        wait_for(&player, &recorded, |_, frames| frames > 0);
+        player.pause();
+        wait_for(&player, &recorded, |status, _| !status.playing);
+        let paused_at = recorded.lock().unwrap().len();
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(recorded.lock().unwrap().len(), paused_at);
+        assert!(paused_at / 2 < RATE as usize);
+
+        player.play();
+        wait_for(&player, &recorded, |status, frames| status.current.is_none() && frames >= RATE as usize - 1);
+        // Nothing played twice and nothing skipped.
+        let runs = runs(&recorded);
+        assert!(runs.len() == 1 && about(runs[0].1, RATE as usize), "{runs:?}");
+    }
+
+    #[test]
+    fn seeking_skips_what_is_in_between() {
+        let (player, recorded) = player();
+        // 1.5 s quiet, then half a second louder.
+        player.enqueue(wav("steps", &[(0.25, RATE as usize * 3 / 2), (0.5, RATE as usize / 2)]));
+        player.play();
+        wait_for(&player, &recorded, |_, frames| frames > 0);
+        player.seek(Duration::from_millis(1500));
+        wait_for(&player, &recorded, |status, _| status.current.is_none());
+
+        let runs = runs(&recorded);
+        assert_eq!(runs.len(), 2, "{runs:?}");
+        assert!(runs[0].0 == 0.25 && runs[0].1 < RATE as usize, "{runs:?}");
+        assert!(runs[1].0 == 0.5 && about(runs[1].1, RATE as usize / 2), "{runs:?}");
+    }
+
+    /// Takes everything at once, like a sink with an endless buffer.
+    struct Eager(Arc<Mutex<usize>>);
+
+    impl AudioSink for Eager {
+        fn sample_rate(&self) -> u32 {
+            8000
+        }
+
+        fn channels(&self) -> u16 {
+            2
+        }
+
+        fn write(&mut self, samples: &[f32]) {
+            *self.0.lock().unwrap() += samples.len() / 2;
+        }
+
+        fn describe(&self) -> String {
+            "Eager".to_owned()
+        }
+    }
+
+    #[test]
+    fn soundscapes_keep_to_real_time_over_a_sink_that_never_blocks() {
+        let frames = Arc::new(Mutex::new(0));
+        let counted = frames.clone();
+        let player = NativePlayer::new(move || Box::new(Eager(counted)));
+        let rain = Layer { sound: Sound::Rain, volume: 0.5 };
+        player.set_soundscape(Some(Soundscape { layers: vec![rain], seed: 1 }));
+        std::thread::sleep(Duration::from_millis(300));
+        player.set_soundscape(None);
+        let written = *frames.lock().unwrap();
+        // 300 ms and the lead, with room for a slow machine, but nowhere
+        // near the minutes an unpaced loop gets through.
+        assert!(written > 8000 / 4, "{written} frames");
+        assert!(written < 8000 * 2, "{written} frames");
+    }
+}
@@ -0,0 +1,278 @@
+#[cfg(any(test, feature = "audio-output"))]
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+/// Where decoded audio goes: interleaved `f32` frames at a fixed rate. A
+/// sink is opened on, and stays on, the player's engine thread.
+pub trait AudioSink {
+    fn sample_rate(&self) -> u32;
+    fn channels(&self) -> u16;
+    /// Queues `samples`, blocking while the device's buffer is full so the
+    /// engine doesn't run ahead of playback.
+    fn write(&mut self, samples: &[f32]);
+    /// Drops anything queued but not yet heard, for seeking and skipping.
+    fn clear(&mut self) {}
+    /// Holds what is queued without dropping it.
+    fn set_paused(&mut self, _paused: bool) {}
+    /// Short name for the player panel.
+    fn describe(&self) -> String;
+}
+
+/// Plays nothing, for builds without `audio-output` and machines without a
+/// sound card. Writes are held to real time like a device's, so the queue
+/// moves at the speed it would if it were heard.
+pub struct NullSink {
+    sample_rate: u32,
+    channels: u16,
+    pace: Pace,
+    /// Every sample written, for checking the engine's output headless.
+    #[cfg(test)]
+    recorded: Arc<Mutex<Vec<f32>>>,
+}
+
+impl NullSink {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            sample_rate,
+            channels,
+            pace: Pace::new(sample_rate, NULL_BUFFER),
+            #[cfg(test)]
+            recorded: Arc::default(),
+        }
+    }
+
+    /// Everything written so far, readable after the sink has been handed to
+    /// a `Player`.
+    #[cfg(test)]
+    pub fn recorded(&self) -> Arc<Mutex<Vec<f32>>> {
+        self.recorded.clone()
+    }
+}
+
+impl AudioSink for NullSink {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        #[cfg(test)]
+        self.recorded.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(samples);
+        self.pace.wait(samples.len() / self.channels.max(1) as usize);
+    }
+
+    fn clear(&mut self) {
+        self.pace.reset();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        if paused {
+            self.pace.reset();
+        }
+    }
+
+    fn describe(&self) -> String {
+        if cfg!(feature = "audio-output") {
+            "No audio output".to_owned()
+        } else {
+            "No audio output (built without audio-output)".to_owned()
+        }
+    }
+}
+
+/// Holds a writer to real time: `wait` returns once what has been written
+/// is at most `lead` ahead of the clock. A writer that falls further behind
+/// than that, say after sitting idle, starts the clock again rather than
+/// rushing to catch up.
+pub struct Pace {
+    rate: u32,
+    lead: Duration,
+    started: Option<Instant>,
+    frames: u64,
+}
+
+impl Pace {
+    pub fn new(rate: u32, lead: Duration) -> Self {
+        Self { rate: rate.max(1), lead, started: None, frames: 0 }
+    }
+
+    /// Counts `frames` as written, sleeping until they're due.
+    pub fn wait(&mut self, frames: usize) {
+        let now = Instant::now();
+        let started = match self.started {
+            Some(started) if started + self.written() + self.lead >= now => started,
+            _ => {
+                self.frames = 0;
+                now
+            }
+        };
+        self.started = Some(started);
+        self.frames += frames as u64;
+        let due = started + self.written();
+        if let Some(ahead) = due.checked_duration_since(now).and_then(|ahead| ahead.checked_sub(self.lead)) {
+            std::thread::sleep(ahead);
+        }
+    }
+
+    /// Forgets what was written, for when it's been dropped or held.
+    pub fn reset(&mut self) {
+        self.started = None;
+        self.frames = 0;
+    }
+
+    fn written(&self) -> Duration {
+        Duration::from_secs_f64(self.frames as f64 / self.rate as f64)
+    }
+}
+
+/// The default output device, through cpal.
+#[cfg(feature = "audio-output")]
+pub struct DeviceSink {
+    _stream: cpal::Stream,
+    sample_rate: u32,
+    channels: u16,
+    name: String,
+    buffer: Arc<Mutex<std::collections::VecDeque<f32>>>,
+    paused: Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(feature = "audio-output")]
+impl DeviceSink {
+    pub fn open() -> Result<Self, String> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or("no audio output device")?;
+        let name = device.name().unwrap_or_else(|_| "Audio output".to_owned());
+        let config = device.default_output_config().map_err(|e| e.to_string())?;
+        if config.sample_format() != cpal::SampleFormat::F32 {
+            return Err(format!("{name} doesn't take f32 samples"));
+        }
+        let config: cpal::StreamConfig = config.into();
+        let buffer = Arc::new(Mutex::new(std::collections::VecDeque::new()));
+        let paused = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let (source, held) = (buffer.clone(), paused.clone());
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |out: &mut [f32], _| {
+                    if held.load(std::sync::atomic::Ordering::Relaxed) {
+                        out.fill(0.0);
+                        return;
+                    }
+                    let mut source = source.lock().unwrap_or_else(|e| e.into_inner());
+                    for sample in out {
+                        // Running dry plays silence rather than stopping.
+                        *sample = source.pop_front().unwrap_or(0.0);
+                    }
+                },
+                |e| eprintln!("audio output: {e}"),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(Self {
+            _stream: stream,
+            sample_rate: config.sample_rate.0,
+            channels: config.channels,
+            name,
+            buffer,
+            paused,
+        })
+    }
+}
+
+#[cfg(feature = "audio-output")]
+impl AudioSink for DeviceSink {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        let limit = (self.sample_rate as f32 * self.channels as f32 * BUFFER_SECONDS) as usize;
+        loop {
+            {
+                let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+                if buffer.len() < limit {
+                    buffer.extend(samples);
+                    return;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn clear(&mut self) {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused.store(paused, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn describe(&self) -> String {
+        self.name.clone()
+    }
+}
+
+
+
+/// How far ahead of the speaker the engine may decode. Long enough to ride
+/// out a slow frame, short enough that pause and seek feel immediate.
+#[cfg(feature = "audio-output")]
+const BUFFER_SECONDS: f32 = 0.25;
+
+/// How far ahead of the clock a `NullSink` lets the engine get, as if it
+/// had a device's buffer.
+const NULL_BUFFER: Duration = Duration::from_millis(250);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_sink_keeps_to_real_time() {
+        let mut sink = NullSink::new(1000, 2);
+        let started = Instant::now();
+        // Half a second, in 50 ms writes.
+        for _ in 0..10 {
+            sink.write(&[0.25; 100]);
+        }
+        let took = started.elapsed();
+        assert!(took >= Duration::from_millis(200), "took {took:?}");
+        assert!(took < Duration::from_millis(450), "took {took:?}");
+        assert_eq!(sink.recorded().lock().unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn pace_starts_over_after_idling() {
+        let mut pace = Pace::new(1000, Duration::ZERO);
+        pace.wait(10);
+        std::thread::sleep(Duration::from_millis(50));
+        // Behind by 40 ms, which isn't made up for with a burst.
+        let started = Instant::now();
+        pace.wait(20);
+        pace.wait(20);
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn clearing_forgets_what_was_written() {
+        let mut sink = NullSink::new(1000, 1);
+        sink.write(&[0.0; 200]);
+        sink.clear();
+        let started = Instant::now();
+        sink.write(&[0.0; 200]);
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+}
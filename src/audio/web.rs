@@ -0,0 +1,556 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use eframe::wasm_bindgen::closure::Closure;
+use eframe::wasm_bindgen::{JsCast as _, JsValue};
+use plistr_core::{Soundscape, Track, TrackId};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AudioBuffer, AudioBufferSourceNode, AudioContext, AudioContextState, AudioScheduledSourceNode, GainNode};
+
+use super::{Playback, Status, CHIME_LEVEL, CHIME_PARTIALS, CHIME_SECONDS, RESTART_AFTER};
+
+
+/// Plays through the Web Audio API. Tracks are fetched and decoded whole and
+/// then scheduled on the audio clock, so the next one starts (or fades in)
+/// exactly as the last ends, even while the tab isn't drawing.
+pub struct WebPlayer {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Inner {
+    this: Weak<RefCell<Inner>>,
+    context: AudioContext,
+    /// Volume, shared by everything.
+    master: GainNode,
+    /// `Playback::set_gain`, which only the queue's voices go through.
+    music: GainNode,
+    status: Status,
+    /// Only the current and next tracks are kept; a decoded track is
+    /// around 10 MB a minute.
+    buffers: HashMap<TrackId, AudioBuffer>,
+    loading: HashSet<TrackId>,
+    failed: HashSet<TrackId>,
+    /// Where in the current track to start from when it isn't sounding.
+    offset: f64,
+    current: Option<Voice>,
+    /// Scheduled to start as `current` ends.
+    upcoming: Option<Voice>,
+    /// The track before, finishing its fade out.
+    fading: Option<Voice>,
+    /// A rendered stretch of the soundscape, looping.
+    soundscape: Option<AudioBufferSourceNode>,
+    /// Resumes the context on the first click or key press, which browsers
+    /// insist on before any sound.
+    unlock: Option<Closure<dyn FnMut()>>,
+}
+
+/// One track playing, or scheduled to, through its own gain for fading.
+struct Voice {
+    index: usize,
+    source: AudioBufferSourceNode,
+    gain: GainNode,
+    /// Audio-clock time at which the track's very start would have played.
+    origin: f64,
+    end: f64,
+    _ended: Closure<dyn FnMut()>,
+}
+
+impl WebPlayer {
+    pub fn new() -> Result<Self, String> {
+        let context = AudioContext::new().map_err(describe)?;
+        let master = context.create_gain().map_err(describe)?;
+        master.connect_with_audio_node(&context.destination()).map_err(describe)?;
+        let music = context.create_gain().map_err(describe)?;
+        music.connect_with_audio_node(&master).map_err(describe)?;
+        let inner = Rc::new_cyclic(|this| {
+            RefCell::new(Inner {
+                this: this.clone(),
+                context,
+                master,
+                music,
+                status: Status {
+                    crossfade: Some(Duration::ZERO),
+                    output: "Web Audio".to_owned(),
+                    ..Default::default()
+                },
+                buffers: HashMap::new(),
+                loading: HashSet::new(),
+                failed: HashSet::new(),
+                offset: 0.0,
+                current: None,
+                upcoming: None,
+                fading: None,
+                soundscape: None,
+                unlock: None,
+            })
+        });
+        {
+            let mut inner = inner.borrow_mut();
+            if inner.context.state() != AudioContextState::Running {
+                inner.listen_for_gesture().map_err(describe)?;
+            }
+            inner.refresh();
+        }
+        Ok(Self { inner })
+    }
+
+    fn with(&self, change: impl FnOnce(&mut Inner)) {
+        let mut inner = self.inner.borrow_mut();
+        change(&mut inner);
+        inner.refresh();
+    }
+}
+
+impl Playback for WebPlayer {
+    fn status(&self) -> Status {
+        let mut inner = self.inner.borrow_mut();
+        inner.refresh();
+        inner.status.clone()
+    }
+
+    /// `track.source` is a URL, which may be a `blob:` one for dropped files.
+    fn enqueue(&self, track: Track) {
+        self.with(|inner| inner.status.queue.push(track));
+    }
+
+    fn remove(&self, index: usize) {
+        self.with(|inner| {
+            if index >= inner.status.queue.len() {
+                return;
+            }
+            inner.status.queue.remove(index);
+            match inner.status.current {
+                Some(current) if index < current => {
+                    inner.status.current = Some(current - 1);
+                    inner.cancel_upcoming();
+                    if let Some(voice) = &mut inner.current {
+                        voice.index -= 1;
+                    }
+                }
+                // The next track slides into its place and plays from the
+                // start.
+                Some(current) if index == current => inner.go_to(current),
+                _ => inner.cancel_upcoming(),
+            }
+        });
+    }
+
+    fn clear(&self) {
+        self.with(|inner| {
+            inner.status.queue.clear();
+            inner.go_to(usize::MAX);
+        });
+    }
+
+    fn play(&self) {
+        self.with(|inner| {
+            // Counts as a gesture in most browsers, as egui handles input
+            // straight after the event.
+            let _ = inner.context.resume();
+            if inner.status.current.is_none() && !inner.status.queue.is_empty() {
+                inner.status.current = Some(0);
+            }
+            inner.status.playing = inner.status.current.is_some();
+        });
+    }
+
+    fn pause(&self) {
+        self.with(|inner| {
+            inner.offset = inner.position();
+            inner.stop_voices();
+            inner.status.playing = false;
+        });
+    }
+
+    fn jump(&self, index: usize) {
+        self.with(|inner| {
+            inner.go_to(index);
+            inner.status.playing = inner.status.current.is_some();
+        });
+    }
+
+    fn next(&self) {
+        self.with(|inner| {
+            if let Some(current) = inner.status.current {
+                inner.go_to(current + 1);
+            }
+        });
+    }
+
+    fn previous(&self) {
+        self.with(|inner| {
+            if let Some(current) = inner.status.current {
+                let restart = inner.position() > RESTART_AFTER.as_secs_f64() || current == 0;
+                inner.go_to(if restart { current } else { current - 1 });
+            }
+        });
+    }
+
+    fn seek(&self, position: Duration) {
+        self.with(|inner| {
+            inner.stop_voices();
+            inner.offset = position.as_secs_f64();
+        });
+    }
+
+    fn set_volume(&self, volume: f32) {
+        self.with(|inner| {
+            inner.status.volume = volume.clamp(0.0, 1.0);
+            inner.master.gain().set_value(inner.status.volume);
+        });
+    }
+
+    fn set_crossfade(&self, crossfade: Duration) {
+        self.with(|inner| {
+            inner.status.crossfade = Some(crossfade);
+            inner.cancel_upcoming();
+        });
+    }
+
+    fn set_gain(&self, gain: f32) {
+        self.inner.borrow().music.gain().set_value(gain.clamp(0.0, 1.0));
+    }
+
+    fn cue(&self, source: &str) {
+        let inner = self.inner.borrow();
+        let (url, context, master, this) = (source.to_owned(), inner.context.clone(), inner.master.clone(), inner.this.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            let played = match decode(&context, &url).await {
+                Ok(buffer) => play_once(&context, &master, &buffer).map_err(describe),
+                Err(e) => Err(e),
+            };
+            if let (Err(e), Some(inner)) = (played, this.upgrade()) {
+                inner.borrow_mut().status.error = Some(format!("{url}: {e}"));
+            }
+        });
+    }
+
+    fn set_soundscape(&self, soundscape: Option<Soundscape>) {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(source) = inner.soundscape.take() {
+            let _ = scheduled(&source).stop_with_when(0.0);
+            let _ = source.disconnect();
+        }
+        let Some(soundscape) = soundscape else {
+            return;
+        };
+        match inner.start_soundscape(&soundscape) {
+            Ok(source) => inner.soundscape = Some(source),
+            Err(e) => inner.status.error = Some(describe(e)),
+        }
+    }
+
+    fn chime(&self) {
+        self.with(|inner| {
+            if let Err(e) = inner.chime() {
+                inner.status.error = Some(describe(e));
+            }
+        });
+    }
+}
+
+impl Inner {
+    /// Brings everything up to date with the audio clock: promotes the next
+    /// track once it has started, starts whatever should be sounding but
+    /// isn't, and loads and schedules what comes after.
+    fn refresh(&mut self) {
+        let now = self.context.current_time();
+        self.status.locked = self.context.state() != AudioContextState::Running;
+        if !self.status.locked {
+            self.stop_listening();
+        }
+        if self.upcoming.as_ref().is_some_and(|voice| now >= voice.origin) {
+            let fading = std::mem::replace(&mut self.current, self.upcoming.take());
+            if let Some(voice) = std::mem::replace(&mut self.fading, fading) {
+                voice.stop();
+            }
+            self.status.current = self.current.as_ref().map(|voice| voice.index);
+        }
+        if let Some(voice) = &self.current {
+            if now >= voice.end {
+                let next = voice.index + 1;
+                self.go_to(next);
+            }
+        }
+        if self.status.playing && self.current.is_none() {
+            self.start_current(now);
+        }
+        if let Err(e) = self.schedule_upcoming() {
+            self.status.error = Some(describe(e));
+        }
+
+        let keep: Vec<TrackId> = self.status.current.into_iter()
+            .flat_map(|current| current..current + 2)
+            .filter_map(|index| self.status.queue.get(index).map(|track| track.id))
+            .collect();
+        self.buffers.retain(|id, _| keep.contains(id));
+        for id in keep {
+            self.load(id);
+        }
+        self.status.position = Duration::from_secs_f64(self.position());
+        self.status.duration = self.status.current
+            .and_then(|current| self.status.queue[current].duration);
+    }
+
+    /// Makes `index` the current track, from its start. Past the end of the
+    /// queue, playback stops.
+    fn go_to(&mut self, index: usize) {
+        self.stop_voices();
+        self.offset = 0.0;
+        if index < self.status.queue.len() {
+            self.status.current = Some(index);
+        } else {
+            self.status.current = None;
+            self.status.playing = false;
+        }
+    }
+
+    /// Starts the current track from `offset`, once it's loaded. Tracks that
+    /// failed to load are skipped.
+    fn start_current(&mut self, now: f64) {
+        while let Some(index) = self.status.current {
+            let id = self.status.queue[index].id;
+            if !self.failed.contains(&id) {
+                break;
+            }
+            self.go_to(index + 1);
+        }
+        let Some(index) = self.status.current else {
+            return;
+        };
+        if !self.buffers.contains_key(&self.status.queue[index].id) {
+            return;
+        }
+        match self.voice(index, now, self.offset) {
+            Ok(voice) => self.current = Some(voice),
+            Err(e) => {
+                self.status.error = Some(describe(e));
+                self.status.playing = false;
+            }
+        }
+    }
+
+    /// Schedules the track after the current one to start as it ends,
+    /// overlapping by the crossfade.
+    fn schedule_upcoming(&mut self) -> Result<(), JsValue> {
+        let Some(current) = &self.current else {
+            return Ok(());
+        };
+        if self.upcoming.is_some() {
+            return Ok(());
+        }
+        let index = current.index + 1;
+        let Some(buffer) = self.status.queue.get(index).and_then(|track| self.buffers.get(&track.id)) else {
+            return Ok(());
+        };
+        let now = self.context.current_time();
+        let crossfade = self.status.crossfade.unwrap_or_default().as_secs_f64();
+        let fade = crossfade.min(current.end - now).min(buffer.duration() / 2.0).max(0.0);
+        let start = current.end - fade;
+        let voice = self.voice(index, start, 0.0)?;
+        if fade > 0.0 {
+            voice.gain.gain().set_value_at_time(0.0, start)?;
+            voice.gain.gain().linear_ramp_to_value_at_time(1.0, current.end)?;
+            current.gain.gain().set_value_at_time(1.0, start)?;
+            current.gain.gain().linear_ramp_to_value_at_time(0.0, current.end)?;
+        }
+        self.upcoming = Some(voice);
+        Ok(())
+    }
+
+    fn voice(&self, index: usize, when: f64, offset: f64) -> Result<Voice, JsValue> {
+        let buffer = &self.buffers[&self.status.queue[index].id];
+        let source = self.context.create_buffer_source()?;
+        source.set_buffer(Some(buffer));
+        let gain = self.context.create_gain()?;
+        source.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&self.music)?;
+        // Keeps the queue moving in a background tab, where egui stops
+        // drawing and so stops asking for the status.
+        let this = self.this.clone();
+        let ended = Closure::<dyn FnMut()>::new(move || {
+            if let Some(inner) = this.upgrade() {
+                inner.borrow_mut().refresh();
+            }
+        });
+        scheduled(&source).set_onended(Some(ended.as_ref().unchecked_ref()));
+        source.start_with_when_and_grain_offset(when, offset)?;
+        Ok(Voice {
+            index,
+            source,
+            gain,
+            origin: when - offset,
+            end: when - offset + buffer.duration(),
+            _ended: ended,
+        })
+    }
+
+    /// Rings over whatever is playing, since the tracks change around it
+    /// anyway.
+    fn chime(&self) -> Result<(), JsValue> {
+        let now = self.context.current_time();
+        let gain = self.context.create_gain()?;
+        gain.connect_with_audio_node(&self.master)?;
+        gain.gain().set_value_at_time(0.0, now)?;
+        gain.gain().linear_ramp_to_value_at_time(CHIME_LEVEL / CHIME_PARTIALS.len() as f32, now + 0.005)?;
+        gain.gain().exponential_ramp_to_value_at_time(0.0001, now + CHIME_SECONDS)?;
+        for frequency in CHIME_PARTIALS {
+            let bell = self.context.create_oscillator()?;
+            bell.frequency().set_value(frequency);
+            bell.connect_with_audio_node(&gain)?;
+            bell.start()?;
+            bell.stop_with_when(now + CHIME_SECONDS)?;
+        }
+        Ok(())
+    }
+
+    /// Web Audio can't run a generator without a worklet, so a stretch is
+    /// rendered up front and looped; it's made to join up with itself.
+    fn start_soundscape(&self, soundscape: &Soundscape) -> Result<AudioBufferSourceNode, JsValue> {
+        let rate = self.context.sample_rate();
+        let samples = soundscape.render_loop(rate as u32, SOUNDSCAPE_LOOP_SECONDS);
+        let channels = Soundscape::CHANNELS as usize;
+        let buffer = self.context.create_buffer(channels as u32, (samples.len() / channels) as u32, rate)?;
+        for channel in 0..channels {
+            let data: Vec<f32> = samples.iter().skip(channel).step_by(channels).copied().collect();
+            buffer.copy_to_channel(&data, channel as i32)?;
+        }
+        let source = self.context.create_buffer_source()?;
+        source.set_buffer(Some(&buffer));
+        source.set_loop(true);
+        source.connect_with_audio_node(&self.master)?;
+        source.start()?;
+        Ok(source)
+    }
+
+    fn cancel_upcoming(&mut self) {
+        if let Some(voice) = self.upcoming.take() {
+            voice.stop();
+        }
+        if let Some(voice) = &self.current {
+            let gain = voice.gain.gain();
+            let _ = gain.cancel_scheduled_values(0.0);
+            gain.set_value(1.0);
+        }
+    }
+
+    fn stop_voices(&mut self) {
+        for voice in [self.current.take(), self.upcoming.take(), self.fading.take()].into_iter().flatten() {
+            voice.stop();
+        }
+    }
+
+    /// Seconds into the current track.
+    fn position(&self) -> f64 {
+        match &self.current {
+            Some(voice) => (self.context.current_time() - voice.origin).clamp(0.0, voice.end - voice.origin),
+            None => self.offset,
+        }
+    }
+
+    /// Fetches and decodes a track in the background, then refreshes so it
+    /// can start or be scheduled.
+    fn load(&mut self, id: TrackId) {
+        if self.buffers.contains_key(&id) || self.failed.contains(&id) || self.loading.contains(&id) {
+            return;
+        }
+        let Some(track) = self.status.queue.iter().find(|track| track.id == id) else {
+            return;
+        };
+        self.loading.insert(id);
+        let (url, context, this) = (track.source.clone(), self.context.clone(), self.this.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            let decoded = decode(&context, &url).await;
+            let Some(inner) = this.upgrade() else {
+                return;
+            };
+            let mut inner = inner.borrow_mut();
+            inner.loading.remove(&id);
+            match decoded {
+                Ok(buffer) => {
+                    let duration = Duration::from_secs_f64(buffer.duration());
+                    if let Some(track) = inner.status.queue.iter_mut().find(|track| track.id == id) {
+                        track.duration = Some(duration);
+                    }
+                    inner.buffers.insert(id, buffer);
+                }
+                Err(e) => {
+                    inner.failed.insert(id);
+                    inner.status.error = Some(format!("{url}: {e}"));
+                }
+            }
+            inner.refresh();
+        });
+    }
+
+    fn listen_for_gesture(&mut self) -> Result<(), JsValue> {
+        let context = self.context.clone();
+        let unlock = Closure::<dyn FnMut()>::new(move || {
+            let _ = context.resume();
+        });
+        let window = web_sys::window().ok_or("no window")?;
+        for event in GESTURES {
+            // Capturing, since egui stops some events reaching the document.
+            window.add_event_listener_with_callback_and_bool(event, unlock.as_ref().unchecked_ref(), true)?;
+        }
+        self.unlock = Some(unlock);
+        Ok(())
+    }
+
+    fn stop_listening(&mut self) {
+        let (Some(unlock), Some(window)) = (self.unlock.take(), web_sys::window()) else {
+            return;
+        };
+        for event in GESTURES {
+            let _ = window.remove_event_listener_with_callback_and_bool(event, unlock.as_ref().unchecked_ref(), true);
+        }
+    }
+}
+
+impl Voice {
+    fn stop(self) {
+        // Cleared first: the closure is dropped with the voice, and an `ended`
+        // event may already be queued.
+        scheduled(&self.source).set_onended(None);
+        let _ = scheduled(&self.source).stop_with_when(0.0);
+        let _ = self.gain.disconnect();
+    }
+}
+
+async fn decode(context: &AudioContext, url: &str) -> Result<AudioBuffer, String> {
+    let response = ehttp::fetch_async(ehttp::Request::get(url)).await?;
+    if !response.ok {
+        return Err(format!("{} {}", response.status, response.status_text));
+    }
+    let bytes = js_sys::Uint8Array::from(response.bytes.as_slice());
+    let decoding = context.decode_audio_data(&bytes.buffer()).map_err(describe)?;
+    let buffer = JsFuture::from(decoding).await.map_err(describe)?;
+    Ok(buffer.unchecked_into())
+}
+
+fn play_once(context: &AudioContext, destination: &GainNode, buffer: &AudioBuffer) -> Result<(), JsValue> {
+    let source = context.create_buffer_source()?;
+    source.set_buffer(Some(buffer));
+    source.connect_with_audio_node(destination)?;
+    source.start()
+}
+
+/// `stop` and `onended` on the base interface; the copies on
+/// `AudioBufferSourceNode` are deprecated in `web-sys`.
+fn scheduled(source: &AudioBufferSourceNode) -> &AudioScheduledSourceNode {
+    source.unchecked_ref()
+}
+
+fn describe(error: JsValue) -> String {
+    error.as_string().unwrap_or_else(|| format!("{error:?}"))
+}
+
+
+/// Long enough that the loop isn't noticed: rain drops and gusts repeating
+/// every few seconds would be.
+const SOUNDSCAPE_LOOP_SECONDS: f32 = 45.0;
+
+/// Events browsers count as the user asking for sound.
+const GESTURES: [&str; 4] = ["click", "keydown", "pointerup", "touchend"];
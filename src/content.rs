@@ -0,0 +1,502 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::i18n;
+
+
+/// Everything the site says, loaded from `assets/content.ron`, or from
+/// `assets/content.<language>.ron` in the other interface languages.
+#[derive(Deserialize)]
+pub struct Content {
+    pub home: HomePage,
+    pub about: AboutPage,
+    pub timeline: TimelinePage,
+    pub deliverables: DeliverablesPage,
+    pub contact: ContactPage,
+}
+
+#[derive(Deserialize)]
+pub struct HomePage {
+    pub title: String,
+    pub tagline: String,
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct AboutPage {
+    pub title: String,
+    pub team: Vec<TeamMember>,
+}
+
+#[derive(Deserialize)]
+pub struct TeamMember {
+    pub name: String,
+    pub role: String,
+    pub bio: String,
+    pub responsibilities: Vec<String>,
+    /// Path of a portrait under `assets/`. Members without one get their
+    /// initials instead.
+    #[serde(default)]
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+}
+
+#[derive(Deserialize)]
+pub struct Link {
+    pub label: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+pub struct TimelinePage {
+    pub title: String,
+    pub hint: String,
+    pub milestones: Vec<Milestone>,
+}
+
+#[derive(Deserialize)]
+pub struct Milestone {
+    pub date: Date,
+    pub title: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeliverablesPage {
+    pub title: String,
+    pub items: Vec<Deliverable>,
+    pub outro_heading: String,
+    pub outro: String,
+    pub outro_link: String,
+}
+
+#[derive(Deserialize)]
+pub struct Deliverable {
+    pub title: String,
+    pub status: Status,
+    /// When it was finished, or when it's due if it isn't yet.
+    pub date: Date,
+    pub description: String,
+    /// Path of the PDF under `assets/`, checked at build time.
+    #[serde(default)]
+    pub asset: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+pub enum Status {
+    Planned,
+    InProgress,
+    Complete,
+}
+
+impl Status {
+    pub fn label(self) -> String {
+        match self {
+            Status::Planned => i18n::tr("status-planned"),
+            Status::InProgress => i18n::tr("status-in-progress"),
+            Status::Complete => i18n::tr("status-complete"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ContactPage {
+    pub title: String,
+    pub heading: String,
+    pub link_text: String,
+    pub url: String,
+    pub blurb: String,
+}
+
+/// A calendar date, written `YYYY-MM-DD` in the content file.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// In the interface language.
+    pub fn month_name(self) -> String {
+        i18n::tr(&format!("month-{}", self.month))
+    }
+
+    /// `9/10/24`
+    pub fn short(self) -> String {
+        format!("{}/{}/{:02}", self.month, self.day, self.year % 100)
+    }
+
+    /// Today in UTC.
+    pub fn today() -> Self {
+        let since_epoch = web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::from_days((since_epoch.as_secs() / 86_400) as i64)
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days(self) -> i64 {
+        let (month, day) = (self.month as i64, self.day as i64);
+        let year = self.year as i64 - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self { year: year as i32, month: month as u32, day: day as u32 }
+    }
+
+    pub fn first_of_month(self) -> Self {
+        Self { day: 1, ..self }
+    }
+
+    pub fn next_month(self) -> Self {
+        if self.month == 12 {
+            Self { year: self.year + 1, month: 1, day: 1 }
+        } else {
+            Self { month: self.month + 1, day: 1, ..self }
+        }
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("{text:?} is not a valid YYYY-MM-DD date");
+        let mut parts = text.splitn(3, '-').map(str::parse::<u32>);
+        let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days_in_month = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return Err(invalid()),
+        };
+        if day == 0 || day > days_in_month {
+            return Err(invalid());
+        }
+        Ok(Self { year: year as i32, month, day })
+    }
+}
+
+/// `10/25/2024`
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.month, self.day, self.year)
+    }
+}
+
+/// What was wrong with a content file, and which one: a path under
+/// `assets/` or the URL it was fetched from.
+pub enum ContentError {
+    Parse { source: String, error: ron::error::SpannedError },
+    Invalid { source: String, problems: Vec<String> },
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContentError::Parse { source, error } => {
+                write!(f, "{source}:{}:{}: {}", error.position.line, error.position.col, error.code)
+            }
+            ContentError::Invalid { source, problems } => {
+                write!(f, "{source} is invalid:")?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Content {
+    /// In the interface language, or English if it has no content file.
+    pub fn bundled() -> Self {
+        let (language, text) = BUNDLED.iter().find(|(id, _)| *id == i18n::current()).unwrap_or(&BUNDLED[0]);
+        match Self::parse(&path(language), text) {
+            Ok(content) => content,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// `source` names the file or URL `text` came from, for the error.
+    pub fn parse(source: &str, text: &str) -> Result<Self, ContentError> {
+        let content: Content = ron::from_str(text)
+            .map_err(|error| ContentError::Parse { source: source.to_owned(), error })?;
+        let problems = content.validate();
+        if problems.is_empty() {
+            Ok(content)
+        } else {
+            Err(ContentError::Invalid { source: source.to_owned(), problems })
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut require = |field: &str, value: &str| {
+            if value.trim().is_empty() {
+                problems.push(format!("{field} is empty"));
+            }
+        };
+
+        require("home.title", &self.home.title);
+        require("home.tagline", &self.home.tagline);
+        require("about.title", &self.about.title);
+        for (i, member) in self.about.team.iter().enumerate() {
+            require(&format!("about.team[{i}].name"), &member.name);
+            require(&format!("about.team[{i}].role"), &member.role);
+        }
+        require("timeline.title", &self.timeline.title);
+        for (i, milestone) in self.timeline.milestones.iter().enumerate() {
+            require(&format!("timeline.milestones[{i}].title"), &milestone.title);
+        }
+        require("deliverables.title", &self.deliverables.title);
+        for (i, item) in self.deliverables.items.iter().enumerate() {
+            require(&format!("deliverables.items[{i}].title"), &item.title);
+        }
+        require("contact.title", &self.contact.title);
+
+        let mut urls = vec![("contact.url".to_owned(), &self.contact.url)];
+        for (i, member) in self.about.team.iter().enumerate() {
+            for (j, link) in member.links.iter().enumerate() {
+                urls.push((format!("about.team[{i}].links[{j}].url"), &link.url));
+            }
+        }
+        for (field, url) in urls {
+            if !url.starts_with("https://") {
+                problems.push(format!("{field} must be an https:// link, got {url:?}"));
+            }
+        }
+
+        for (i, item) in self.deliverables.items.iter().enumerate() {
+            if let Some(asset) = &item.asset {
+                if !asset.starts_with("assets/") {
+                    problems.push(format!("deliverables.items[{i}].asset must be under assets/, got {asset:?}"));
+                }
+            }
+        }
+        if self.about.team.is_empty() {
+            problems.push("about.team has no members".to_owned());
+        }
+        if !self.timeline.milestones.is_sorted_by_key(|milestone| milestone.date) {
+            problems.push("timeline.milestones must be in date order".to_owned());
+        }
+        problems
+    }
+}
+
+/// Where the content in `language` is kept, under the app or the site.
+pub fn path(language: &str) -> String {
+    match language {
+        "en-US" => "assets/content.ron".to_owned(),
+        other => format!("assets/content.{other}.ron"),
+    }
+}
+
+/// Fetches a newer copy of the content from the hosting site so copy edits go
+/// live without a rebuild. The result is handed back through the channel and
+/// only replaces the bundled content if it validates.
+pub fn fetch_override(ctx: &eframe::egui::Context, url: &str) -> std::sync::mpsc::Receiver<Content> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let ctx = ctx.clone();
+    let source = url.to_owned();
+    ehttp::fetch(ehttp::Request::get(url), move |response| {
+        let text = match response {
+            Ok(response) if response.ok => response.text().map(str::to_owned),
+            Ok(response) => {
+                warn(&format!("{source}: {} {}", response.status, response.status_text));
+                return;
+            }
+            Err(e) => {
+                warn(&format!("{source}: {e}"));
+                return;
+            }
+        };
+        match Content::parse(&source, &text.unwrap_or_default()) {
+            Ok(content) => {
+                let _ = sender.send(content);
+                ctx.request_repaint();
+            }
+            Err(e) => warn(&e.to_string()),
+        }
+    });
+    receiver
+}
+
+/// Turns a path under `assets/` into something `egui::Image` or a browser can
+/// load: a URL next to the page on the web, and on native a `file://` URL
+/// made absolute against the working directory, since other programs opening
+/// it don't share that.
+#[cfg(target_arch = "wasm32")]
+pub fn asset_uri(path: &str) -> String {
+    let base = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.base_uri().ok().flatten())
+        .unwrap_or_default();
+    format!("{}/{}", base.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn asset_uri(path: &str) -> String {
+    let absolute = std::env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.into());
+    let absolute = absolute.to_string_lossy().replace('\\', "/");
+    // A Windows path starts with its drive letter rather than a slash.
+    if absolute.starts_with('/') {
+        format!("file://{absolute}")
+    } else {
+        format!("file:///{absolute}")
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn warn(message: &str) {
+    web_sys::console::warn_1(&message.into());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn warn(message: &str) {
+    eprintln!("{message}");
+}
+
+
+/// A content file for each catalog in `assets/i18n`, English first.
+const BUNDLED: [(&str, &str); 2] = [
+    ("en-US", include_str!("../assets/content.ron")),
+    ("es", include_str!("../assets/content.es.ron")),
+];
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(language: &str, text: &str) -> Content {
+        Content::parse(&path(language), text).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    #[test]
+    fn every_language_has_content() {
+        for (id, _) in i18n::languages() {
+            assert!(BUNDLED.iter().any(|(language, _)| *language == id), "no {} for {id}", path(id));
+        }
+    }
+
+    #[test]
+    fn every_content_file_parses() {
+        for (language, text) in BUNDLED {
+            parsed(language, text);
+        }
+    }
+
+    fn english() -> Content {
+        parsed(BUNDLED[0].0, BUNDLED[0].1)
+    }
+
+    #[test]
+    fn errors_name_their_source() {
+        let url = "https://example.com/content.ron";
+        let Err(e) = Content::parse(url, "(home:") else { panic!("parsed a broken file") };
+        assert!(e.to_string().starts_with(&format!("{url}:1:")), "{e}");
+    }
+
+    #[test]
+    fn validate_rejects_empty_fields() {
+        let mut content = english();
+        content.home.title = " ".to_owned();
+        content.about.team[0].role.clear();
+        let problems = content.validate();
+        assert!(problems.contains(&"home.title is empty".to_owned()), "{problems:?}");
+        assert!(problems.contains(&"about.team[0].role is empty".to_owned()), "{problems:?}");
+    }
+
+    #[test]
+    fn validate_rejects_links_that_are_not_https() {
+        let mut content = english();
+        content.contact.url = "http://example.com".to_owned();
+        let problems = content.validate();
+        assert_eq!(problems, ["contact.url must be an https:// link, got \"http://example.com\""]);
+    }
+
+    #[test]
+    fn validate_rejects_unsorted_milestones() {
+        let mut content = english();
+        assert!(content.validate().is_empty());
+        content.timeline.milestones.reverse();
+        assert_eq!(content.validate(), ["timeline.milestones must be in date order"]);
+    }
+
+    #[test]
+    fn days_round_trip() {
+        assert_eq!(Date { year: 1970, month: 1, day: 1 }.days(), 0);
+        assert_eq!(Date { year: 1969, month: 12, day: 31 }.days(), -1);
+        assert_eq!(Date::from_days(19_782), Date { year: 2024, month: 2, day: 29 });
+        for days in (-1_000_000..1_000_000).step_by(37) {
+            let date = Date::from_days(days);
+            assert_eq!(date.days(), days, "{date:?}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn native_assets_are_absolute_urls() {
+        let uri = asset_uri("assets/wbs.pdf");
+        let path = uri.strip_prefix("file://").unwrap();
+        assert!(path.starts_with('/'), "{uri}");
+        assert!(std::path::Path::new(path).is_file(), "{uri}");
+    }
+
+    /// Translations change the words, never the dates, files or links.
+    #[test]
+    fn translations_only_change_the_words() {
+        let english = parsed(BUNDLED[0].0, BUNDLED[0].1);
+        for (language, text) in &BUNDLED[1..] {
+            let file = path(language);
+            let content = parsed(language, text);
+
+            let team = |content: &Content| -> Vec<_> {
+                content.about.team.iter()
+                    .map(|member| (
+                        member.name.clone(),
+                        member.avatar.clone(),
+                        member.links.iter().map(|link| link.url.clone()).collect::<Vec<_>>(),
+                    ))
+                    .collect()
+            };
+            assert_eq!(team(&content), team(&english), "{file}: about.team");
+
+            let dates = |content: &Content| -> Vec<_> {
+                content.timeline.milestones.iter().map(|milestone| milestone.date).collect()
+            };
+            assert_eq!(dates(&content), dates(&english), "{file}: timeline.milestones");
+
+            let items = |content: &Content| -> Vec<_> {
+                content.deliverables.items.iter()
+                    .map(|item| (item.status, item.date, item.asset.clone()))
+                    .collect()
+            };
+            assert_eq!(items(&content), items(&english), "{file}: deliverables.items");
+
+            assert_eq!(content.contact.url, english.contact.url, "{file}: contact.url");
+        }
+    }
+}
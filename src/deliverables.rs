@@ -0,0 +1,41 @@
+use eframe::egui;
+
+use crate::content::{self, Deliverable, Status};
+use crate::{i18n, icons};
+use crate::pdf::{self, PdfViewer};
+
+
+pub fn show_deliverables(ui: &mut egui::Ui, items: &[Deliverable], viewer: &mut Option<PdfViewer>) {
+    for item in items {
+        ui.add_space(29.0);
+        ui.horizontal(|ui| {
+            ui.heading(&item.title);
+            if item.status != Status::Complete {
+                ui.weak(format!("({})", item.status.label()));
+            }
+        });
+        ui.separator();
+        match &item.asset {
+            Some(asset) => {
+                ui.horizontal(|ui| {
+                    // Without a renderer the PDF is only opened elsewhere.
+                    if pdf::available() && ui.link(format!("{} {}", icons::EYE, i18n::tr("deliverable-preview"))).clicked() {
+                        *viewer = Some(PdfViewer::open(ui.ctx(), &item.title, asset));
+                    }
+                    if ui.link(format!("{} {}", i18n::tr("deliverable-view-pdf"), icons::ARROW_SQUARE_OUT)).clicked() {
+                        ui.ctx().open_url(egui::OpenUrl::new_tab(content::asset_uri(asset)));
+                    }
+                });
+            }
+            None => {
+                ui.weak(i18n::tr("deliverable-unpublished"));
+            }
+        }
+        ui.horizontal(|ui| {
+            let key = if item.status == Status::Complete { "deliverable-completed" } else { "deliverable-due" };
+            ui.strong(i18n::tr(key));
+            ui.label(item.date.to_string());
+        });
+        ui.label(&item.description);
+    }
+}
@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use eframe::egui;
+use plistr_core::{Clock, FocusSettings, FocusTimer, Phase, Session, SessionId, SessionKind, Soundscape, Track, UserId};
+
+use crate::audio::Playback;
+use crate::{a11y, history, i18n, icons, soundscape, typography};
+
+
+/// The Pomodoro timer and the music for each side of it. When a phase ends
+/// the player chimes and, if music was picked for the new phase, swaps its
+/// queue over.
+pub struct Focus {
+    timer: FocusTimer<SystemClock>,
+    focus_music: Vec<Track>,
+    break_music: Vec<Track>,
+    soundscape: soundscape::Mixer,
+    /// When the last focus block that ran to the end started, and how long
+    /// it was.
+    finished: Option<(u64, Duration)>,
+}
+
+/// Seconds since the app started. `web_time`, since `std::time::Instant`
+/// panics in the browser.
+pub struct SystemClock(web_time::Instant);
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self(web_time::Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+impl Focus {
+    pub fn new(settings: FocusSettings, soundscape: Soundscape) -> Self {
+        Self {
+            timer: FocusTimer::new(settings, SystemClock::new()),
+            focus_music: Vec::new(),
+            break_music: Vec::new(),
+            soundscape: soundscape::Mixer::new(soundscape),
+            finished: None,
+        }
+    }
+
+    pub fn settings(&self) -> FocusSettings {
+        self.timer.settings()
+    }
+
+    pub fn soundscape(&self) -> Soundscape {
+        self.soundscape.soundscape()
+    }
+
+    /// The last focus block that wasn't skipped, as a session `user` can
+    /// share.
+    pub fn last_finished(&self, user: UserId) -> Option<Session> {
+        let (started_at, length) = self.finished?;
+        let mut session = Session::new(SessionId(started_at), user, SessionKind::Study, started_at, length);
+        session.record(length);
+        Some(session)
+    }
+
+    /// Call every frame, whichever page is showing, so phases change on
+    /// time.
+    pub fn update(&mut self, ctx: &egui::Context, player: Option<&dyn Playback>) {
+        // More than one change means the app wasn't drawn for a while; only
+        // the phase it lands in gets music.
+        let mut changed = None;
+        for _ in 0..MAX_CATCH_UP {
+            match self.timer.poll() {
+                Some(phase) => {
+                    if phase.is_break() {
+                        let length = self.timer.settings().focus;
+                        self.finished = Some((history::now().saturating_sub(length.as_secs()), length));
+                    }
+                    changed = Some(phase);
+                }
+                None => break,
+            }
+        }
+        if let (Some(phase), Some(player)) = (changed, player) {
+            self.change_music(phase, player);
+        }
+        if self.timer.is_running() {
+            // Wakes up as the displayed second ticks over.
+            let remaining = self.timer.remaining();
+            ctx.request_repaint_after(Duration::from_nanos(remaining.subsec_nanos().into()));
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(phase_name(self.timer.phase())).text_style(typography::title()));
+            ui.label(egui::RichText::new(countdown(self.timer.remaining())).text_style(typography::display()));
+            ui.add(egui::ProgressBar::new(self.timer.progress()).desired_width(311.0));
+            ui.add_space(11.0);
+            ui.horizontal(|ui| {
+                // Centres the row of buttons under the clock.
+                ui.add_space((ui.available_width() - 239.0).max(0.0) / 2.0);
+                self.show_controls(ui, player);
+            });
+            let completed = self.timer.completed();
+            ui.weak(i18n::tr_with("focus-completed", &[("count", completed.into())]));
+        });
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("focus-lengths"));
+        ui.separator();
+        self.show_settings(ui);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("focus-music"));
+        ui.separator();
+        ui.weak(i18n::tr("focus-music-hint"));
+        let queue = player.map(|player| player.status().queue).unwrap_or_default();
+        show_music(ui, &i18n::tr("focus-music-focus"), &mut self.focus_music, &queue);
+        show_music(ui, &i18n::tr("focus-music-break"), &mut self.break_music, &queue);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("soundscape"));
+        ui.separator();
+        self.soundscape.show(ui, player);
+    }
+
+    fn show_controls(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        let running = self.timer.is_running();
+        let (icon, key) = if running { (icons::PAUSE, "focus-pause") } else { (icons::PLAY, "focus-start") };
+        if ui.button(format!("{icon} {}", i18n::tr(key))).clicked() {
+            if running {
+                self.timer.pause();
+                if let Some(player) = player {
+                    player.pause();
+                }
+            } else {
+                let fresh = self.timer.elapsed().is_zero();
+                self.timer.resume();
+                match player {
+                    Some(player) if fresh => self.start_music(self.timer.phase(), player),
+                    Some(player) => player.play(),
+                    None => {}
+                }
+            }
+        }
+        let skip = ui.button(icons::SKIP_FORWARD);
+        if a11y::label(skip, &i18n::tr("focus-skip")).clicked() {
+            let phase = self.timer.skip();
+            if let Some(player) = player {
+                self.change_music(phase, player);
+            }
+        }
+        if ui.button(i18n::tr("focus-reset")).clicked() {
+            self.timer.reset();
+        }
+    }
+
+    fn show_settings(&mut self, ui: &mut egui::Ui) {
+        let mut settings = self.timer.settings();
+        let minutes = i18n::tr("focus-minutes");
+        egui::Grid::new("focus lengths").num_columns(2).spacing([19.0, 7.0]).show(ui, |ui| {
+            for (key, length, range) in [
+                ("focus-length-focus", &mut settings.focus, 5..=90),
+                ("focus-length-short-break", &mut settings.short_break, 1..=30),
+                ("focus-length-long-break", &mut settings.long_break, 5..=60),
+            ] {
+                ui.label(i18n::tr(key));
+                let mut value = (length.as_secs() / 60) as u32;
+                let slider = egui::Slider::new(&mut value, range).suffix(format!(" {minutes}"));
+                if ui.add(slider).changed() {
+                    *length = Duration::from_secs(u64::from(value) * 60);
+                }
+                ui.end_row();
+            }
+            ui.label(i18n::tr("focus-long-break-every"));
+            ui.add(egui::Slider::new(&mut settings.long_break_every, 2..=8));
+            ui.end_row();
+        });
+        if settings != self.timer.settings() {
+            self.timer.set_settings(settings);
+        }
+    }
+
+    /// A new phase: chime, then the phase's music if it has any. Without,
+    /// whatever was playing carries on.
+    fn change_music(&self, phase: Phase, player: &dyn Playback) {
+        player.chime();
+        self.start_music(phase, player);
+    }
+
+    fn start_music(&self, phase: Phase, player: &dyn Playback) {
+        let music = if phase.is_break() { &self.break_music } else { &self.focus_music };
+        if music.is_empty() {
+            return;
+        }
+        player.clear();
+        for track in music {
+            player.enqueue(track.clone());
+        }
+        player.play();
+    }
+}
+
+fn show_music(ui: &mut egui::Ui, label: &str, music: &mut Vec<Track>, queue: &[Track]) {
+    ui.horizontal_wrapped(|ui| {
+        ui.strong(label);
+        if music.is_empty() {
+            ui.weak(i18n::tr("focus-music-none"));
+        } else {
+            let titles: Vec<&str> = music.iter().map(|track| track.title.as_str()).collect();
+            ui.label(i18n::tr_with("focus-music-tracks", &[("count", music.len().into())]))
+                .on_hover_text(titles.join("\n"));
+        }
+        if ui.add_enabled(!queue.is_empty(), egui::Button::new(i18n::tr("focus-use-queue"))).clicked() {
+            *music = queue.to_vec();
+        }
+        if !music.is_empty() && ui.button(i18n::tr("focus-music-clear")).clicked() {
+            music.clear();
+        }
+    });
+}
+
+pub fn phase_name(phase: Phase) -> String {
+    i18n::tr(match phase {
+        Phase::Focus => "focus-phase-focus",
+        Phase::ShortBreak => "focus-phase-short-break",
+        Phase::LongBreak => "focus-phase-long-break",
+    })
+}
+
+/// Rounded up, so it shows the full length at the start and 0:00 only as
+/// the phase ends.
+pub fn countdown(remaining: Duration) -> String {
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+
+/// Phase changes handled in one frame after the app has been asleep.
+const MAX_CATCH_UP: usize = 16;
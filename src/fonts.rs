@@ -0,0 +1,87 @@
+use std::sync::mpsc;
+
+use eframe::egui::{self, FontData, FontDefinitions, FontFamily};
+
+
+/// The family display, title and heading text is set in. Until the brand
+/// font has arrived it falls back to the same fonts as body text.
+pub fn brand() -> FontFamily {
+    FontFamily::Name(BRAND_FAMILY.into())
+}
+
+/// Registers the bundled fonts. The icon font is compiled in because the
+/// header needs it on the first frame; on the web the brand font is fetched
+/// afterwards so it doesn't hold up first paint.
+pub struct Fonts {
+    brand: Option<Vec<u8>>,
+    pending: Option<mpsc::Receiver<Vec<u8>>>,
+}
+
+impl Fonts {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(ctx: &egui::Context) -> Self {
+        let fonts = Self {
+            brand: Some(include_bytes!("../assets/fonts/DejaVuSans-ExtraLight.ttf").to_vec()),
+            pending: None,
+        };
+        fonts.install(ctx);
+        fonts
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new(ctx: &egui::Context) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let repaint = ctx.clone();
+        let url = crate::content::asset_uri(BRAND_PATH);
+        ehttp::fetch(ehttp::Request::get(&url), move |response| match response {
+            Ok(response) if response.ok => {
+                let _ = sender.send(response.bytes);
+                repaint.request_repaint();
+            }
+            Ok(response) => {
+                web_sys::console::warn_1(&format!("{url}: {} {}", response.status, response.status_text).into());
+            }
+            Err(e) => web_sys::console::warn_1(&format!("{url}: {e}").into()),
+        });
+        let fonts = Self {
+            brand: None,
+            pending: Some(receiver),
+        };
+        fonts.install(ctx);
+        fonts
+    }
+
+    /// Swaps the brand font in once it has been fetched.
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        if let Some(bytes) = self.pending.as_ref().and_then(|r| r.try_recv().ok()) {
+            self.brand = Some(bytes);
+            self.pending = None;
+            self.install(ctx);
+        }
+    }
+
+    fn install(&self, ctx: &egui::Context) {
+        let mut fonts = FontDefinitions::default();
+        fonts.font_data.insert(ICONS.to_owned(), FontData::from_static(ICON_FONT));
+        for family in [FontFamily::Proportional, FontFamily::Monospace] {
+            fonts.families.entry(family).or_default().push(ICONS.to_owned());
+        }
+
+        let mut brand_fonts = fonts.families[&FontFamily::Proportional].clone();
+        if let Some(bytes) = &self.brand {
+            fonts.font_data.insert(BRAND.to_owned(), FontData::from_owned(bytes.clone()));
+            brand_fonts.insert(0, BRAND.to_owned());
+        }
+        fonts.families.insert(brand(), brand_fonts);
+
+        ctx.set_fonts(fonts);
+    }
+}
+
+
+const BRAND_FAMILY: &str = "Brand";
+const BRAND: &str = "DejaVuSans-ExtraLight";
+#[cfg(target_arch = "wasm32")]
+const BRAND_PATH: &str = "assets/fonts/DejaVuSans-ExtraLight.ttf";
+const ICONS: &str = "Phosphor";
+const ICON_FONT: &[u8] = include_bytes!("../assets/fonts/Phosphor.ttf");
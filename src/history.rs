@@ -0,0 +1,80 @@
+//! Listening history: each track heard for long enough goes into the
+//! `Repository` as a `Play` once it stops being the current one.
+
+use std::time::Duration;
+
+use plistr_core::{Play, Repository, Track};
+
+use crate::audio::Playback;
+use crate::content;
+
+
+pub struct History {
+    repository: Box<dyn Repository>,
+    listening: Option<Listening>,
+}
+
+/// The track the player is on, and how far into it it's been.
+struct Listening {
+    track: Track,
+    /// Seconds since the Unix epoch.
+    started_at: u64,
+    heard: Duration,
+}
+
+impl History {
+    pub fn new(repository: Box<dyn Repository>) -> Self {
+        Self { repository, listening: None }
+    }
+
+    /// Call every frame, whichever page is showing, so plays are caught
+    /// when the track changes rather than when the player is next looked at.
+    pub fn update(&mut self, player: Option<&dyn Playback>) {
+        let status = player.map(|player| player.status()).unwrap_or_default();
+        let current = status.current.and_then(|index| status.queue.get(index));
+        let same = match (&self.listening, current) {
+            (Some(listening), Some(track)) => listening.track.id == track.id && listening.track.source == track.source,
+            (None, None) => true,
+            _ => false,
+        };
+        if !same {
+            self.finish();
+            self.listening = current.map(|track| Listening { track: track.clone(), started_at: now(), heard: Duration::ZERO });
+        }
+        if let Some(listening) = &mut self.listening {
+            listening.heard = listening.heard.max(status.position);
+        }
+    }
+
+    fn finish(&mut self) {
+        let Some(listening) = self.listening.take() else {
+            return;
+        };
+        if listening.heard < MIN_HEARD {
+            return;
+        }
+        let recorded = self.repository.remember_track(&listening.track).and_then(|track| {
+            self.repository.record_play(&Play { track: track.id, played_at: listening.started_at, listened: listening.heard })
+        });
+        if let Err(e) = recorded {
+            content::warn(&format!("couldn't add to the listening history: {e}"));
+        }
+    }
+}
+
+impl Drop for History {
+    /// Keeps the track that was playing when the app closed.
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Seconds since the Unix epoch, which is how plays, sessions and posts
+/// are stamped.
+pub fn now() -> u64 {
+    web_time::SystemTime::now().duration_since(web_time::UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+
+/// Less than this is a skip, not a listen.
+const MIN_HEARD: Duration = Duration::from_secs(5);
@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+
+/// Interface text in the current language, falling back to English for
+/// anything the translation hasn't covered yet.
+pub fn tr(key: &str) -> String {
+    tr_with(key, &[])
+}
+
+/// `tr` for messages with `{ $placeables }`, e.g.
+/// `tr_with("pdf-page", &[("page", 2.into()), ("count", 5.into())])`.
+pub fn tr_with(key: &str, args: &[(&str, FluentValue)]) -> String {
+    let catalogs = catalogs();
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    let current = &catalogs[CURRENT.load(Ordering::Relaxed)];
+    for catalog in [current, &catalogs[0]] {
+        let Some(pattern) = catalog.bundle.get_message(key).and_then(|message| message.value()) else {
+            continue;
+        };
+        let mut errors = Vec::new();
+        let text = catalog.bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+        if !errors.is_empty() {
+            warn_once(key, &format!("{}: {key}: {errors:?}", catalog.id));
+        }
+        return text.into_owned();
+    }
+    warn_once(key, &format!("no message named {key:?} in {}", CATALOGS[0].0));
+    key.to_owned()
+}
+
+/// Every bundled language as its id and its name in that language.
+pub fn languages() -> impl Iterator<Item = (&'static str, String)> {
+    catalogs().iter().map(|catalog| {
+        let name = catalog.bundle.get_message("language-name")
+            .and_then(|message| message.value())
+            .map_or_else(|| catalog.id.to_owned(), |pattern| {
+                catalog.bundle.format_pattern(pattern, None, &mut Vec::new()).into_owned()
+            });
+        (catalog.id, name)
+    })
+}
+
+/// The id of the language in use, like `"es"`.
+pub fn current() -> &'static str {
+    catalogs()[CURRENT.load(Ordering::Relaxed)].id
+}
+
+/// Switches to the bundled language closest to `choice`, or to the OS or
+/// browser language when there is no choice.
+pub fn select(choice: Option<&str>) {
+    let requested = choice.map(str::to_owned).or_else(system_locale).unwrap_or_default();
+    let index = negotiate(&requested);
+    CURRENT.store(index, Ordering::Relaxed);
+    set_document_language(catalogs()[index].id);
+}
+
+/// The best match for `requested` (e.g. "es-MX" picks "es"), or English.
+fn negotiate(requested: &str) -> usize {
+    // POSIX locales look like "es_MX.UTF-8".
+    let requested = requested.split('.').next().unwrap_or_default().replace('_', "-");
+    let Ok(requested) = requested.parse::<LanguageIdentifier>() else {
+        return 0;
+    };
+    let catalogs = catalogs();
+    catalogs.iter().position(|catalog| catalog.langid == requested)
+        .or_else(|| catalogs.iter().position(|catalog| catalog.langid.language == requested.language))
+        .unwrap_or(0)
+}
+
+struct Catalog {
+    id: &'static str,
+    langid: LanguageIdentifier,
+    bundle: FluentBundle<FluentResource>,
+}
+
+fn catalogs() -> &'static [Catalog] {
+    static LOADED: OnceLock<Vec<Catalog>> = OnceLock::new();
+    LOADED.get_or_init(|| {
+        CATALOGS.iter().map(|&(id, source)| {
+            let langid: LanguageIdentifier = id.parse().unwrap_or_else(|e| panic!("{id}: {e}"));
+            let resource = FluentResource::try_new(source.to_owned())
+                .unwrap_or_else(|(_, errors)| panic!("{id}.ftl: {errors:?}"));
+            let mut bundle = FluentBundle::new_concurrent(vec![langid.clone()]);
+            // egui draws the bidi isolation marks Fluent adds around arguments.
+            bundle.set_use_isolating(false);
+            bundle.add_resource(resource).unwrap_or_else(|errors| panic!("{id}.ftl: {errors:?}"));
+            Catalog { id, langid, bundle }
+        }).collect()
+    })
+}
+
+/// Reports each broken message once rather than every frame.
+fn warn_once(key: &str, message: &str) {
+    static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+    if WARNED.lock().is_ok_and(|mut warned| warned.insert(key.to_owned())) {
+        crate::content::warn(message);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn system_locale() -> Option<String> {
+    web_sys::window()?.navigator().language()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn system_locale() -> Option<String> {
+    sys_locale::get_locale()
+}
+
+/// Keeps `<html lang>` in step so the browser's own screen reader and
+/// hyphenation use the right language.
+#[cfg(target_arch = "wasm32")]
+fn set_document_language(id: &str) {
+    let root = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.document_element());
+    if let Some(root) = root {
+        let _ = root.set_attribute("lang", id);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn set_document_language(_id: &str) {}
+
+
+/// English comes first: it is the fallback for every other catalog.
+const CATALOGS: [(&str, &str); 2] = [
+    ("en-US", include_str!("../assets/i18n/en-US.ftl")),
+    ("es", include_str!("../assets/i18n/es.ftl")),
+];
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// The ids of a Fluent file's messages: unindented `id = ...` lines.
+    /// Terms (`-id`) and comments are skipped.
+    fn message_ids(text: &str) -> BTreeSet<&str> {
+        text.lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| Some(line.split_once('=')?.0.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn every_catalog_on_disk_is_bundled() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/i18n");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "ftl") {
+                let id = path.file_stem().unwrap().to_string_lossy();
+                assert!(CATALOGS.iter().any(|(bundled, _)| *bundled == id), "{} isn't in CATALOGS", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn every_catalog_translates_every_message() {
+        let english = message_ids(CATALOGS[0].1);
+        for (id, source) in &CATALOGS[1..] {
+            let ids = message_ids(source);
+            let missing: Vec<_> = english.difference(&ids).collect();
+            assert!(missing.is_empty(), "{id}.ftl has no translation for: {missing:?}");
+            // English is the fallback, so these could never be shown.
+            let unknown: Vec<_> = ids.difference(&english).collect();
+            assert!(unknown.is_empty(), "{id}.ftl has messages that English doesn't: {unknown:?}");
+        }
+    }
+
+    #[test]
+    fn every_catalog_parses() {
+        for catalog in catalogs() {
+            let ids = message_ids(CATALOGS.iter().find(|(id, _)| *id == catalog.id).unwrap().1);
+            for id in ids {
+                assert!(catalog.bundle.has_message(id), "{}: {id} didn't parse", catalog.id);
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Glyphs from the bundled Phosphor icon font (regular weight). Use them like
+//! any other text, e.g. `ui.button(icons::SUN)` or `format!("{} Open", icons::ARROW_SQUARE_OUT)`.
+
+pub const ARROWS_OUT: &str = "\u{E968}";
+pub const ARROW_SQUARE_OUT: &str = "\u{E972}";
+pub const CARET_DOWN: &str = "\u{E9FE}";
+pub const CARET_LEFT: &str = "\u{E9FF}";
+pub const CARET_RIGHT: &str = "\u{EA00}";
+/// Only the native library table sorts.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub const CARET_UP: &str = "\u{EA01}";
+pub const CROSSHAIR: &str = "\u{EA76}";
+pub const EYE: &str = "\u{EAD2}";
+pub const LIST: &str = "\u{EBCA}";
+pub const MAGNIFYING_GLASS_MINUS: &str = "\u{EBDE}";
+pub const MAGNIFYING_GLASS_PLUS: &str = "\u{EBDF}";
+pub const MOON: &str = "\u{EBFE}";
+pub const PAUSE: &str = "\u{EC50}";
+pub const PLAY: &str = "\u{EC7E}";
+pub const SKIP_BACK: &str = "\u{ECEF}";
+pub const SKIP_FORWARD: &str = "\u{ECF1}";
+pub const SPEAKER_HIGH: &str = "\u{ED0C}";
+pub const SUN: &str = "\u{ED3E}";
+pub const TEXT_AA: &str = "\u{ED57}";
+pub const TRANSLATE: &str = "\u{ED89}";
+pub const X: &str = "\u{EDDB}";
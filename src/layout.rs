@@ -0,0 +1,64 @@
+use eframe::egui;
+
+
+/// Width classes the pages lay themselves out for. Compare with `<`/`>=` to
+/// ask "at least a tablet".
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Breakpoint {
+    Phone,
+    Tablet,
+    Desktop,
+}
+
+impl Breakpoint {
+    pub fn of_width(width: f32) -> Self {
+        if width < TABLET_WIDTH {
+            Breakpoint::Phone
+        } else if width < DESKTOP_WIDTH {
+            Breakpoint::Tablet
+        } else {
+            Breakpoint::Desktop
+        }
+    }
+
+    /// Based on the whole window rather than the current `Ui`, so every panel
+    /// of a frame agrees.
+    pub fn current(ctx: &egui::Context) -> Self {
+        Self::of_width(ctx.screen_rect().width())
+    }
+
+    /// The frame every page's content sits in.
+    pub fn page_frame(self) -> egui::Frame {
+        let margin = match self {
+            Breakpoint::Phone => egui::Margin::symmetric(11.0, 7.0),
+            Breakpoint::Tablet => egui::Margin::symmetric(29.0, 11.0),
+            Breakpoint::Desktop => egui::Margin::symmetric(59.0, 11.0),
+        };
+        egui::Frame {
+            inner_margin: margin,
+            ..Default::default()
+        }
+    }
+
+    /// Gap between items in the header and other rows of controls.
+    pub fn item_spacing(self) -> f32 {
+        match self {
+            Breakpoint::Phone => 11.0,
+            Breakpoint::Tablet => 19.0,
+            Breakpoint::Desktop => 29.0,
+        }
+    }
+
+    /// How many cards fit side by side.
+    pub fn columns(self) -> usize {
+        match self {
+            Breakpoint::Phone => 1,
+            Breakpoint::Tablet => 2,
+            Breakpoint::Desktop => 3,
+        }
+    }
+}
+
+
+const TABLET_WIDTH: f32 = 600.0;
+const DESKTOP_WIDTH: f32 = 1000.0;
@@ -0,0 +1,318 @@
+//! The user's own music: the folders to look in, and a table of everything
+//! found there to search, sort and queue from. Reading folders needs the
+//! file system, so only the desktop app has a library.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{mpsc, Arc};
+
+use eframe::egui;
+#[cfg(not(target_arch = "wasm32"))]
+use plistr_core::{Library, LibraryColumn, TrackId};
+
+use crate::audio::Playback;
+use crate::i18n;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{a11y, icons, player};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod scan;
+
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct LibraryView {
+    folders: Vec<String>,
+    /// The folder being typed, before it's added.
+    new_folder: String,
+    library: Arc<Library>,
+    store: scan::Store,
+    scan: Option<mpsc::Receiver<scan::Update>>,
+    /// Files checked and found by the running scan.
+    progress: Option<(usize, usize)>,
+    /// The folders changed during a scan, so another is needed after it.
+    stale: bool,
+    query: String,
+    sort: LibraryColumn,
+    ascending: bool,
+    /// Indices into `library` matching `query`, in `sort` order. Only
+    /// rebuilt when one of those changes, since a big library takes a
+    /// moment to sort.
+    shown: Vec<usize>,
+}
+
+/// Browsers can't look through the user's folders, so the web build only
+/// keeps the list for the next desktop session.
+#[cfg(target_arch = "wasm32")]
+pub struct LibraryView {
+    folders: Vec<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LibraryView {
+    /// Shows the saved index straight away and rescans `folders` behind it.
+    pub fn new(ctx: &egui::Context, folders: Vec<String>) -> Self {
+        let dir = eframe::storage_dir(crate::APP_NAME).unwrap_or_else(|| std::env::temp_dir().join(crate::APP_NAME));
+        let store = scan::Store::new(dir);
+        let library = Arc::new(Library::default());
+        let scan = scan::start(ctx, store.clone(), folders.clone(), library.clone(), true);
+        Self {
+            folders,
+            new_folder: String::new(),
+            library,
+            store,
+            scan: Some(scan),
+            progress: None,
+            stale: false,
+            query: String::new(),
+            sort: LibraryColumn::Artist,
+            ascending: true,
+            shown: Vec::new(),
+        }
+    }
+
+    pub fn folders(&self) -> Vec<String> {
+        self.folders.clone()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        self.poll(ui.ctx());
+        self.accept_dropped(ui.ctx());
+        ui.heading(i18n::tr("library-folders"));
+        ui.separator();
+        self.show_folders(ui);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("library-tracks"));
+        ui.separator();
+        ui.horizontal(|ui| {
+            let search = egui::TextEdit::singleline(&mut self.query).hint_text(i18n::tr("library-search"));
+            if ui.add(search).changed() {
+                self.refresh();
+            }
+            let all = egui::Button::new(i18n::tr_with("library-queue-all", &[("count", self.shown.len().into())]));
+            if ui.add_enabled(player.is_some() && !self.shown.is_empty(), all).clicked() {
+                if let Some(player) = player {
+                    let entries = self.library.entries();
+                    let tracks = self.shown.iter().map(|&i| entries[i].track(TrackId::unstored())).collect();
+                    player::enqueue(player, tracks);
+                }
+            }
+        });
+        self.show_table(ui, player);
+    }
+
+    fn show_folders(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        for (index, folder) in self.folders.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let remove = ui.add(egui::Button::new(icons::X).frame(false));
+                if a11y::label(remove, &i18n::tr("library-remove-folder")).clicked() {
+                    removed = Some(index);
+                }
+                ui.label(folder);
+            });
+        }
+        if let Some(index) = removed {
+            self.folders.remove(index);
+            self.rescan(ui.ctx());
+        }
+        ui.horizontal(|ui| {
+            let field = ui.add(egui::TextEdit::singleline(&mut self.new_folder).hint_text(i18n::tr("library-folder-hint")));
+            let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let add = egui::Button::new(i18n::tr("library-add-folder"));
+            if ui.add_enabled(!self.new_folder.trim().is_empty(), add).clicked() || entered {
+                let folder = std::mem::take(&mut self.new_folder);
+                self.add_folder(ui.ctx(), folder.trim().to_owned());
+            }
+        });
+        ui.weak(i18n::tr("library-drop-hint"));
+        ui.horizontal(|ui| {
+            let rescan = egui::Button::new(i18n::tr("library-rescan"));
+            if ui.add_enabled(self.scan.is_none() && !self.folders.is_empty(), rescan).clicked() {
+                self.rescan(ui.ctx());
+            }
+            match self.progress {
+                Some((checked, found)) => {
+                    ui.spinner();
+                    ui.weak(i18n::tr_with("library-scanning", &[("checked", checked.into()), ("found", found.into())]));
+                }
+                None if self.scan.is_some() => {
+                    ui.spinner();
+                }
+                None => {
+                    ui.weak(i18n::tr_with("library-count", &[("count", self.library.len().into())]));
+                }
+            }
+        });
+    }
+
+    /// Only the rows in view are laid out, so the frame costs the same for
+    /// ten tracks or fifty thousand.
+    fn show_table(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        use egui_extras::{Column, TableBuilder};
+
+        if self.shown.is_empty() {
+            let key = if self.library.is_empty() { "library-empty" } else { "library-no-matches" };
+            ui.weak(i18n::tr(key));
+            return;
+        }
+        let row_height = ui.spacing().interact_size.y.max(ART_SIZE);
+        let mut sort_by = None;
+        let mut queued = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .max_scroll_height(TABLE_HEIGHT)
+            .column(Column::exact(ART_SIZE))
+            .column(Column::remainder().at_least(120.0).clip(true))
+            .column(Column::initial(179.0).at_least(59.0).clip(true))
+            .column(Column::initial(179.0).at_least(59.0).clip(true))
+            .column(Column::auto().at_least(47.0))
+            .header(row_height, |mut header| {
+                header.col(|_| {});
+                for (column, key) in COLUMNS {
+                    header.col(|ui| {
+                        let sorted = self.sort == column;
+                        let arrow = match (sorted, self.ascending) {
+                            (false, _) => "",
+                            (true, true) => icons::CARET_UP,
+                            (true, false) => icons::CARET_DOWN,
+                        };
+                        if ui.selectable_label(sorted, format!("{} {arrow}", i18n::tr(key))).clicked() {
+                            sort_by = Some(column);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                let entries = self.library.entries();
+                body.rows(row_height, self.shown.len(), |mut row| {
+                    let index = self.shown[row.index()];
+                    let entry = &entries[index];
+                    row.col(|ui| {
+                        if let Some(art) = &entry.art {
+                            let uri = format!("file://{}", self.store.art_path(art).display());
+                            ui.add(egui::Image::new(uri).fit_to_exact_size(egui::vec2(ART_SIZE, ART_SIZE)));
+                        }
+                    });
+                    row.col(|ui| {
+                        let title = ui.add_enabled(player.is_some(), egui::SelectableLabel::new(false, &entry.title));
+                        if title.on_hover_text(&entry.path).clicked() {
+                            queued = Some(index);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.artist.as_deref().unwrap_or_default());
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.album.as_deref().unwrap_or_default());
+                    });
+                    row.col(|ui| {
+                        if let Some(duration) = entry.duration {
+                            ui.weak(player::clock(duration));
+                        }
+                    });
+                });
+            });
+        if let Some(column) = sort_by {
+            self.ascending = self.sort != column || !self.ascending;
+            self.sort = column;
+            self.refresh();
+        }
+        if let (Some(index), Some(player)) = (queued, player) {
+            let track = self.library.entries()[index].track(TrackId::unstored());
+            player::enqueue(player, vec![track]);
+        }
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.scan else {
+            return;
+        };
+        let updates: Vec<scan::Update> = receiver.try_iter().collect();
+        let mut finished = false;
+        for update in updates {
+            match update {
+                scan::Update::Indexed(library) => self.library = Arc::new(library),
+                scan::Update::Progress { checked, found } => {
+                    self.progress = Some((checked, found));
+                    continue;
+                }
+                scan::Update::Scanned(library) => {
+                    self.library = Arc::new(library);
+                    finished = true;
+                }
+            }
+            self.refresh();
+        }
+        if finished {
+            self.scan = None;
+            self.progress = None;
+            if std::mem::take(&mut self.stale) {
+                self.rescan(ctx);
+            }
+        }
+    }
+
+    fn rescan(&mut self, ctx: &egui::Context) {
+        if self.scan.is_some() {
+            self.stale = true;
+            return;
+        }
+        let folders = self.folders.clone();
+        self.scan = Some(scan::start(ctx, self.store.clone(), folders, self.library.clone(), false));
+    }
+
+    fn add_folder(&mut self, ctx: &egui::Context, folder: String) {
+        if folder.is_empty() || self.folders.contains(&folder) {
+            return;
+        }
+        self.folders.push(folder);
+        self.rescan(ctx);
+    }
+
+    /// Folders dropped on the page are added to the library.
+    fn accept_dropped(&mut self, ctx: &egui::Context) {
+        let folders: Vec<String> = ctx.input(|i| {
+            i.raw.dropped_files.iter()
+                .filter_map(|file| file.path.as_ref())
+                .filter(|path| path.is_dir())
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect()
+        });
+        for folder in folders {
+            self.add_folder(ctx, folder);
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.shown = self.library.search(&self.query, self.sort, self.ascending);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LibraryView {
+    pub fn new(_ctx: &egui::Context, folders: Vec<String>) -> Self {
+        Self { folders }
+    }
+
+    pub fn folders(&self) -> Vec<String> {
+        self.folders.clone()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, _player: Option<&dyn Playback>) {
+        ui.weak(i18n::tr("library-desktop-only"));
+    }
+}
+
+
+#[cfg(not(target_arch = "wasm32"))]
+const COLUMNS: [(LibraryColumn, &str); 4] = [
+    (LibraryColumn::Title, "library-title"),
+    (LibraryColumn::Artist, "library-artist"),
+    (LibraryColumn::Album, "library-album"),
+    (LibraryColumn::Duration, "library-duration"),
+];
+#[cfg(not(target_arch = "wasm32"))]
+const ART_SIZE: f32 = 23.0;
+#[cfg(not(target_arch = "wasm32"))]
+const TABLE_HEIGHT: f32 = 611.0;
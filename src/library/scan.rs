@@ -0,0 +1,321 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use eframe::egui;
+use plistr_core::{Library, LibraryEntry};
+use symphonia::core::codecs::CODEC_TYPE_NULL;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey};
+use symphonia::core::probe::Hint;
+use walkdir::WalkDir;
+
+use crate::{audio, content};
+
+
+/// What a scan on its background thread reports back.
+pub enum Update {
+    /// The index saved by the last scan, to show while this one runs.
+    Indexed(Library),
+    /// Files checked so far, out of those found.
+    Progress { checked: usize, found: usize },
+    Scanned(Library),
+}
+
+/// Where the index and the covers taken from tracks are kept.
+#[derive(Clone)]
+pub struct Store {
+    dir: PathBuf,
+}
+
+/// Tags as read from one file.
+#[derive(Default)]
+struct Tags {
+    title: Option<String>,
+    artist: Option<String>,
+    album_artist: Option<String>,
+    album: Option<String>,
+    art: Option<String>,
+}
+
+impl Store {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn art_path(&self, name: &str) -> PathBuf {
+        self.dir.join(ART_DIR).join(name)
+    }
+
+    fn load(&self) -> Option<Library> {
+        let text = std::fs::read_to_string(self.dir.join(INDEX_FILE)).ok()?;
+        ron::from_str(&text).map_err(|e| content::warn(&format!("library index: {e}"))).ok()
+    }
+
+    /// Writes to a temporary file first so a crash mid-write can't leave a
+    /// half index behind.
+    fn save(&self, library: &Library) -> Result<(), String> {
+        let text = ron::to_string(library).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let temporary = self.dir.join(format!("{INDEX_FILE}.new"));
+        std::fs::write(&temporary, text).map_err(|e| e.to_string())?;
+        std::fs::rename(&temporary, self.dir.join(INDEX_FILE)).map_err(|e| e.to_string())
+    }
+
+    /// Names covers after their contents, so an album's tracks share one
+    /// file.
+    fn save_art(&self, media_type: &str, data: &[u8]) -> Option<String> {
+        let extension = match media_type {
+            "image/jpeg" | "image/jpg" => "jpg",
+            "image/png" => "png",
+            _ => return None,
+        };
+        let name = format!("{:016x}.{extension}", fnv1a(data));
+        let path = self.art_path(&name);
+        if !path.exists() {
+            std::fs::create_dir_all(path.parent()?).ok()?;
+            std::fs::write(&path, data).ok()?;
+        }
+        Some(name)
+    }
+}
+
+/// Starts a thread that rescans `folders`, rereading only files that are
+/// new or changed since `previous`. With `load` set it first reads the saved
+/// index and uses that as `previous`.
+pub fn start(
+    ctx: &egui::Context,
+    store: Store,
+    folders: Vec<String>,
+    previous: Arc<Library>,
+    load: bool,
+) -> Receiver<Update> {
+    let (sender, receiver) = mpsc::channel();
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        let send = |update| {
+            let _ = sender.send(update);
+            ctx.request_repaint();
+        };
+        let mut previous = previous;
+        if load {
+            if let Some(library) = store.load() {
+                previous = Arc::new(library.clone());
+                send(Update::Indexed(library));
+            }
+        }
+        let library = scan(&store, &folders, &previous, &sender, &ctx);
+        if let Err(e) = store.save(&library) {
+            content::warn(&format!("library index: {e}"));
+        }
+        send(Update::Scanned(library));
+    });
+    receiver
+}
+
+fn scan(store: &Store, folders: &[String], previous: &Library, sender: &Sender<Update>, ctx: &egui::Context) -> Library {
+    let paths: Vec<PathBuf> = folders
+        .iter()
+        .flat_map(|folder| WalkDir::new(folder).into_iter().filter_map(Result::ok))
+        .filter(|entry| entry.file_type().is_file() && is_audio(entry.path()))
+        .map(walkdir::DirEntry::into_path)
+        .collect();
+    let found = paths.len();
+    let mut entries = Vec::with_capacity(found);
+    for (checked, path) in paths.into_iter().enumerate() {
+        if checked % PROGRESS_EVERY == 0 {
+            let _ = sender.send(Update::Progress { checked, found });
+            ctx.request_repaint();
+        }
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs());
+        let source = path.to_string_lossy().into_owned();
+        match previous.unchanged(&source, modified, metadata.len()) {
+            Some(entry) => entries.push(entry.clone()),
+            None => entries.push(read(store, &path, source, modified, metadata.len())),
+        }
+    }
+    Library::new(entries)
+}
+
+/// Files that can't be read still get an entry, named after the file, so
+/// they show up and the player can say what's wrong when one is picked.
+fn read(store: &Store, path: &Path, source: String, modified: u64, size: u64) -> LibraryEntry {
+    let (tags, duration) = read_tags(store, path).unwrap_or_default();
+    let title = tags.title.unwrap_or_else(|| {
+        path.file_stem().map_or_else(|| source.clone(), |stem| stem.to_string_lossy().into_owned())
+    });
+    LibraryEntry {
+        path: source,
+        modified,
+        size,
+        title,
+        artist: tags.artist.or(tags.album_artist),
+        album: tags.album,
+        duration,
+        art: tags.art,
+    }
+}
+
+/// ID3v2 tags come out of the probe, ahead of the MP3 itself; Vorbis
+/// comments and MP4 atoms come from the container.
+fn read_tags(store: &Store, path: &Path) -> Option<(Tags, Option<Duration>)> {
+    let file = File::open(path).ok()?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .ok()?;
+    let mut tags = Tags::default();
+    if let Some(mut metadata) = probed.metadata.get() {
+        if let Some(revision) = metadata.skip_to_latest() {
+            tags.add(store, revision);
+        }
+    }
+    if let Some(revision) = probed.format.metadata().skip_to_latest() {
+        tags.add(store, revision);
+    }
+    let duration = probed.format.tracks().iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .and_then(|track| track.codec_params.time_base.zip(track.codec_params.n_frames))
+        .map(|(base, frames)| {
+            let time = base.calc_time(frames);
+            Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac)
+        });
+    Some((tags, duration))
+}
+
+impl Tags {
+    /// Fills in whatever is still missing from `revision`.
+    fn add(&mut self, store: &Store, revision: &MetadataRevision) {
+        for tag in revision.tags() {
+            let field = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => &mut self.title,
+                Some(StandardTagKey::Artist) => &mut self.artist,
+                Some(StandardTagKey::AlbumArtist) => &mut self.album_artist,
+                Some(StandardTagKey::Album) => &mut self.album,
+                _ => continue,
+            };
+            // RIFF INFO strings often keep their C terminators.
+            let value = tag.value.to_string();
+            let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+            if field.is_none() && !value.is_empty() {
+                *field = Some(value.to_owned());
+            }
+        }
+        if self.art.is_none() {
+            // The front cover if it's marked, otherwise whatever picture there is.
+            let visuals = revision.visuals();
+            let cover = visuals.iter()
+                .find(|visual| visual.usage == Some(StandardVisualKey::FrontCover))
+                .or_else(|| visuals.first());
+            if let Some(cover) = cover {
+                self.art = store.save_art(&cover.media_type, &cover.data);
+            }
+        }
+    }
+}
+
+fn is_audio(path: &Path) -> bool {
+    let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    extension.is_some_and(|extension| audio::EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// A hash that stays the same between builds, unlike `DefaultHasher`, so
+/// covers already saved are found again.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+
+const INDEX_FILE: &str = "library.ron";
+const ART_DIR: &str = "art";
+/// How many files to check between progress updates.
+const PROGRESS_EVERY: usize = 64;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A folder of its own under the system's temporary one.
+    fn folder(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("plistr-scan-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn audio_goes_by_extension_in_any_case() {
+        for path in ["a.mp3", "b.FLAC", "c/d.Ogg", "e.wave"] {
+            assert!(is_audio(Path::new(path)), "{path}");
+        }
+        for path in ["cover.jpg", "notes.txt", "mp3", "flac/readme", ".ogg.bak"] {
+            assert!(!is_audio(Path::new(path)), "{path}");
+        }
+    }
+
+    #[test]
+    fn fnv1a_is_pinned() {
+        // The published FNV-1a test vectors; covers already saved are named
+        // with it, so it mustn't change.
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn unreadable_files_are_named_after_the_file() {
+        let dir = folder("unreadable");
+        let path = dir.join("Field recording 3.mp3");
+        std::fs::write(&path, b"not really audio").unwrap();
+        let entry = read(&Store::new(dir.clone()), &path, path.to_string_lossy().into_owned(), 7, 16);
+        assert_eq!(entry.title, "Field recording 3");
+        assert_eq!((entry.artist, entry.album, entry.duration, entry.art), (None, None, None, None));
+        assert_eq!((entry.modified, entry.size), (7, 16));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn covers_are_saved_once_under_their_hash() {
+        let dir = folder("art");
+        let store = Store::new(dir.clone());
+        let name = store.save_art("image/jpeg", b"jpeg bytes").unwrap();
+        assert_eq!(name, format!("{:016x}.jpg", fnv1a(b"jpeg bytes")));
+        assert_eq!(store.save_art("image/jpg", b"jpeg bytes").unwrap(), name);
+        assert_eq!(std::fs::read(store.art_path(&name)).unwrap(), b"jpeg bytes");
+        assert_eq!(store.save_art("image/gif", b"gif bytes"), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn the_index_round_trips() {
+        let dir = folder("index");
+        let store = Store::new(dir.join("nested"));
+        assert_eq!(store.load(), None);
+        let library = Library::new(vec![LibraryEntry {
+            path: "/music/a.ogg".to_owned(),
+            modified: 1,
+            size: 2,
+            title: "A".to_owned(),
+            artist: Some("Someone".to_owned()),
+            album: None,
+            duration: Some(Duration::from_millis(1500)),
+            art: Some("0123.png".to_owned()),
+        }]);
+        store.save(&library).unwrap();
+        assert_eq!(store.load(), Some(library));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
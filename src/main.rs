@@ -1,7 +1,35 @@
 
 
+use std::sync::{mpsc, Arc};
+
 use eframe::egui;
 
+mod a11y;
+mod audio;
+mod content;
+mod deliverables;
+mod focus;
+mod fonts;
+mod history;
+mod i18n;
+mod icons;
+mod layout;
+mod library;
+mod meditation;
+mod nav;
+mod pdf;
+mod player;
+mod room;
+mod route;
+mod settings;
+mod social;
+mod soundscape;
+mod storage;
+mod team;
+mod theme;
+mod timeline;
+mod typography;
+
 
 #[cfg(target_arch = "wasm32")]
 fn main() {
@@ -49,7 +77,7 @@ fn main() {
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
     let _ = eframe::run_native(
-        "School Project",
+        APP_NAME,
         eframe::NativeOptions {
             viewport: egui::ViewportBuilder {
                 inner_size: Some(egui::vec2(1280.0, 720.0)),
@@ -64,93 +92,119 @@ fn main() {
 
 struct Program {
     page: Page,
-    dark_mode: bool,
+    router: route::Router,
+    content: Arc<content::Content>,
+    content_override: Option<mpsc::Receiver<content::Content>>,
+    pdf_viewer: Option<pdf::PdfViewer>,
+    themes: theme::Themes,
+    font_scale: f32,
+    fonts: fonts::Fonts,
+    screen_reader: bool,
+    /// The side panel phones get instead of header links.
+    menu_open: bool,
+    /// A language id from `assets/i18n`, or `None` to follow the OS/browser.
+    language: Option<String>,
+    /// `None` where the browser has no Web Audio.
+    player: Option<Box<dyn audio::Playback>>,
+    focus: focus::Focus,
+    meditation: meditation::Meditation,
+    library: library::LibraryView,
+    history: history::History,
+    social: social::SocialView,
+    rooms: room::RoomView,
 }
 
 impl eframe::App for Program {
+    fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        a11y::follow_tab_order(ctx, raw_input);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::TopBottomPanel::top("header")
+        if let Some(page) = self.router.incoming() {
+            self.page = page;
+        }
+        if let Some(content) = self.content_override.as_ref().and_then(|r| r.try_recv().ok()) {
+            self.content = Arc::new(content);
+            self.content_override = None;
+        }
+        self.themes.apply(ctx);
+        self.fonts.poll(ctx);
+        a11y::begin_frame(ctx);
+        if let Some(page) = a11y::page_shortcut(ctx) {
+            self.page = page;
+        }
+        if typography::handle_shortcuts(ctx, &mut self.font_scale) {
+            typography::install(ctx, self.font_scale);
+        }
+        self.focus.update(ctx, self.player.as_deref());
+        self.meditation.update(ctx, self.player.as_deref());
+        self.history.update(self.player.as_deref());
+        self.rooms.update(ctx, self.player.as_deref());
+        let breakpoint = layout::Breakpoint::current(ctx);
+        if breakpoint > layout::Breakpoint::Phone {
+            self.menu_open = false;
+        }
+        let header = egui::TopBottomPanel::top("header")
             .exact_height(47.0)
             .show(ctx, |ui| {
                 ui.horizontal_centered(|ui| {
-                    ui.visuals_mut().button_frame = false;
-                    ui.style_mut().spacing.item_spacing = egui::vec2(29.0, 0.0);
-                    if ui.button(egui::RichText::new("Plistr").heading()).clicked() {
-                        self.page = Page::Home;
-                    }
-                    if ui.button("About Us").clicked() {
-                        self.page = Page::AboutUs;
-                    }
-                    // if ui.button("Timeline").clicked() {
-                    //     self.page = Page::Timeline;
-                    // }
-                    if ui.button("Deliverables").clicked() {
-                        self.page = Page::Deliverables;
-                    }
-                    if ui.button("Contact").clicked() {
-                        self.page = Page::Contact;
+                    if breakpoint == layout::Breakpoint::Phone {
+                        let menu = ui.add(egui::Button::new(icons::LIST).frame(false));
+                        if a11y::label(menu, &i18n::tr("nav-menu")).clicked() {
+                            self.menu_open = !self.menu_open;
+                        }
                     }
+                    let icon = if self.themes.is_dark(ctx) { icons::MOON } else { icons::SUN };
+                    let tools: f32 = [icon, icons::TEXT_AA, icons::TRANSLATE].iter()
+                        .map(|tool| nav::button_width(ui, egui::RichText::new(*tool)) + breakpoint.item_spacing())
+                        .sum();
+                    nav::show_header(ui, &mut self.page, breakpoint, tools);
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if ui.button(if self.dark_mode { "☀" } else { "🌙" }).clicked() {
-                            self.dark_mode = !self.dark_mode;
-                            let mut visuals = ctx.style().visuals.clone();
-                            if self.dark_mode {
-                                apply_dark_mode(&mut visuals);
-                            } else {
-                                apply_light_mode(&mut visuals);
-                            }
-                            ctx.set_visuals(visuals);
-                        }
+                        let theme = ui.menu_button(icon, |ui| self.show_theme_menu(ui)).response;
+                        a11y::label(theme, &i18n::tr("menu-theme"));
+                        let text_size = ui.menu_button(icons::TEXT_AA, |ui| self.show_text_size_menu(ui)).response;
+                        a11y::label(text_size, &i18n::tr("menu-text-size"));
+                        let language = ui.menu_button(icons::TRANSLATE, |ui| self.show_language_menu(ui)).response;
+                        a11y::label(language, &i18n::tr("menu-language"));
                     });
                 });
             });
-        egui::TopBottomPanel::bottom("footer")
+        let footer = egui::TopBottomPanel::bottom("footer")
             .exact_height(47.0)
             .show(ctx, |ui| {
-                ui.horizontal_centered(|ui| {
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        match self.page {
-                            Page::Home => {
-                                if ui.button("Next: About Us").clicked() {
-                                    self.page = Page::AboutUs;
-                                }
-                            }
-                            Page::AboutUs => {
-                                // if ui.button("Next: Timeline").clicked() {
-                                //     self.page = Page::Timeline;
-                                // }
-                                if ui.button("Next: Deliverables").clicked() {
-                                    self.page = Page::Deliverables;
-                                }
-                            }
-                            Page::Timeline => {
-                                if ui.button("Next: Deliverables").clicked() {
-                                    self.page = Page::Deliverables;
-                                }
-                            }
-                            Page::Deliverables => {
-                                if ui.button("Next: Contact").clicked() {
-                                    self.page = Page::Contact;
-                                }
-                            }
-                            Page::Contact => {}
-                        }
-                    });
-                });
+                ui.horizontal_centered(|ui| nav::show_footer(ui, &mut self.page, breakpoint));
             });
-        egui::CentralPanel::default().show(ctx, |ui| {
+        let player_bar = self.player.as_deref().map(|player| {
+            player::accept_dropped(ctx, player);
+            egui::TopBottomPanel::bottom("player")
+                .exact_height(47.0)
+                .show(ctx, |ui| {
+                    ui.horizontal_centered(|ui| player::show_player(ui, player, breakpoint));
+                })
+        });
+        let menu = self.menu_open.then(|| {
+            egui::SidePanel::left("menu")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.add_space(11.0);
+                    let picked = nav::show_menu(ui, &mut self.page);
+                    if picked || ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        self.menu_open = false;
+                    }
+                })
+        });
+        let content = self.content.clone();
+        let page = egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                nav::show_breadcrumb(ui, &mut self.page);
                 match self.page {
                     Page::Home => {
-                        ui.label(egui::RichText::new(APP_NAME).size(59.0));
+                        ui.label(egui::RichText::new(&content.home.title).text_style(typography::display()));
                         ui.separator();
-                        egui::Frame {
-                            inner_margin: egui::Margin::symmetric(59.0, 11.0),
-                            ..Default::default()
-                        }.show(ui, |ui| {
+                        breakpoint.page_frame().show(ui, |ui| {
                             ui.add_space(29.0);
-                            if ui.available_width() >= 700.0 {
+                            if breakpoint >= layout::Breakpoint::Tablet {
                                 let panel_size = egui::vec2(
                                     ui.available_width() * 0.5,
                                     ui.available_height(),
@@ -158,162 +212,47 @@ impl eframe::App for Program {
                                 let layout = egui::Layout::top_down(egui::Align::Center);
                                 ui.horizontal(|ui| {
                                     ui.allocate_ui_with_layout(panel_size, layout, |ui| {
-                                        self.show_home_info(ui);
+                                        self.show_home_info(ui, &content.home);
                                     });
                                     ui.allocate_ui_with_layout(panel_size, layout, |ui| {
-                                        self.show_home_links(ui);
+                                        nav::show_home_links(ui, &mut self.page);
                                     });
                                 });
                             } else {
-                                self.show_home_info(ui);
-                                self.show_home_links(ui);
+                                self.show_home_info(ui, &content.home);
+                                nav::show_home_links(ui, &mut self.page);
                             }
                         });
                     }
                     Page::AboutUs => {
-                        ui.label(egui::RichText::new("About Us").size(59.0));
+                        ui.label(egui::RichText::new(&content.about.title).text_style(typography::display()));
                         ui.separator();
-                        egui::Frame {
-                            inner_margin: egui::Margin::symmetric(59.0, 11.0),
-                            ..Default::default()
-                        }.show(ui, |ui| {
-                            ui.horizontal(|ui| {
-                                ui.style_mut().spacing.button_padding = egui::Vec2::ZERO;
-                                ui.style_mut().spacing.item_spacing = egui::Vec2::ZERO;
-                                let month_width = ui.available_size_before_wrap().x / 3.0;
-                                let layout = egui::Layout::top_down(egui::Align::Center);
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("Matthew Norman");
-                                    ui.weak("Developer");
-                                    ui.separator();
-                                    ui.label("As our developer, Matthew is responsible for the actual implementation of Plistr. He writes the code that runs not just the app, but also this very website.");
-                                    ui.separator();
-                                    ui.heading("Responsibilities:");
-                                    ui.strong("Programming");
-                                    ui.strong("Development");
-                                });
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("Sara Quintana");
-                                    ui.weak("Designer");
-                                    ui.separator();
-                                    ui.label("As our designer, Sara is responsible for designing Plistr. Everything from the application interface to the user experience falls under her purview.");
-                                    ui.separator();
-                                    ui.heading("Responsibilities:");
-                                    ui.strong("UI/UX Design");
-                                    ui.strong("Wireframing");
-                                });
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("Snailey Dol");
-                                    ui.weak("Researcher");
-                                    ui.separator();
-                                    ui.label("As our researcher, Snailey is responsible for information collection. Anything from running user tests, to analyzing the efficacy of the studying methodologies we support.");
-                                    ui.separator();
-                                    ui.heading("Responsibilities:");
-                                    ui.strong("User Testing");
-                                    ui.strong("Efficacy Analysis");
-                                });
-                            });
+                        breakpoint.page_frame().show(ui, |ui| {
+                            ui.add_space(29.0);
+                            team::show_team(ui, &content.about.team);
                         });
                     }
                     Page::Timeline => {
-                        ui.label(egui::RichText::new("Timeline").size(59.0));
+                        ui.label(egui::RichText::new(&content.timeline.title).text_style(typography::display()));
                         ui.separator();
-                        egui::Frame {
-                            inner_margin: egui::Margin::symmetric(59.0, 11.0),
-                            ..Default::default()
-                        }.show(ui, |ui| {
+                        breakpoint.page_frame().show(ui, |ui| {
                             ui.add_space(29.0);
-                            ui.weak("Hover over important dates to see more.");
-                            ui.add_space(19.0);
-                            ui.horizontal(|ui| {
-                                ui.style_mut().spacing.button_padding = egui::Vec2::ZERO;
-                                ui.style_mut().spacing.item_spacing = egui::Vec2::ZERO;
-                                let month_width = ui.available_size_before_wrap().x / 4.0;
-                                let layout = egui::Layout::top_down(egui::Align::Center);
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("September");
-                                });
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("October");
-                                });
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("November");
-                                });
-                                ui.allocate_ui_with_layout(egui::vec2(month_width, 111.0), layout, |ui| {
-                                    ui.heading("December");
-                                });
-                            });
+                            ui.weak(&content.timeline.hint);
                             ui.add_space(19.0);
-                            let width = ui.available_width();
-                            ui.horizontal(|ui| {
-                                ui.add_space(width * 0.25 * 0.3);
-                                ui.heading("9/10/24").on_hover_ui(|ui| {
-                                    ui.label("Initial ideation phase");
-                                });
-                                ui.add_space(width * 0.5 * 0.3);
-                                ui.heading("11/8/24").on_hover_ui(|ui| {
-                                    ui.label("Rough prototype commpleted");
-                                });
-                            });
-                            ui.separator();
-                            ui.horizontal(|ui| {
-                                ui.add_space(width * 0.25 * 0.75);
-                                ui.heading("9/19/24").on_hover_ui(|ui| {
-                                    ui.label("Initial ideation");
-                                });
-                                ui.add_space(width * 0.5 * 0.3);
-                                ui.heading("11/15/24").on_hover_ui(|ui| {
-                                    ui.label("First prototype commpleted");
-                                });
-                            });
+                            timeline::Timeline::new(&content.timeline.milestones, content::Date::today()).show(ui);
                         });
                     }
                     Page::Deliverables => {
-                        ui.label(egui::RichText::new("Deliverables").size(59.0));
-                            ui.separator();
-                        egui::Frame {
-                            inner_margin: egui::Margin::symmetric(59.0, 11.0),
-                            ..Default::default()
-                        }.show(ui, |ui| {
-                            ui.add_space(29.0);
-                            ui.heading("Work Breakdown Structure");
-                            ui.separator();
-                            if ui.link("View PDF ⎆").clicked() {
-                                ctx.open_url(egui::OpenUrl::new_tab(WBS_URL));
-                            }
-                            ui.horizontal(|ui| {
-                                ui.strong("COMPLETION DATE: ");
-                                ui.label("10/25/2024");
-                            });
-                            ui.label(WBS_DESC);
-                            ui.add_space(29.0);
-                            ui.heading("Wireframes (Rough Prototype)");
-                            ui.separator();
-                            if ui.link("View PDF ⎆").clicked() {
-                                ctx.open_url(egui::OpenUrl::new_tab(WIREFRAMES_URL));
-                            }
-                            ui.horizontal(|ui| {
-                                ui.strong("COMPLETION DATE: ");
-                                ui.label("11/8/2024");
-                            });
-                            ui.label(ROUGH_PROTO_DESC);
-                            ui.add_space(29.0);
-                            ui.heading("First Prototype");
-                            ui.separator();
-                            if ui.link("View PDF ⎆").clicked() {
-                                ctx.open_url(egui::OpenUrl::new_tab(PROTOTYPE_URL));
-                            }
-                            ui.horizontal(|ui| {
-                                ui.strong("COMPLETION DATE: ");
-                                ui.label("11/15/2024");
-                            });
-                            ui.label(FIRST_PROTO_DESC);
+                        ui.label(egui::RichText::new(&content.deliverables.title).text_style(typography::display()));
+                        ui.separator();
+                        breakpoint.page_frame().show(ui, |ui| {
+                            deliverables::show_deliverables(ui, &content.deliverables.items, &mut self.pdf_viewer);
                             ui.add_space(29.0);
-                            ui.heading("...And more!");
+                            ui.heading(&content.deliverables.outro_heading);
                             ui.separator();
                             ui.horizontal_wrapped(|ui| {
-                                ui.label("Because this is an ongoing project, more work will need to be done in the future. We plan on providing updates with deliverables here and on the discussion boards linked on our ");
-                                if ui.link("Contact page").clicked() {
+                                ui.label(&content.deliverables.outro);
+                                if ui.link(&content.deliverables.outro_link).clicked() {
                                     self.page = Page::Contact;
                                 }
                                 ui.label(".");
@@ -321,101 +260,250 @@ impl eframe::App for Program {
                         });
                     }
                     Page::Contact => {
-                        ui.label(egui::RichText::new("Contact").size(59.0));
+                        ui.label(egui::RichText::new(&content.contact.title).text_style(typography::display()));
                         ui.separator();
-                        egui::Frame {
-                            inner_margin: egui::Margin::symmetric(59.0, 11.0),
-                            ..Default::default()
-                        }.show(ui, |ui| {
+                        breakpoint.page_frame().show(ui, |ui| {
                             ui.add_space(29.0);
-                            ui.heading("Discussions");
-                            if ui.link("Open discussion boards in a new tab").clicked() {
-                                ctx.open_url(egui::OpenUrl::new_tab(DISCUSSIONS_URL));
+                            ui.heading(&content.contact.heading);
+                            if ui.link(&content.contact.link_text).clicked() {
+                                ctx.open_url(egui::OpenUrl::new_tab(&content.contact.url));
                             }
-                            ui.label("This site is heavily integrate with GitHub for hosting, content delivery, and communications. We have set up a discussion forum at the link above where you can view updates, ask questions, and chat with us.");
+                            ui.label(&content.contact.blurb);
+                        });
+                    }
+                    Page::Focus => {
+                        ui.label(egui::RichText::new(i18n::tr("page-focus")).text_style(typography::display()));
+                        ui.separator();
+                        breakpoint.page_frame().show(ui, |ui| {
+                            ui.add_space(29.0);
+                            self.focus.show(ui, self.player.as_deref());
+                        });
+                    }
+                    Page::Meditate => {
+                        ui.label(egui::RichText::new(i18n::tr("page-meditate")).text_style(typography::display()));
+                        ui.separator();
+                        breakpoint.page_frame().show(ui, |ui| {
+                            ui.add_space(29.0);
+                            self.meditation.show(ui, self.player.as_deref());
+                        });
+                    }
+                    Page::Library => {
+                        ui.label(egui::RichText::new(i18n::tr("page-library")).text_style(typography::display()));
+                        ui.separator();
+                        breakpoint.page_frame().show(ui, |ui| {
+                            ui.add_space(29.0);
+                            self.library.show(ui, self.player.as_deref());
+                        });
+                    }
+                    Page::Social => {
+                        ui.label(egui::RichText::new(i18n::tr("page-social")).text_style(typography::display()));
+                        ui.separator();
+                        breakpoint.page_frame().show(ui, |ui| {
+                            ui.add_space(29.0);
+                            self.social.show(ui, self.player.as_deref(), &self.focus);
+                        });
+                    }
+                    Page::Rooms => {
+                        ui.label(egui::RichText::new(i18n::tr("page-rooms")).text_style(typography::display()));
+                        ui.separator();
+                        breakpoint.page_frame().show(ui, |ui| {
+                            ui.add_space(29.0);
+                            self.rooms.show(ui, self.player.as_deref(), &self.focus);
                         });
                     }
                 }
             });
         });
+        // The panels are added outside in, so Tab would go from the header
+        // straight to the footer without this.
+        let panels = [
+            Some(header.response.rect),
+            menu.map(|menu| menu.response.rect),
+            Some(page.response.rect),
+            player_bar.map(|bar| bar.response.rect),
+            Some(footer.response.rect),
+        ];
+        a11y::set_tab_order(ctx, panels.into_iter().flatten().collect());
+        if let Some(viewer) = &mut self.pdf_viewer {
+            if !viewer.show(ctx) {
+                self.pdf_viewer = None;
+            }
+        }
+        a11y::focus_ring(ctx);
+        self.router.outgoing(self.page);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        settings::SavedState {
+            version: settings::VERSION,
+            page: self.page.slug().to_owned(),
+            theme: self.themes.settings.clone(),
+            font_scale: self.font_scale,
+            screen_reader: self.screen_reader,
+            language: self.language.clone(),
+            focus: self.focus.settings(),
+            soundscape: self.focus.soundscape(),
+            library_folders: self.library.folders(),
+            server_account: self.social.credentials(),
+        }.save(storage);
     }
 }
 
 impl Program {
     fn new(cc: &eframe::CreationContext) -> Self {
-        let mut visuals = egui::Visuals::light();
-        apply_light_mode(&mut visuals);
-        cc.egui_ctx.set_visuals(visuals);
-        cc.egui_ctx.style_mut(|s| {
-            use egui::FontFamily::Proportional;
-            use egui::FontId;
-            use egui::TextStyle::*;
-            
-            s.text_styles = [
-                (Heading, FontId::new(37.0, Proportional)),
-                (Body, FontId::new(29.0, Proportional)),
-                (Monospace, FontId::new(29.0, Proportional)),
-                (Button, FontId::new(23.0, Proportional)),
-                (Small, FontId::new(19.0, Proportional)),
-              ].into();
-        });
+        egui_extras::install_image_loaders(&cc.egui_ctx);
+        let saved = cc.storage.and_then(settings::SavedState::load);
+        route::listen(&cc.egui_ctx);
+        let page = route::start_page()
+            .or_else(|| Page::from_slug(&saved.as_ref()?.page))
+            .unwrap_or(Page::Home);
+        let (theme_settings, font_scale, screen_reader, language, focus_settings, soundscape, library_folders, server_account) = match saved {
+            Some(saved) => (
+                saved.theme,
+                saved.font_scale,
+                saved.screen_reader,
+                saved.language,
+                saved.focus,
+                saved.soundscape,
+                saved.library_folders,
+                saved.server_account,
+            ),
+            None => (Default::default(), 1.0, false, None, Default::default(), Default::default(), Vec::new(), None),
+        };
+        i18n::select(language.as_deref());
+        cc.egui_ctx.options_mut(|o| o.screen_reader = screen_reader);
+        let fonts = fonts::Fonts::new(&cc.egui_ctx);
+        typography::install(&cc.egui_ctx, font_scale);
+        // Ctrl +/- resize text through the type scale instead.
+        cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
+        let content_override = content_override_url()
+            .map(|url| content::fetch_override(&cc.egui_ctx, &url));
 
         Self {
-            page: Page::Home,
-            dark_mode: false,
+            page,
+            router: route::Router::new(page),
+            content: Arc::new(content::Content::bundled()),
+            content_override,
+            pdf_viewer: None,
+            themes: theme::Themes::bundled(theme_settings),
+            font_scale,
+            fonts,
+            screen_reader,
+            menu_open: false,
+            language,
+            player: audio::default_player(),
+            focus: focus::Focus::new(focus_settings, soundscape),
+            meditation: meditation::Meditation::bundled(),
+            library: library::LibraryView::new(&cc.egui_ctx, library_folders),
+            history: history::History::new(storage::open()),
+            social: match server_account {
+                Some(credentials) => social::SocialView::signed_in(plistr_core::ApiClient::new(credentials)),
+                None => social::SocialView::offline(),
+            },
+            rooms: room::RoomView::new(),
         }
     }
 
-    fn show_home_info(&mut self, ui: &mut egui::Ui) {
-        ui.label(egui::RichText::new(TAGLINE).size(43.0));
+    fn show_theme_menu(&mut self, ui: &mut egui::Ui) {
+        use theme::ThemeChoice;
+
+        let palettes: Vec<String> = self.themes.palette_names().map(str::to_owned).collect();
+        let accents: Vec<String> = self.themes.accent_names().map(str::to_owned).collect();
+        let settings = &mut self.themes.settings;
+        ui.weak(i18n::tr("menu-theme"));
+        ui.radio_value(&mut settings.choice, ThemeChoice::System, i18n::tr("menu-theme-system"));
+        for name in palettes {
+            ui.radio_value(&mut settings.choice, ThemeChoice::Named(name.clone()), name);
+        }
         ui.separator();
-        ui.label(egui::RichText::new(DESCRIPTION).size(23.0));
+        ui.weak(i18n::tr("menu-accent"));
+        ui.radio_value(&mut settings.accent, None, i18n::tr("menu-accent-default"));
+        for name in accents {
+            ui.radio_value(&mut settings.accent, Some(name.clone()), name);
+        }
     }
 
-    fn show_home_links(&mut self, ui: &mut egui::Ui) {
-        // if ui.link(egui::RichText::new("Timeline").size(37.0)).clicked() {
-        //     self.page = Page::Timeline;
-        // }
-        // ui.add_space(29.0);
-        if ui.link(egui::RichText::new("About Us").size(37.0)).clicked() {
-            self.page = Page::AboutUs;
+    fn show_text_size_menu(&mut self, ui: &mut egui::Ui) {
+        ui.weak(i18n::tr("menu-text-size"));
+        for scale in typography::SCALES {
+            let label = format!("{:.0}%", scale * 100.0);
+            if ui.radio_value(&mut self.font_scale, scale, label).changed() {
+                typography::install(ui.ctx(), self.font_scale);
+            }
+        }
+        ui.weak(i18n::tr("menu-text-size-hint"));
+        // Native builds talk to the OS screen reader through AccessKit instead.
+        #[cfg(target_arch = "wasm32")]
+        {
+            ui.separator();
+            if ui.checkbox(&mut self.screen_reader, i18n::tr("menu-screen-reader")).changed() {
+                ui.ctx().options_mut(|o| o.screen_reader = self.screen_reader);
+            }
         }
-        ui.add_space(29.0);
-        if ui.link(egui::RichText::new("Deliverables").size(37.0)).clicked() {
-            self.page = Page::Deliverables;
+    }
+
+    fn show_language_menu(&mut self, ui: &mut egui::Ui) {
+        ui.weak(i18n::tr("menu-language"));
+        let mut changed = ui.radio_value(&mut self.language, None, i18n::tr("menu-language-system")).changed();
+        for (id, name) in i18n::languages() {
+            changed |= ui.radio_value(&mut self.language, Some(id.to_owned()), name).changed();
         }
-        ui.add_space(29.0);
-        if ui.link(egui::RichText::new("Contact").size(37.0)).clicked() {
-            self.page = Page::Contact;
+        if changed {
+            i18n::select(self.language.as_deref());
+            self.content = Arc::new(content::Content::bundled());
+            self.content_override = content_override_url().map(|url| content::fetch_override(ui.ctx(), &url));
         }
     }
+
+    fn show_home_info(&mut self, ui: &mut egui::Ui, home: &content::HomePage) {
+        ui.label(egui::RichText::new(&home.tagline).text_style(typography::title()));
+        ui.separator();
+        ui.label(egui::RichText::new(&home.description).text_style(typography::label()));
+    }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Page {
     Home,
     AboutUs,
     Timeline,
     Deliverables,
     Contact,
+    Focus,
+    Meditate,
+    Library,
+    Social,
+    Rooms,
+}
+
+/// The deployed site always checks for edited copy in the interface language
+/// next to itself; the native build only does when given `--content-url`,
+/// which is used whatever the language.
+#[cfg(target_arch = "wasm32")]
+fn content_override_url() -> Option<String> {
+    Some(content::path(i18n::current()))
 }
 
-fn apply_dark_mode(visuals: &mut egui::Visuals) {
-    *visuals = egui::Visuals::dark();
+#[cfg(not(target_arch = "wasm32"))]
+fn content_override_url() -> Option<String> {
+    cli_arg("--content-url")
 }
 
-fn apply_light_mode(visuals: &mut egui::Visuals) {
-    *visuals = egui::Visuals::light();
+/// Looks up `--name value` or `--name=value` on the command line.
+#[cfg(not(target_arch = "wasm32"))]
+fn cli_arg(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix(name) {
+            Some("") => return args.next(),
+            Some(rest) if rest.starts_with('=') => return Some(rest[1..].to_owned()),
+            _ => {}
+        }
+    }
+    None
 }
 
 
-const APP_NAME: &str = "Plistr";
-const TAGLINE: &str = "An audio-based social networking platform";
-const DESCRIPTION: &str = "With a focus on productivity, Plistr aims to be an all-in-one studying/meditation tool for students, professionals, and anyone who wants to get some work done.";
-const WBS_URL: &str = "https://github.com/mrnrm/plistr/blob/master/assets/wbs.pdf";
-const WIREFRAMES_URL: &str = "https://github.com/mrnrm/plistr/blob/master/assets/wireframes.pdf";
-const PROTOTYPE_URL: &str = "https://github.com/mrnrm/plistr/blob/master/assets/first_prototype.pdf";
-const DISCUSSIONS_URL: &str = "https://github.com/mrnrm/plistr/discussions";
-const WBS_DESC: &str = "An overview of all work that must be done to see our project through to completion. This documents details the categorization of tasks and the steps required to see them through.";
-const ROUGH_PROTO_DESC: &str = "A set of wireframes that visually explain the initial idea for the project.";
-const FIRST_PROTO_DESC: &str = "An interactive set of high fidelity wireframes detailing all the routes a user could take as s/he navigates Plistr.";
+/// Names the window, and the folder native saves and the library index go in.
+#[cfg(not(target_arch = "wasm32"))]
+const APP_NAME: &str = "School Project";
@@ -0,0 +1,372 @@
+//! Guided meditation. A script paces the session: prompts, chimes, spoken
+//! clips, ambient sound that fades with each segment, and a breathing circle
+//! to follow.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use eframe::egui;
+use plistr_core::{Clock, Track, TrackId};
+
+use crate::audio::Playback;
+use crate::focus::SystemClock;
+use crate::{content, i18n, icons, typography};
+
+mod script;
+
+use script::{Ambient, Breath, Script};
+
+
+pub struct Meditation {
+    scripts: Vec<Loaded>,
+    selected: usize,
+    run: Option<Run>,
+    /// Scripts dropped on the page that couldn't be used, and why.
+    problems: Vec<String>,
+    clock: SystemClock,
+}
+
+struct Loaded {
+    name: String,
+    script: Script,
+    /// Where a dropped script's relative paths start from. Bundled scripts
+    /// use paths under `assets/`.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    folder: Option<PathBuf>,
+}
+
+/// A session under way.
+struct Run {
+    script: usize,
+    /// Seconds played before the last resume.
+    banked: f32,
+    resumed_at: Option<Duration>,
+    /// The segment whose cues have been played.
+    segment: Option<usize>,
+    ambient: Option<Ambient>,
+    /// The queue as it was before the first ambient took it over.
+    saved: Option<Saved>,
+}
+
+/// What the player was doing, to put back when the session ends.
+struct Saved {
+    queue: Vec<Track>,
+    current: Option<usize>,
+    position: Duration,
+    playing: bool,
+}
+
+impl Meditation {
+    /// Loads the scripts in `assets/meditations`. They're checked like the
+    /// rest of the bundled content, so a broken one stops the app at start.
+    pub fn bundled() -> Self {
+        let scripts = BUNDLED.iter()
+            .map(|&(name, text)| match Script::parse(text) {
+                Ok(script) => Loaded { name: name.to_owned(), script, folder: None },
+                Err(e) => panic!("assets/meditations/{name}: {e}"),
+            })
+            .collect();
+        Self {
+            scripts,
+            selected: 0,
+            run: None,
+            problems: Vec::new(),
+            clock: SystemClock::new(),
+        }
+    }
+
+    /// Call every frame, whichever page is showing, so cues play on time.
+    pub fn update(&mut self, ctx: &egui::Context, player: Option<&dyn Playback>) {
+        let Some(run) = &mut self.run else {
+            return;
+        };
+        let loaded = &self.scripts[run.script];
+        let Some((index, into)) = loaded.script.at(run.elapsed(&self.clock)) else {
+            if let Some(player) = player {
+                player.chime();
+                finish(run, player);
+            }
+            self.run = None;
+            return;
+        };
+        if !run.is_running() {
+            return;
+        }
+        ctx.request_repaint();
+        let Some(player) = player else {
+            return;
+        };
+        let segment = &loaded.script.segments[index];
+        if run.segment != Some(index) {
+            run.segment = Some(index);
+            if segment.chime {
+                player.chime();
+            }
+            if let Some(voice) = &segment.voice {
+                player.cue(&loaded.source(voice));
+            }
+            if segment.ambient != run.ambient {
+                match &segment.ambient {
+                    Some(ambient) => {
+                        run.saved.get_or_insert_with(|| Saved::take(player));
+                        player.clear();
+                        player.enqueue(Track::new(TrackId::unstored(), &loaded.script.title, loaded.source(&ambient.source)));
+                        player.play();
+                    }
+                    None => player.clear(),
+                }
+                run.ambient = segment.ambient.clone();
+            }
+        }
+        if let Some(ambient) = &run.ambient {
+            player.set_gain(ambient.volume * segment.fade(into));
+            // Loops the ambient. Only files that opened, which have a
+            // length, so a broken one isn't retried every frame.
+            let status = player.status();
+            let opened = status.queue.first().is_some_and(|track| track.duration.is_some());
+            if opened && status.current.is_none() {
+                player.jump(0);
+            }
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        self.accept_dropped(ui.ctx());
+        let running = self.run.is_some();
+        ui.heading(i18n::tr("meditate-scripts"));
+        ui.separator();
+        for (i, loaded) in self.scripts.iter().enumerate() {
+            let minutes = (loaded.script.length() / 60.0).round() as u32;
+            let title = i18n::tr_with("meditate-script", &[
+                ("title", loaded.script.title.as_str().into()),
+                ("minutes", minutes.into()),
+            ]);
+            let response = ui.add_enabled(!running, egui::SelectableLabel::new(self.selected == i, title));
+            if response.on_hover_text(&loaded.name).clicked() {
+                self.selected = i;
+            }
+            if self.selected == i && !loaded.script.description.is_empty() {
+                ui.weak(&loaded.script.description);
+            }
+        }
+        ui.weak(i18n::tr("meditate-drop-hint"));
+        for problem in &self.problems {
+            ui.colored_label(ui.visuals().error_fg_color, problem);
+        }
+        ui.add_space(29.0);
+        ui.vertical_centered(|ui| self.show_session(ui, player));
+    }
+
+    fn show_session(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        let index = self.run.as_ref().map_or(self.selected, |run| run.script);
+        let script = &self.scripts[index].script;
+        let elapsed = self.run.as_ref().map_or(0.0, |run| run.elapsed(&self.clock));
+        let (segment, into) = script.at(elapsed).unwrap_or((0, 0.0));
+        let segment = &script.segments[segment];
+
+        ui.label(egui::RichText::new(&script.title).text_style(typography::title()));
+        show_breathing(ui, segment.breathing.map(|breathing| (breathing, into)));
+        let prompt = segment.prompt.as_deref().filter(|_| self.run.is_some()).unwrap_or("");
+        ui.label(egui::RichText::new(prompt).text_style(typography::heading()));
+        ui.add_space(11.0);
+        let progress = egui::ProgressBar::new(elapsed / script.length())
+            .desired_width(311.0)
+            .text(format!("{} / {}", clock(elapsed), clock(script.length())));
+        ui.add(progress);
+        ui.add_space(11.0);
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - 193.0).max(0.0) / 2.0);
+            match &mut self.run {
+                None => {
+                    if ui.button(format!("{} {}", icons::PLAY, i18n::tr("meditate-start"))).clicked() {
+                        self.run = Some(Run::start(self.selected, &self.clock));
+                    }
+                }
+                Some(run) => {
+                    let (icon, key) = if run.is_running() {
+                        (icons::PAUSE, "meditate-pause")
+                    } else {
+                        (icons::PLAY, "meditate-resume")
+                    };
+                    if ui.button(format!("{icon} {}", i18n::tr(key))).clicked() {
+                        if run.is_running() {
+                            run.pause(&self.clock);
+                            if let Some(player) = player.filter(|_| run.ambient.is_some()) {
+                                player.pause();
+                            }
+                        } else {
+                            run.resume(&self.clock);
+                            if let Some(player) = player.filter(|_| run.ambient.is_some()) {
+                                player.play();
+                            }
+                        }
+                    }
+                    if ui.button(i18n::tr("meditate-stop")).clicked() {
+                        if let Some(player) = player {
+                            finish(run, player);
+                        }
+                        self.run = None;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Adds `.ron` scripts dropped on the page, noting any that don't parse.
+    fn accept_dropped(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped {
+            let name = match &file.path {
+                Some(path) => path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                None => file.name.clone(),
+            };
+            if !name.to_ascii_lowercase().ends_with(".ron") {
+                continue;
+            }
+            let text = match (&file.path, &file.bytes) {
+                (_, Some(bytes)) => String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string()),
+                (Some(path), None) => std::fs::read_to_string(path).map_err(|e| e.to_string()),
+                (None, None) => continue,
+            };
+            match text.and_then(|text| Script::parse(&text).map_err(|e| e.to_string())) {
+                Ok(script) => {
+                    let folder = file.path.as_ref().and_then(|path| path.parent()).map(PathBuf::from);
+                    self.scripts.push(Loaded { name, script, folder });
+                    if self.run.is_none() {
+                        self.selected = self.scripts.len() - 1;
+                    }
+                }
+                Err(e) => {
+                    let problem = i18n::tr_with("meditate-load-failed", &[
+                        ("name", name.as_str().into()),
+                        ("error", e.as_str().into()),
+                    ]);
+                    content::warn(&problem);
+                    self.problems.push(problem);
+                }
+            }
+        }
+    }
+}
+
+impl Loaded {
+    /// Where the player finds a file the script names.
+    fn source(&self, path: &str) -> String {
+        if path.contains("://") {
+            return path.to_owned();
+        }
+        #[cfg(target_arch = "wasm32")]
+        return content::asset_uri(path);
+        #[cfg(not(target_arch = "wasm32"))]
+        match &self.folder {
+            Some(folder) => folder.join(path).to_string_lossy().into_owned(),
+            None => path.to_owned(),
+        }
+    }
+}
+
+impl Run {
+    fn start(script: usize, clock: &SystemClock) -> Self {
+        Self {
+            script,
+            banked: 0.0,
+            resumed_at: Some(clock.now()),
+            segment: None,
+            ambient: None,
+            saved: None,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.resumed_at.is_some()
+    }
+
+    fn elapsed(&self, clock: &impl Clock) -> f32 {
+        let running = self.resumed_at.map_or(0.0, |at| (clock.now() - at).as_secs_f32());
+        self.banked + running
+    }
+
+    fn pause(&mut self, clock: &impl Clock) {
+        self.banked = self.elapsed(clock);
+        self.resumed_at = None;
+    }
+
+    fn resume(&mut self, clock: &impl Clock) {
+        self.resumed_at.get_or_insert(clock.now());
+    }
+}
+
+/// Hands the player back as it was before the session.
+fn finish(run: &mut Run, player: &dyn Playback) {
+    if let Some(saved) = run.saved.take() {
+        saved.restore(player);
+    }
+    player.set_gain(1.0);
+}
+
+impl Saved {
+    fn take(player: &dyn Playback) -> Self {
+        let status = player.status();
+        Self { queue: status.queue, current: status.current, position: status.position, playing: status.playing }
+    }
+
+    fn restore(self, player: &dyn Playback) {
+        player.clear();
+        for track in self.queue {
+            player.enqueue(track);
+        }
+        if let Some(current) = self.current {
+            player.jump(current);
+            player.seek(self.position);
+            if !self.playing {
+                player.pause();
+            }
+        }
+    }
+}
+
+/// A circle that swells and shrinks with the breath, with what to do now
+/// and for how long in the middle. Segments without a pace get a still one.
+fn show_breathing(ui: &mut egui::Ui, breathing: Option<(script::Breathing, f32)>) {
+    let size = egui::vec2(ui.available_width().min(BREATHING_SIZE), BREATHING_SIZE);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let (fullness, label) = match breathing {
+        Some((breathing, into)) => {
+            let (breath, left) = breathing.at(into);
+            let key = match breath {
+                Breath::In => "meditate-breathe-in",
+                Breath::Hold => "meditate-hold",
+                Breath::Out => "meditate-breathe-out",
+                Breath::Rest => "meditate-rest",
+            };
+            (breathing.fullness(into), format!("{}\n{}", i18n::tr(key), left.ceil()))
+        }
+        None => (0.5, String::new()),
+    };
+    let accent = ui.visuals().selection.bg_fill;
+    let radius = egui::lerp(rect.height() * 0.2..=rect.height() * 0.5, fullness);
+    let painter = ui.painter_at(rect);
+    painter.circle(rect.center(), radius, accent.gamma_multiply(0.25), egui::Stroke::new(2.0, accent));
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        &label,
+        typography::heading().resolve(ui.style()),
+        ui.visuals().strong_text_color(),
+    );
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, label.replace('\n', " ")));
+}
+
+/// `m:ss`.
+fn clock(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+
+/// Every `.ron` file in `assets/meditations`, as `(file name, text)`, listed
+/// by `build.rs`.
+const BUNDLED: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/meditations.rs"));
+
+const BREATHING_SIZE: f32 = 239.0;
@@ -0,0 +1,367 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+
+/// A guided session, read from a `.ron` file: segments played one after
+/// another, each with its own prompt, sounds and breathing pace.
+#[derive(Deserialize)]
+pub struct Script {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Deserialize)]
+pub struct Segment {
+    /// Seconds.
+    pub length: f32,
+    /// Shown for the whole segment.
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// A spoken clip played as the segment starts.
+    #[serde(default)]
+    pub voice: Option<String>,
+    /// Rings as the segment starts.
+    #[serde(default)]
+    pub chime: bool,
+    /// Plays under everything else. A segment with the same ambient as the
+    /// one before carries on without restarting it.
+    #[serde(default)]
+    pub ambient: Option<Ambient>,
+    #[serde(default)]
+    pub breathing: Option<Breathing>,
+    /// Seconds over which the ambient rises at the start and falls at the
+    /// end.
+    #[serde(default)]
+    pub fade_in: f32,
+    #[serde(default)]
+    pub fade_out: f32,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Ambient {
+    pub source: String,
+    /// From 0 to 1.
+    #[serde(default = "full_volume")]
+    pub volume: f32,
+}
+
+/// One breath, in seconds per part. Repeats for the whole segment.
+#[derive(Clone, Copy, Deserialize)]
+pub struct Breathing {
+    pub inhale: f32,
+    #[serde(default)]
+    pub hold: f32,
+    pub exhale: f32,
+    /// After breathing out, before the next breath.
+    #[serde(default)]
+    pub rest: f32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Breath {
+    In,
+    Hold,
+    Out,
+    Rest,
+}
+
+pub enum ScriptError {
+    Parse(ron::error::SpannedError),
+    /// What's wrong, each starting with the line of the segment it's in.
+    Invalid(Vec<String>),
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let script: Script = ron::from_str(text).map_err(ScriptError::Parse)?;
+        let problems = script.validate(&segment_lines(text));
+        if problems.is_empty() {
+            Ok(script)
+        } else {
+            Err(ScriptError::Invalid(problems))
+        }
+    }
+
+    /// Seconds.
+    pub fn length(&self) -> f32 {
+        self.segments.iter().map(|segment| segment.length).sum()
+    }
+
+    /// The segment playing `seconds` in, and how far into it.
+    pub fn at(&self, seconds: f32) -> Option<(usize, f32)> {
+        let mut start = 0.0;
+        for (i, segment) in self.segments.iter().enumerate() {
+            if seconds < start + segment.length {
+                return Some((i, seconds - start));
+            }
+            start += segment.length;
+        }
+        None
+    }
+
+    /// `lines` has the line each segment starts on.
+    fn validate(&self, lines: &[usize]) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.title.trim().is_empty() {
+            problems.push("title is empty".to_owned());
+        }
+        if self.segments.is_empty() {
+            problems.push("segments is empty".to_owned());
+        }
+        for (i, segment) in self.segments.iter().enumerate() {
+            let field = |name: &str| match lines.get(i) {
+                Some(line) => format!("{line}: segments[{i}].{name}"),
+                None => format!("segments[{i}].{name}"),
+            };
+            if !(segment.length.is_finite() && segment.length > 0.0) {
+                problems.push(format!("{} must be more than 0 seconds", field("length")));
+            }
+            for (name, text) in [("prompt", &segment.prompt), ("voice", &segment.voice)] {
+                if text.as_ref().is_some_and(|text| text.trim().is_empty()) {
+                    problems.push(format!("{} is empty", field(name)));
+                }
+            }
+            if !(segment.fade_in >= 0.0 && segment.fade_out >= 0.0) {
+                problems.push(format!("{} and fade_out can't be negative", field("fade_in")));
+            } else if segment.fade_in + segment.fade_out > segment.length {
+                problems.push(format!("{} and fade_out are longer than the segment", field("fade_in")));
+            }
+            if let Some(ambient) = &segment.ambient {
+                if ambient.source.trim().is_empty() {
+                    problems.push(format!("{} is empty", field("ambient.source")));
+                }
+                if !(0.0..=1.0).contains(&ambient.volume) {
+                    problems.push(format!("{} must be from 0 to 1, got {}", field("ambient.volume"), ambient.volume));
+                }
+            }
+            if let Some(breathing) = &segment.breathing {
+                if !(breathing.inhale > 0.0 && breathing.exhale > 0.0) {
+                    problems.push(format!("{} needs an inhale and exhale longer than 0", field("breathing")));
+                }
+                if !(breathing.hold >= 0.0 && breathing.rest >= 0.0) {
+                    problems.push(format!("{} can't hold or rest for negative time", field("breathing")));
+                }
+            }
+        }
+        problems
+    }
+}
+
+impl Segment {
+    /// The ambient's level `seconds` in, from the fades.
+    pub fn fade(&self, seconds: f32) -> f32 {
+        let rising = if self.fade_in > 0.0 { seconds / self.fade_in } else { 1.0 };
+        let falling = if self.fade_out > 0.0 { (self.length - seconds) / self.fade_out } else { 1.0 };
+        rising.min(falling).clamp(0.0, 1.0)
+    }
+}
+
+impl Breathing {
+    pub fn period(&self) -> f32 {
+        self.inhale + self.hold + self.exhale + self.rest
+    }
+
+    /// The part of the breath `seconds` in, and the seconds left in it.
+    pub fn at(&self, seconds: f32) -> (Breath, f32) {
+        let mut t = seconds % self.period();
+        for (breath, length) in [
+            (Breath::In, self.inhale),
+            (Breath::Hold, self.hold),
+            (Breath::Out, self.exhale),
+            (Breath::Rest, self.rest),
+        ] {
+            if t < length {
+                return (breath, length - t);
+            }
+            t -= length;
+        }
+        (Breath::Rest, 0.0)
+    }
+
+    /// How full the lungs are `seconds` in, from 0 to 1, eased so the
+    /// animation slows at each turn like a real breath.
+    pub fn fullness(&self, seconds: f32) -> f32 {
+        let (breath, left) = self.at(seconds);
+        let through = |length: f32| 1.0 - left / length;
+        let ease = |t: f32| 0.5 - 0.5 * (std::f32::consts::PI * t).cos();
+        match breath {
+            Breath::In => ease(through(self.inhale)),
+            Breath::Hold => 1.0,
+            Breath::Out => 1.0 - ease(through(self.exhale)),
+            Breath::Rest => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::Parse(e) => write!(f, "{}:{}: {}", e.position.line, e.position.col, e.code),
+            ScriptError::Invalid(problems) => {
+                write!(f, "invalid script:")?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn full_volume() -> f32 {
+    1.0
+}
+
+/// The line each segment's `(` is on, counting from 1. Strings and
+/// comments are skipped, so a bracket in a prompt doesn't throw it off.
+fn segment_lines(text: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let (mut line, mut depth) = (1, 0);
+    let mut word = String::new();
+    let mut in_segments = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\n' => line += 1,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&c| c == '\n');
+                line += 1;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '(' | '[' | '{' => {
+                if c == '(' && in_segments && depth == 2 {
+                    lines.push(line);
+                }
+                if c == '[' && depth == 1 && word == "segments" {
+                    in_segments = true;
+                }
+                depth += 1;
+            }
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 1 {
+                    in_segments = false;
+                }
+            }
+            _ => {}
+        }
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+        } else if !(c.is_whitespace() || c == ':') {
+            word.clear();
+        }
+    }
+    lines
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        match Script::parse(text) {
+            Ok(_) => panic!("parsed:\n{text}"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn problems(text: &str) -> Vec<String> {
+        match Script::parse(text) {
+            Err(ScriptError::Invalid(problems)) => problems,
+            Err(e) => panic!("{e}"),
+            Ok(_) => panic!("parsed:\n{text}"),
+        }
+    }
+
+    #[test]
+    fn every_bundled_script_parses() {
+        assert!(!crate::meditation::BUNDLED.is_empty());
+        for &(name, text) in crate::meditation::BUNDLED {
+            let script = Script::parse(text).unwrap_or_else(|e| panic!("assets/meditations/{name}: {e}"));
+            assert_eq!(segment_lines(text).len(), script.segments.len(), "{name}");
+            assert!(script.length() > 0.0, "{name}");
+        }
+    }
+
+    #[test]
+    fn a_segment_that_isnt_ron_gives_its_line() {
+        let text = "(\n    title: \"Sit\",\n    segments: [\n        (length: 30),\n        (length: \"long\"),\n    ],\n)";
+        assert!(error(text).starts_with("5:"), "{}", error(text));
+        let text = "(\n    title: \"Sit\",\n    segments: [\n        (length: 30),\n        (\n            prompt: Some(\"Breathe\"),\n        ),\n    ],\n)";
+        let e = error(text);
+        assert!(e.starts_with("7:") && e.contains("length"), "{e}");
+    }
+
+    #[test]
+    fn invalid_segments_give_their_lines() {
+        let text = r#"(
+    title: " ",
+    segments: [
+        (length: 30),
+        (
+            length: 0,
+            prompt: Some(""),
+        ),
+        (length: 10, fade_in: 8, fade_out: 4, ambient: Some((source: "rain.ogg", volume: 2))),
+        (length: 10, breathing: Some((inhale: 0, exhale: 4, rest: -1))),
+    ],
+)"#;
+        assert_eq!(problems(text), [
+            "title is empty",
+            "5: segments[1].length must be more than 0 seconds",
+            "5: segments[1].prompt is empty",
+            "9: segments[2].fade_in and fade_out are longer than the segment",
+            "9: segments[2].ambient.volume must be from 0 to 1, got 2",
+            "10: segments[3].breathing needs an inhale and exhale longer than 0",
+            "10: segments[3].breathing can't hold or rest for negative time",
+        ]);
+        assert_eq!(problems("(title: \"Sit\", segments: [])"), ["segments is empty"]);
+    }
+
+    #[test]
+    fn segment_lines_skip_strings_and_comments() {
+        let text = r#"Script(
+    // segments: [(
+    title: "segments: [(",
+    /* (
+       [ */
+    description: "a \"(\"
+(",
+    segments: [
+        (length: 1, prompt: Some("[(")),
+
+        Segment(length: 2), (length: 3)
+    ],
+    other: [(a: 1)],
+)"#;
+        assert_eq!(segment_lines(text), [9, 11, 11]);
+    }
+}
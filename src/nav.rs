@@ -0,0 +1,269 @@
+use eframe::egui;
+
+use crate::layout::Breakpoint;
+use crate::{a11y, i18n, icons, typography, Page};
+
+
+/// Where a page sits in the site. Everything that lists pages — the header,
+/// the footer's Previous/Next, the home page links, the breadcrumb and the
+/// sitemap — reads `PAGES`, so reordering, renaming or hiding a page is one
+/// edit there.
+pub struct PageInfo {
+    pub page: Page,
+    /// Message id of the page's name in `assets/i18n`.
+    pub title_key: &'static str,
+    /// Hidden pages drop out of every list but still open from a link or URL.
+    pub visible: bool,
+    /// The key that jumps here, without modifiers. Hidden pages don't answer
+    /// to theirs.
+    pub shortcut: egui::Key,
+}
+
+pub const PAGES: [PageInfo; 10] = [
+    PageInfo { page: Page::Home, title_key: "page-home", visible: true, shortcut: egui::Key::Num1 },
+    PageInfo { page: Page::AboutUs, title_key: "page-about", visible: true, shortcut: egui::Key::Num2 },
+    PageInfo { page: Page::Timeline, title_key: "page-timeline", visible: true, shortcut: egui::Key::Num3 },
+    PageInfo { page: Page::Deliverables, title_key: "page-deliverables", visible: true, shortcut: egui::Key::Num4 },
+    PageInfo { page: Page::Contact, title_key: "page-contact", visible: true, shortcut: egui::Key::Num5 },
+    PageInfo { page: Page::Focus, title_key: "page-focus", visible: true, shortcut: egui::Key::Num6 },
+    PageInfo { page: Page::Meditate, title_key: "page-meditate", visible: true, shortcut: egui::Key::Num7 },
+    PageInfo { page: Page::Library, title_key: "page-library", visible: true, shortcut: egui::Key::Num8 },
+    PageInfo { page: Page::Social, title_key: "page-social", visible: true, shortcut: egui::Key::Num9 },
+    PageInfo { page: Page::Rooms, title_key: "page-rooms", visible: true, shortcut: egui::Key::Num0 },
+];
+
+impl PageInfo {
+    pub fn title(&self) -> String {
+        i18n::tr(self.title_key)
+    }
+}
+
+/// The visible pages, in order.
+pub fn visible() -> impl Iterator<Item = &'static PageInfo> {
+    PAGES.iter().filter(|info| info.visible)
+}
+
+pub fn info(page: Page) -> &'static PageInfo {
+    PAGES.iter().find(|info| info.page == page).expect("every page is in PAGES")
+}
+
+/// The neighbours of `page` among the visible pages. A hidden page has
+/// neither.
+pub fn neighbours(page: Page) -> (Option<&'static PageInfo>, Option<&'static PageInfo>) {
+    let pages: Vec<_> = visible().collect();
+    match pages.iter().position(|info| info.page == page) {
+        Some(i) => (i.checked_sub(1).map(|i| pages[i]), pages.get(i + 1).copied()),
+        None => (None, None),
+    }
+}
+
+/// The site name, linking home, then a button per visible page that fits in
+/// the header's width less `trailing`, which is kept for the controls after
+/// it. Pages that don't fit go under a "More" menu at the end. Phones only get
+/// the site name; the pages move into `show_menu`.
+pub fn show_header(ui: &mut egui::Ui, current: &mut Page, breakpoint: Breakpoint, trailing: f32) {
+    ui.visuals_mut().button_frame = false;
+    ui.style_mut().spacing.item_spacing = egui::vec2(breakpoint.item_spacing(), 0.0);
+    let pages: Vec<_> = visible().collect();
+    let inline = if breakpoint == Breakpoint::Phone {
+        1
+    } else {
+        let widths: Vec<_> = pages.iter().map(|info| button_width(ui, header_text(info))).collect();
+        let more = button_width(ui, more_text());
+        fitting(&widths, ui.spacing().item_spacing.x, more, ui.available_width() - trailing)
+    };
+    let (shown, overflow) = pages.split_at(inline.min(pages.len()));
+    for &info in shown {
+        let response = ui.button(header_text(info));
+        let response = if info.page == Page::Home {
+            a11y::label(response, &i18n::tr("nav-home-label"))
+        } else {
+            response
+        };
+        let response = match a11y::shortcut_hint(info) {
+            Some(hint) => response.on_hover_text(hint),
+            None => response,
+        };
+        if response.clicked() {
+            *current = info.page;
+        }
+    }
+    if breakpoint != Breakpoint::Phone && !overflow.is_empty() {
+        ui.menu_button(more_text(), |ui| {
+            for &info in overflow {
+                let response = ui.selectable_label(*current == info.page, info.title());
+                let response = match a11y::shortcut_hint(info) {
+                    Some(hint) => response.on_hover_text(hint),
+                    None => response,
+                };
+                if response.clicked() {
+                    *current = info.page;
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+}
+
+/// The site name for Home, which leads the header, and the page's name for
+/// the rest.
+fn header_text(info: &PageInfo) -> egui::RichText {
+    if info.page == Page::Home {
+        egui::RichText::new(i18n::tr("app-name")).heading()
+    } else {
+        egui::RichText::new(info.title())
+    }
+}
+
+fn more_text() -> egui::RichText {
+    egui::RichText::new(format!("{} {}", i18n::tr("nav-more"), icons::CARET_DOWN))
+}
+
+/// How wide a frameless button with `text` is drawn in `ui`.
+pub fn button_width(ui: &egui::Ui, text: egui::RichText) -> f32 {
+    let galley = egui::WidgetText::from(text).into_galley(ui, Some(egui::TextWrapMode::Extend), f32::INFINITY, egui::TextStyle::Button);
+    galley.size().x + 2.0 * ui.spacing().button_padding.x
+}
+
+/// How many of the buttons `widths` fit side by side in `room`, leaving space
+/// for a `more` button when they don't all fit. The first, the site name,
+/// always shows.
+fn fitting(widths: &[f32], spacing: f32, more: f32, room: f32) -> usize {
+    let total: f32 = widths.iter().sum::<f32>() + spacing * widths.len().saturating_sub(1) as f32;
+    if total <= room {
+        return widths.len();
+    }
+    let mut used = more;
+    let mut count = 0;
+    for width in widths {
+        used += width + spacing;
+        if used > room {
+            break;
+        }
+        count += 1;
+    }
+    count.max(1)
+}
+
+/// Previous on the left, Next on the right. Phones drop the words and the
+/// sitemap, which the side menu already covers.
+pub fn show_footer(ui: &mut egui::Ui, current: &mut Page, breakpoint: Breakpoint) {
+    let phone = breakpoint == Breakpoint::Phone;
+    let (previous, next) = neighbours(*current);
+    if let Some(info) = previous {
+        let text = if phone {
+            format!("{} {}", icons::CARET_LEFT, info.title())
+        } else {
+            format!("{} {}", icons::CARET_LEFT, i18n::tr_with("nav-previous", &[("page", info.title().into())]))
+        };
+        if ui.button(text).clicked() {
+            *current = info.page;
+        }
+    }
+    if !phone {
+        ui.menu_button(i18n::tr("nav-sitemap"), |ui| show_sitemap(ui, current));
+    }
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        if let Some(info) = next {
+            let text = if phone {
+                format!("{} {}", info.title(), icons::CARET_RIGHT)
+            } else {
+                format!("{} {}", i18n::tr_with("nav-next", &[("page", info.title().into())]), icons::CARET_RIGHT)
+            };
+            if ui.button(text).clicked() {
+                *current = info.page;
+            }
+        }
+    });
+}
+
+/// The phone header's pages, for the side panel the hamburger opens. Returns
+/// true once a page has been picked.
+pub fn show_menu(ui: &mut egui::Ui, current: &mut Page) -> bool {
+    let mut picked = false;
+    for info in visible() {
+        let text = egui::RichText::new(info.title()).text_style(typography::heading());
+        if ui.selectable_label(*current == info.page, text).clicked() {
+            *current = info.page;
+            picked = true;
+        }
+    }
+    picked
+}
+
+/// A large link to every other visible page, for the home page.
+pub fn show_home_links(ui: &mut egui::Ui, current: &mut Page) {
+    let mut first = true;
+    for info in visible().filter(|info| info.page != Page::Home) {
+        if !first {
+            ui.add_space(29.0);
+        }
+        first = false;
+        if ui.link(egui::RichText::new(info.title()).text_style(typography::heading())).clicked() {
+            *current = info.page;
+        }
+    }
+}
+
+/// "Home › About Us" above a page's title. Nothing on the home page itself.
+pub fn show_breadcrumb(ui: &mut egui::Ui, current: &mut Page) {
+    if *current == Page::Home {
+        return;
+    }
+    ui.horizontal(|ui| {
+        let home = info(Page::Home);
+        if ui.link(egui::RichText::new(home.title()).text_style(typography::caption())).clicked() {
+            *current = Page::Home;
+        }
+        ui.weak(egui::RichText::new(SEPARATOR).text_style(typography::caption()));
+        ui.weak(egui::RichText::new(info(*current).title()).text_style(typography::caption()));
+    });
+}
+
+/// Every visible page with the address it lives at.
+pub fn show_sitemap(ui: &mut egui::Ui, current: &mut Page) {
+    for info in visible() {
+        ui.horizontal(|ui| {
+            if ui.link(info.title()).clicked() {
+                *current = info.page;
+                ui.close_menu();
+            }
+            ui.weak(info.page.fragment());
+        });
+    }
+}
+
+
+const SEPARATOR: &str = "›";
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_page_has_its_own_shortcut() {
+        for (i, info) in PAGES.iter().enumerate() {
+            let clash = PAGES[..i].iter().find(|other| other.shortcut == info.shortcut);
+            assert!(clash.is_none(), "{} and {} both use {:?}", info.title_key, clash.unwrap().title_key, info.shortcut);
+        }
+    }
+
+    #[test]
+    fn every_button_shows_when_there_is_room() {
+        assert_eq!(fitting(&[80.0, 40.0, 40.0], 10.0, 50.0, 180.0), 3);
+        assert_eq!(fitting(&[80.0, 40.0, 40.0], 10.0, 50.0, 1000.0), 3);
+    }
+
+    #[test]
+    fn the_rest_go_under_more() {
+        // 80 + 10 + 40 + 10 and More's 50 is 190; another 50 doesn't fit.
+        assert_eq!(fitting(&[80.0, 40.0, 40.0, 40.0], 10.0, 50.0, 200.0), 2);
+        assert_eq!(fitting(&[80.0, 40.0, 40.0, 40.0], 10.0, 50.0, 189.0), 1);
+    }
+
+    #[test]
+    fn the_site_name_always_shows() {
+        assert_eq!(fitting(&[80.0, 40.0], 10.0, 50.0, 0.0), 1);
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use crate::{i18n, icons};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+#[cfg(not(target_arch = "wasm32"))]
+use native::Document;
+#[cfg(target_arch = "wasm32")]
+use web::Document;
+
+
+/// Whether PDFs can be previewed here: Pdfium on native and pdf.js on the
+/// web are found at run time, so they may be missing.
+pub fn available() -> bool {
+    Document::available()
+}
+
+/// A window that rasterizes one bundled PDF a page at a time. Pages are
+/// rendered the first time they are shown and kept as textures after that.
+pub struct PdfViewer {
+    title: String,
+    asset: String,
+    document: Document,
+    page: usize,
+    textures: HashMap<usize, egui::TextureHandle>,
+}
+
+impl PdfViewer {
+    pub fn open(ctx: &egui::Context, title: &str, asset: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            asset: asset.to_owned(),
+            document: Document::load(ctx, asset),
+            page: 0,
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Returns `false` once the window has been closed.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        egui::Window::new(&self.title)
+            .id(egui::Id::new("pdf_viewer"))
+            .open(&mut open)
+            .default_size(ctx.screen_rect().size() * 0.8)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let page_count = match self.document.page_count() {
+                    Ok(Some(count)) => count,
+                    Ok(None) | Err(_) => 0,
+                };
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.page > 0, egui::Button::new(format!("{} {}", icons::CARET_LEFT, i18n::tr("pdf-previous")))).clicked() {
+                        self.page -= 1;
+                    }
+                    if page_count > 0 {
+                        ui.label(i18n::tr_with("pdf-page", &[("page", (self.page + 1).into()), ("count", page_count.into())]));
+                    }
+                    if ui.add_enabled(self.page + 1 < page_count, egui::Button::new(format!("{} {}", i18n::tr("pdf-next"), icons::CARET_RIGHT))).clicked() {
+                        self.page += 1;
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.link(format!("{} {}", i18n::tr("pdf-open"), icons::ARROW_SQUARE_OUT)).clicked() {
+                            ctx.open_url(egui::OpenUrl::new_tab(crate::content::asset_uri(&self.asset)));
+                        }
+                    });
+                });
+                ui.separator();
+                match self.document.page_count() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        ui.spinner();
+                        return;
+                    }
+                    Err(e) => {
+                        ui.weak(e);
+                        return;
+                    }
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::ArrowRight)) && self.page + 1 < page_count {
+                    self.page += 1;
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::ArrowLeft)) && self.page > 0 {
+                    self.page -= 1;
+                }
+                let texture = match self.textures.get(&self.page) {
+                    Some(texture) => texture.clone(),
+                    None => match self.document.render(self.page, RENDER_WIDTH) {
+                        Some(Ok(image)) => {
+                            let name = format!("{}#{}", self.asset, self.page);
+                            let texture = ctx.load_texture(name, image, egui::TextureOptions::LINEAR);
+                            self.textures.insert(self.page, texture.clone());
+                            texture
+                        }
+                        Some(Err(e)) => {
+                            ui.weak(e);
+                            return;
+                        }
+                        None => {
+                            ui.spinner();
+                            return;
+                        }
+                    },
+                };
+                egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    ui.add(egui::Image::new(&texture).max_width(ui.available_width()));
+                });
+            });
+        open
+    }
+}
+
+
+const RENDER_WIDTH: i32 = 1200;
@@ -0,0 +1,67 @@
+use eframe::egui;
+use pdfium_render::prelude::{PdfDocument, PdfRenderConfig, Pdfium, PdfiumError};
+
+use crate::i18n;
+
+
+/// A PDF opened with Pdfium, which renders pages as soon as they're asked
+/// for.
+pub struct Document(Result<PdfDocument<'static>, String>);
+
+impl Document {
+    pub fn available() -> bool {
+        pdfium().is_ok()
+    }
+
+    pub fn load(_ctx: &egui::Context, asset: &str) -> Self {
+        let failed = |key: &str, e: String| i18n::tr_with(key, &[("asset", asset.into()), ("error", e.into())]);
+        let document = std::fs::read(asset)
+            .map_err(|e| failed("pdf-read-failed", e.to_string()))
+            .and_then(|bytes| {
+                pdfium()?
+                    .load_pdf_from_byte_vec(bytes, None)
+                    .map_err(|e| failed("pdf-open-failed", e.to_string()))
+            });
+        Self(document)
+    }
+
+    /// Never `Ok(None)`: the document is open by the time `load` returns.
+    pub fn page_count(&self) -> Result<Option<usize>, String> {
+        match &self.0 {
+            Ok(document) => Ok(Some(document.pages().len() as usize)),
+            Err(e) => Err(e.clone()),
+        }
+    }
+
+    pub fn render(&self, page: usize, width: i32) -> Option<Result<egui::ColorImage, String>> {
+        let document = self.0.as_ref().ok()?;
+        let failed = |e: PdfiumError| {
+            i18n::tr_with("pdf-render-failed", &[("page", (page + 1).into()), ("error", e.to_string().into())])
+        };
+        let image = document.pages().get(page as u16).and_then(|pdf_page| {
+            let bitmap = pdf_page.render_with_config(&PdfRenderConfig::new().set_target_width(width))?;
+            Ok(bitmap.as_image().into_rgba8())
+        });
+        Some(image.map_err(failed).map(|image| {
+            let size = [image.width() as usize, image.height() as usize];
+            egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw())
+        }))
+    }
+}
+
+/// Pdfium is loaded once, from the working directory or the system library
+/// path, and lives for the rest of the program.
+fn pdfium() -> Result<&'static Pdfium, String> {
+    use std::sync::OnceLock;
+
+    static PDFIUM: OnceLock<Option<Pdfium>> = OnceLock::new();
+    PDFIUM
+        .get_or_init(|| {
+            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
+                .or_else(|_| Pdfium::bind_to_system_library())
+                .map(Pdfium::new)
+                .ok()
+        })
+        .as_ref()
+        .ok_or_else(|| i18n::tr("pdf-needs-pdfium"))
+}
@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use eframe::egui;
+use eframe::wasm_bindgen::{JsCast as _, JsValue};
+use js_sys::{Function, Object, Promise, Reflect};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::{content, i18n};
+
+
+/// A PDF opened with pdf.js, which `index.html` loads. Everything pdf.js
+/// does is asynchronous, so the document and each page turn up a few frames
+/// after they're asked for.
+pub struct Document {
+    ctx: egui::Context,
+    state: Rc<RefCell<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// The `PDFDocumentProxy`, `None` while it opens.
+    document: Option<Result<JsValue, String>>,
+    /// `None` while the page is being drawn. Drawn pages are handed over
+    /// once, since the viewer keeps them as textures.
+    pages: HashMap<usize, Option<Result<egui::ColorImage, String>>>,
+}
+
+impl Document {
+    pub fn available() -> bool {
+        pdfjs().is_some()
+    }
+
+    pub fn load(ctx: &egui::Context, asset: &str) -> Self {
+        let loading = Self { ctx: ctx.clone(), state: Rc::default() };
+        let (url, asset) = (content::asset_uri(asset), asset.to_owned());
+        let (ctx, state) = (loading.ctx.clone(), loading.state.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            let document = open(&url).await.map_err(|e| {
+                i18n::tr_with("pdf-open-failed", &[("asset", asset.into()), ("error", describe(e).into())])
+            });
+            state.borrow_mut().document = Some(document);
+            ctx.request_repaint();
+        });
+        loading
+    }
+
+    pub fn page_count(&self) -> Result<Option<usize>, String> {
+        match &self.state.borrow().document {
+            None => Ok(None),
+            Some(Ok(document)) => {
+                let count = Reflect::get(document, &"numPages".into()).ok().and_then(|count| count.as_f64());
+                Ok(Some(count.unwrap_or(0.0) as usize))
+            }
+            Some(Err(e)) => Err(e.clone()),
+        }
+    }
+
+    pub fn render(&self, page: usize, width: i32) -> Option<Result<egui::ColorImage, String>> {
+        let mut state = self.state.borrow_mut();
+        let document = state.document.clone()?.ok()?;
+        match state.pages.get(&page) {
+            Some(Some(Ok(_))) => return state.pages.remove(&page).flatten(),
+            Some(Some(Err(e))) => return Some(Err(e.clone())),
+            Some(None) => return None,
+            None => {}
+        }
+        state.pages.insert(page, None);
+        let (ctx, state) = (self.ctx.clone(), self.state.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            let image = draw(&document, page, width).await.map_err(|e| {
+                i18n::tr_with("pdf-render-failed", &[("page", (page + 1).into()), ("error", describe(e).into())])
+            });
+            state.borrow_mut().pages.insert(page, Some(image));
+            ctx.request_repaint();
+        });
+        None
+    }
+}
+
+/// The `pdfjsLib` global, unless the script couldn't be loaded (it's only
+/// there once `scripts/fetch-pdfjs.sh` has been run).
+fn pdfjs() -> Option<JsValue> {
+    Reflect::get(&js_sys::global(), &"pdfjsLib".into()).ok().filter(JsValue::is_object)
+}
+
+async fn open(url: &str) -> Result<JsValue, JsValue> {
+    let pdfjs = pdfjs().ok_or_else(|| JsValue::from_str(&i18n::tr("pdf-needs-pdfjs")))?;
+    let task = call(&pdfjs, "getDocument", &[url.into()])?;
+    JsFuture::from(promise(&task)?).await
+}
+
+/// Draws a page onto a canvas of its own, `width` pixels wide, and reads
+/// the pixels back.
+async fn draw(document: &JsValue, page: usize, width: i32) -> Result<egui::ColorImage, JsValue> {
+    let page = JsFuture::from(call(document, "getPage", &[(page + 1).into()])?.dyn_into::<Promise>()?).await?;
+    // At scale 1 the viewport is the page's size in points.
+    let natural = call(&page, "getViewport", &[object(&[("scale", 1.0.into())])?])?;
+    let scale = f64::from(width) / number(&natural, "width")?;
+    let viewport = call(&page, "getViewport", &[object(&[("scale", scale.into())])?])?;
+    let (width, height) = (number(&viewport, "width")?.ceil() as u32, number(&viewport, "height")?.ceil() as u32);
+
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?
+        .create_element("canvas")?
+        .dyn_into()?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("no 2D canvas context"))?
+        .dyn_into()?;
+    let task = call(&page, "render", &[object(&[("canvasContext", context.clone().into()), ("viewport", viewport)])?])?;
+    JsFuture::from(promise(&task)?).await?;
+
+    let pixels = context.get_image_data(0.0, 0.0, f64::from(width), f64::from(height))?.data();
+    Ok(egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels.0))
+}
+
+/// `target.name(...args)`.
+fn call(target: &JsValue, name: &str, args: &[JsValue]) -> Result<JsValue, JsValue> {
+    let method: Function = Reflect::get(target, &name.into())?.dyn_into()?;
+    method.apply(target, &args.iter().collect())
+}
+
+/// The `promise` of a pdf.js loading or render task.
+fn promise(task: &JsValue) -> Result<Promise, JsValue> {
+    Reflect::get(task, &"promise".into())?.dyn_into()
+}
+
+fn number(target: &JsValue, name: &str) -> Result<f64, JsValue> {
+    Reflect::get(target, &name.into())?
+        .as_f64()
+        .ok_or_else(|| JsValue::from_str(&format!("{name} isn't a number")))
+}
+
+fn object(fields: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    let object = Object::new();
+    for (key, value) in fields {
+        Reflect::set(&object, &(*key).into(), value)?;
+    }
+    Ok(object.into())
+}
+
+fn describe(error: JsValue) -> String {
+    error.as_string().unwrap_or_else(|| format!("{error:?}"))
+}
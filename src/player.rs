@@ -0,0 +1,380 @@
+use std::time::Duration;
+
+use eframe::egui;
+use plistr_core::{decode_text, Playlist, PlaylistFormat, PlaylistId, Track, TrackId, UserId};
+
+use crate::audio::{self, Playback};
+use crate::layout::Breakpoint;
+use crate::{a11y, content, i18n, icons};
+
+
+/// Queues any audio files dropped on the window, and the tracks of any
+/// playlist files, starting playback if nothing was playing.
+pub fn accept_dropped(ctx: &egui::Context, player: &dyn Playback) {
+    let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+    let mut tracks = Vec::new();
+    for file in &dropped {
+        let Some(name) = dropped_name(file) else {
+            continue;
+        };
+        match PlaylistFormat::from_path(&name) {
+            Some(format) => match dropped_playlist(file, format) {
+                Ok(playlist) => {
+                    report(ctx, Ok(i18n::tr_with("player-imported", &[
+                        ("count", playlist.len().into()),
+                        ("name", name.as_str().into()),
+                    ])));
+                    tracks.extend_from_slice(playlist.tracks());
+                }
+                Err(e) => report(ctx, Err(import_failed(&name, &e))),
+            },
+            None => tracks.extend(dropped_track(file, &name)),
+        }
+    }
+    enqueue(player, tracks);
+}
+
+/// Adds `tracks` to the end of the queue, starting playback if nothing was
+/// playing.
+pub fn enqueue(player: &dyn Playback, tracks: Vec<Track>) {
+    if tracks.is_empty() {
+        return;
+    }
+    let idle = !player.status().playing;
+    for track in tracks {
+        player.enqueue(track);
+    }
+    if idle {
+        player.play();
+    }
+}
+
+/// The transport bar: skip and play buttons, what's playing with a seek
+/// slider, the queue and the volume.
+pub fn show_player(ui: &mut egui::Ui, player: &dyn Playback, breakpoint: Breakpoint) {
+    let status = player.status();
+    if status.playing {
+        // Keeps the position moving without repainting every frame.
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
+    }
+    let has_queue = !status.queue.is_empty();
+    let back = ui.add_enabled(status.current.is_some(), egui::Button::new(icons::SKIP_BACK).frame(false));
+    if a11y::label(back, &i18n::tr("player-previous")).clicked() {
+        player.previous();
+    }
+    let (icon, name) = if status.playing {
+        (icons::PAUSE, "player-pause")
+    } else {
+        (icons::PLAY, "player-play")
+    };
+    let play = ui.add_enabled(has_queue, egui::Button::new(icon).frame(false));
+    if a11y::label(play, &i18n::tr(name)).clicked() {
+        player.toggle();
+    }
+    let next = ui.add_enabled(status.current.is_some(), egui::Button::new(icons::SKIP_FORWARD).frame(false));
+    if a11y::label(next, &i18n::tr("player-next")).clicked() {
+        player.next();
+    }
+
+    let queue = ui.menu_button(icons::LIST, |ui| show_queue(ui, player, &status));
+    a11y::label(queue.response, &i18n::tr_with("player-queue", &[("count", status.queue.len().into())]));
+
+    match status.current.and_then(|i| status.queue.get(i)) {
+        _ if status.locked && status.playing => {
+            ui.weak(i18n::tr("player-locked"));
+        }
+        Some(track) => {
+            ui.strong(&track.title);
+            show_seek(ui, player, status.position, status.duration, breakpoint);
+        }
+        None => {
+            ui.weak(status.error.as_deref().map_or_else(|| i18n::tr("player-empty"), str::to_owned));
+        }
+    }
+
+    if breakpoint > Breakpoint::Phone {
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let mut volume = status.volume;
+            let slider = ui.add(egui::Slider::new(&mut volume, 0.0..=1.0).show_value(false));
+            if slider.on_hover_text(i18n::tr("player-volume")).changed() {
+                player.set_volume(volume);
+            }
+            ui.label(icons::SPEAKER_HIGH)
+                .on_hover_text(i18n::tr_with("player-output", &[("name", status.output.as_str().into())]));
+        });
+    }
+}
+
+fn show_seek(ui: &mut egui::Ui, player: &dyn Playback, position: Duration, duration: Option<Duration>, breakpoint: Breakpoint) {
+    let Some(duration) = duration else {
+        ui.weak(clock(position));
+        return;
+    };
+    // While dragging the slider shows where it will seek to rather than
+    // snapping back to the playing position each frame.
+    let id = ui.id().with("seek");
+    let dragging = ui.data(|d| d.get_temp::<f32>(id));
+    let mut seconds = dragging.unwrap_or(position.as_secs_f32());
+    ui.weak(clock(Duration::from_secs_f32(seconds)));
+    let width = if breakpoint == Breakpoint::Phone { 89.0 } else { 239.0 };
+    ui.spacing_mut().slider_width = width;
+    let slider = ui.add(egui::Slider::new(&mut seconds, 0.0..=duration.as_secs_f32()).show_value(false));
+    let slider = slider.on_hover_text(i18n::tr("player-seek"));
+    if slider.dragged() {
+        ui.data_mut(|d| d.insert_temp(id, seconds));
+    } else if slider.drag_stopped() || slider.changed() {
+        ui.data_mut(|d| d.remove::<f32>(id));
+        player.seek(Duration::from_secs_f32(seconds));
+    }
+    ui.weak(clock(duration));
+}
+
+fn show_queue(ui: &mut egui::Ui, player: &dyn Playback, status: &audio::Status) {
+    ui.weak(i18n::tr_with("player-queue", &[("count", status.queue.len().into())]));
+    if status.queue.is_empty() {
+        ui.label(i18n::tr("player-empty"));
+        ui.separator();
+        show_playlist_file(ui, player, status);
+        return;
+    }
+    egui::ScrollArea::vertical().max_height(311.0).show(ui, |ui| {
+        for (index, track) in status.queue.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let remove = ui.add(egui::Button::new(icons::X).frame(false));
+                if a11y::label(remove, &i18n::tr("player-remove")).clicked() {
+                    player.remove(index);
+                }
+                let current = status.current == Some(index);
+                if ui.selectable_label(current, &track.title).clicked() {
+                    player.jump(index);
+                }
+                if let Some(duration) = track.duration {
+                    ui.weak(clock(duration));
+                }
+            });
+        }
+    });
+    ui.separator();
+    if let Some(crossfade) = status.crossfade {
+        let mut seconds = crossfade.as_secs_f32();
+        ui.weak(i18n::tr("player-crossfade"));
+        let slider = egui::Slider::new(&mut seconds, 0.0..=CROSSFADE_MAX).suffix(" s").step_by(0.5);
+        if ui.add(slider).changed() {
+            player.set_crossfade(Duration::from_secs_f32(seconds));
+        }
+        ui.separator();
+    }
+    show_playlist_file(ui, player, status);
+    ui.separator();
+    if ui.button(i18n::tr("player-clear")).clicked() {
+        player.clear();
+        ui.close_menu();
+    }
+}
+
+/// Imports a playlist file into the queue, or exports the queue to one, in
+/// whichever format the path's extension names.
+#[cfg(not(target_arch = "wasm32"))]
+fn show_playlist_file(ui: &mut egui::Ui, player: &dyn Playback, status: &audio::Status) {
+    let id = egui::Id::new("playlist-path");
+    let mut path = ui.data_mut(|d| d.get_persisted_mut_or_default::<String>(id).clone());
+    ui.weak(i18n::tr("player-playlist-file"));
+    let field = egui::TextEdit::singleline(&mut path).hint_text("playlist.m3u8");
+    if ui.add(field).changed() {
+        ui.data_mut(|d| d.insert_persisted(id, path.clone()));
+    }
+    let format = PlaylistFormat::from_path(&path);
+    ui.horizontal(|ui| {
+        if ui.add_enabled(format.is_some(), egui::Button::new(i18n::tr("player-import"))).clicked() {
+            if let Some(format) = format {
+                let outcome = import(player, &path, format).map(|count| {
+                    i18n::tr_with("player-imported", &[("count", count.into()), ("name", path.as_str().into())])
+                });
+                report(ui.ctx(), outcome.map_err(|e| import_failed(&path, &e)));
+            }
+        }
+        let export_button = egui::Button::new(i18n::tr("player-export"));
+        if ui.add_enabled(format.is_some() && !status.queue.is_empty(), export_button).clicked() {
+            if let Some(format) = format {
+                let outcome = export(&status.queue, &path, format);
+                report(ui.ctx(), match outcome {
+                    Ok(()) => Ok(i18n::tr_with("player-exported", &[("path", path.as_str().into())])),
+                    Err(e) => Err(i18n::tr_with("player-export-failed", &[
+                        ("path", path.as_str().into()),
+                        ("error", e.as_str().into()),
+                    ])),
+                });
+            }
+        }
+    });
+    if format.is_none() && !path.trim().is_empty() {
+        ui.weak(i18n::tr_with("player-playlist-formats", &[("formats", format_names().into())]));
+    }
+    show_report(ui);
+}
+
+/// Browsers can't be handed a path, so playlists come in by dropping them
+/// on the window and go out as downloads.
+#[cfg(target_arch = "wasm32")]
+fn show_playlist_file(ui: &mut egui::Ui, _player: &dyn Playback, status: &audio::Status) {
+    ui.weak(i18n::tr_with("player-playlist-drop", &[("formats", format_names().into())]));
+    ui.add_enabled_ui(!status.queue.is_empty(), |ui| {
+        ui.menu_button(i18n::tr("player-export"), |ui| {
+            for format in PlaylistFormat::ALL {
+                if ui.button(format.name()).clicked() {
+                    let name = format!("{}.{}", i18n::tr("player-export-name"), format.extension());
+                    let text = format.write(&queue_playlist(&status.queue, &name), "");
+                    if download(&name, &text).is_none() {
+                        report(ui.ctx(), Err(i18n::tr("player-download-failed")));
+                    }
+                    ui.close_menu();
+                }
+            }
+        });
+    });
+    show_report(ui);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn import(player: &dyn Playback, path: &str, format: PlaylistFormat) -> Result<usize, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let playlist = read_playlist(format, &decode_text(&bytes), &folder(path)).map_err(|e| e.to_string())?;
+    let count = playlist.len();
+    enqueue(player, playlist.tracks().to_vec());
+    Ok(count)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export(queue: &[Track], path: &str, format: PlaylistFormat) -> Result<(), String> {
+    let playlist = queue_playlist(queue, path);
+    std::fs::write(path, format.write(&playlist, &folder(path))).map_err(|e| e.to_string())
+}
+
+/// The folder a playlist file's relative paths start from.
+#[cfg(not(target_arch = "wasm32"))]
+fn folder(path: &str) -> String {
+    let parent = std::path::Path::new(path).parent();
+    parent.map(|parent| parent.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Saves the file in the browser's downloads by clicking a link to it.
+#[cfg(target_arch = "wasm32")]
+fn download(name: &str, text: &str) -> Option<()> {
+    use eframe::wasm_bindgen::JsCast as _;
+
+    let parts = js_sys::Array::of1(&text.into());
+    let blob = web_sys::Blob::new_with_str_sequence(&parts).ok()?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).ok()?;
+    let document = web_sys::window()?.document()?;
+    let link: web_sys::HtmlAnchorElement = document.create_element("a").ok()?.dyn_into().ok()?;
+    link.set_href(&url);
+    link.set_download(name);
+    link.click();
+    web_sys::Url::revoke_object_url(&url).ok()
+}
+
+/// The queue as a playlist named after the file it's going to.
+fn queue_playlist(queue: &[Track], path: &str) -> Playlist {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let name = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    let mut playlist = Playlist::new(PlaylistId(0), UserId(0), name);
+    for track in queue {
+        playlist.add(track.clone());
+    }
+    playlist
+}
+
+fn read_playlist(format: PlaylistFormat, text: &str, base: &str) -> Result<Playlist, plistr_core::FormatError> {
+    let mut playlist = Playlist::new(PlaylistId(0), UserId(0), "");
+    format.read(text, base, &mut playlist, TrackId::unstored)?;
+    Ok(playlist)
+}
+
+fn dropped_playlist(file: &egui::DroppedFile, format: PlaylistFormat) -> Result<Playlist, String> {
+    let bytes = match (&file.path, &file.bytes) {
+        (_, Some(bytes)) => bytes.to_vec(),
+        #[cfg(not(target_arch = "wasm32"))]
+        (Some(path), None) => std::fs::read(path).map_err(|e| e.to_string())?,
+        _ => return Err(i18n::tr("player-import-unreadable")),
+    };
+    // A dropped file on the web has no folder, so only absolute entries
+    // will play.
+    let base = match &file.path {
+        Some(path) => path.parent().map(|parent| parent.to_string_lossy().into_owned()).unwrap_or_default(),
+        None => String::new(),
+    };
+    read_playlist(format, &decode_text(&bytes), &base).map_err(|e| e.to_string())
+}
+
+fn import_failed(name: &str, error: &str) -> String {
+    i18n::tr_with("player-import-failed", &[("name", name.into()), ("error", error.into())])
+}
+
+/// Keeps the outcome of the last import or export to show in the queue,
+/// and logs failures.
+fn report(ctx: &egui::Context, outcome: Result<String, String>) {
+    if let Err(problem) = &outcome {
+        content::warn(problem);
+    }
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new("playlist-report"), outcome));
+}
+
+fn show_report(ui: &mut egui::Ui) {
+    match ui.data(|d| d.get_temp::<Result<String, String>>(egui::Id::new("playlist-report"))) {
+        Some(Ok(message)) => {
+            ui.weak(message);
+        }
+        Some(Err(problem)) => {
+            ui.colored_label(ui.visuals().error_fg_color, problem);
+        }
+        None => {}
+    }
+}
+
+/// "M3U, M3U8, PLS, XSPF", for hints.
+fn format_names() -> String {
+    PlaylistFormat::ALL.map(PlaylistFormat::name).join(", ")
+}
+
+fn dropped_name(file: &egui::DroppedFile) -> Option<String> {
+    match &file.path {
+        Some(path) => Some(path.file_name()?.to_string_lossy().into_owned()),
+        None => Some(file.name.clone()),
+    }
+}
+
+fn dropped_track(file: &egui::DroppedFile, name: &str) -> Option<Track> {
+    let (title, extension) = name.rsplit_once('.')?;
+    if !audio::EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+        return None;
+    }
+    Some(Track::new(TrackId::unstored(), title, dropped_source(file)?))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn dropped_source(file: &egui::DroppedFile) -> Option<String> {
+    Some(file.path.as_ref()?.to_string_lossy().into_owned())
+}
+
+/// Browsers give dropped files as bytes, which the player fetches back
+/// through a `blob:` URL like any other track.
+#[cfg(target_arch = "wasm32")]
+fn dropped_source(file: &egui::DroppedFile) -> Option<String> {
+    let bytes = js_sys::Uint8Array::from(&file.bytes.as_ref()?[..]);
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&js_sys::Array::of1(&bytes)).ok()?;
+    web_sys::Url::create_object_url_with_blob(&blob).ok()
+}
+
+/// `m:ss`, or `h:mm:ss` for long tracks.
+pub fn clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+
+const CROSSFADE_MAX: f32 = 12.0;
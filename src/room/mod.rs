@@ -0,0 +1,418 @@
+//! Study rooms: join a room on a relay and the player follows the host's
+//! queue, play, pause and seeks, with the room's focus timer alongside.
+//! Tracks are played from the host's `source`s, so members need the same
+//! files at the same paths, or tracks with URLs.
+
+use std::time::Duration;
+
+use eframe::egui;
+use plistr_core::{ClientMessage, Command, MemberId, Phase, RoomClient, RoomState, ServerMessage, TimerCommand};
+
+use crate::audio::Playback;
+use crate::focus::{self, Focus};
+use crate::{i18n, player, typography};
+
+mod socket;
+
+
+pub struct RoomView {
+    url: String,
+    room: String,
+    name: String,
+    socket: Option<socket::Socket>,
+    client: RoomClient,
+    /// Local time is milliseconds since this.
+    started: web_time::Instant,
+    pings: u32,
+    next_ping: u64,
+    /// The sources of the room's queue as last put in the player. The
+    /// player only gets the room's queue again when this changes, so the
+    /// host can line up a new one before sharing it.
+    applied: Vec<String>,
+    /// The player's status lags behind what it's told, so it's left alone
+    /// for a moment after each correction.
+    settled_at: u64,
+    last_phase: Option<Phase>,
+    error: Option<String>,
+}
+
+impl RoomView {
+    pub fn new() -> Self {
+        Self {
+            url: DEFAULT_RELAY.to_owned(),
+            room: String::new(),
+            name: String::new(),
+            socket: None,
+            client: RoomClient::new(),
+            started: web_time::Instant::now(),
+            pings: 0,
+            next_ping: 0,
+            applied: Vec::new(),
+            settled_at: 0,
+            last_phase: None,
+            error: None,
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Call every frame, whichever page is showing, so the music keeps up
+    /// with the room.
+    pub fn update(&mut self, ctx: &egui::Context, player: Option<&dyn Playback>) {
+        let Some(events) = self.socket.as_ref().map(socket::Socket::poll) else {
+            return;
+        };
+        let now = self.now();
+        let mut closed = None;
+        for event in events {
+            match event {
+                socket::Event::Open => {
+                    let join = ClientMessage::Join { room: self.room.trim().to_owned(), name: self.name.trim().to_owned() };
+                    self.send_message(join);
+                }
+                socket::Event::Message(text) => match ServerMessage::from_json(&text) {
+                    Ok(message) => {
+                        if let Some(reason) = self.client.handle(message, self.now()) {
+                            self.error = Some(i18n::tr_with("rooms-rejected", &[("reason", reason.into())]));
+                        }
+                    }
+                    Err(e) => crate::content::warn(&e.to_string()),
+                },
+                socket::Event::Closed(reason) => closed = Some(reason),
+            }
+        }
+        if let Some(reason) = closed {
+            let key = if self.client.state().is_some() { "rooms-lost" } else { "rooms-failed" };
+            let reason = reason.unwrap_or_else(|| i18n::tr("rooms-closed"));
+            self.error = Some(i18n::tr_with(key, &[("error", reason.into())]));
+            self.disconnect();
+            return;
+        }
+        if self.client.state().is_none() {
+            return;
+        }
+        if now >= self.next_ping {
+            // A few quick ones to settle the clock, then just enough to
+            // follow it.
+            self.pings += 1;
+            self.next_ping = now + if self.pings < QUICK_PINGS { 300 } else { 2000 };
+            self.send_message(RoomClient::ping(now));
+        }
+        if let Some(player) = player {
+            self.follow(player, now);
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    /// Brings the player into line with the room.
+    fn follow(&mut self, player: &dyn Playback, now: u64) {
+        let (Some(state), Some(room)) = (self.client.state(), self.client.playing_at(now)) else {
+            return;
+        };
+        let timer = self.client.timer_at(now).map(|timer| timer.phase());
+        if self.last_phase.is_some() && timer != self.last_phase {
+            player.chime();
+        }
+        self.last_phase = timer;
+        let sources = sources(&state.queue);
+        if sources != self.applied {
+            player.clear();
+            for track in &state.queue {
+                player.enqueue(track.clone());
+            }
+            self.applied = sources;
+            self.settled_at = now + SETTLE;
+            return;
+        }
+        let status = player.status();
+        if now < self.settled_at || !self.following(&status.queue) {
+            return;
+        }
+        let Some(index) = room.current else {
+            if status.playing {
+                player.pause();
+            }
+            return;
+        };
+        let drift = status.position.abs_diff(room.position);
+        if status.current != Some(index) {
+            player.jump(index);
+            player.seek(room.position);
+            if !room.playing {
+                player.pause();
+            }
+        } else if room.playing != status.playing {
+            player.seek(room.position);
+            if room.playing {
+                player.play();
+            } else {
+                player.pause();
+            }
+        } else if drift > MAX_DRIFT {
+            player.seek(room.position);
+        } else {
+            return;
+        }
+        self.settled_at = now + SETTLE;
+    }
+
+    /// Whether the player still has the room's queue.
+    fn following(&self, queue: &[plistr_core::Track]) -> bool {
+        sources(queue) == self.applied
+    }
+
+    fn join(&mut self, ctx: &egui::Context) {
+        self.disconnect();
+        self.error = None;
+        self.socket = Some(socket::Socket::connect(ctx, self.url.trim()));
+    }
+
+    fn leave(&mut self) {
+        self.send_message(ClientMessage::Leave);
+        self.disconnect();
+    }
+
+    fn disconnect(&mut self) {
+        self.socket = None;
+        self.client = RoomClient::new();
+        self.pings = 0;
+        self.applied.clear();
+        self.last_phase = None;
+    }
+
+    fn send(&mut self, command: Command) {
+        self.error = None;
+        self.send_message(ClientMessage::Command(command));
+    }
+
+    fn send_message(&self, message: ClientMessage) {
+        if let Some(socket) = &self.socket {
+            socket.send(message.to_json());
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>, focus: &Focus) {
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let Some(state) = self.client.state().cloned() else {
+            self.show_join(ui);
+            return;
+        };
+        let now = self.now();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(&state.name).text_style(typography::title()));
+            if ui.button(i18n::tr("rooms-leave")).clicked() {
+                self.leave();
+            }
+        });
+        let clock = self.client.clock();
+        match (clock.offset(), clock.round_trip()) {
+            (Some(offset), Some(round_trip)) => ui.weak(i18n::tr_with("rooms-clock", &[
+                ("offset", offset.into()),
+                ("round_trip", round_trip.into()),
+            ])),
+            _ => ui.weak(i18n::tr("rooms-clock-unknown")),
+        };
+        let host = self.client.is_host();
+        if !host {
+            ui.weak(i18n::tr("rooms-guest-hint"));
+        }
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("rooms-members"));
+        ui.separator();
+        self.show_members(ui, &state, host);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("rooms-music"));
+        ui.separator();
+        self.show_music(ui, &state, player, host, now);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("rooms-timer"));
+        ui.separator();
+        self.show_timer(ui, focus, host, now);
+    }
+
+    fn show_join(&mut self, ui: &mut egui::Ui) {
+        ui.label(i18n::tr("rooms-intro"));
+        ui.add_space(11.0);
+        let connecting = self.socket.is_some();
+        egui::Grid::new("rooms join").num_columns(2).spacing([19.0, 7.0]).show(ui, |ui| {
+            ui.label(i18n::tr("rooms-relay"));
+            ui.add_enabled(!connecting, egui::TextEdit::singleline(&mut self.url));
+            ui.end_row();
+            ui.label(i18n::tr("rooms-room"));
+            ui.add_enabled(!connecting, egui::TextEdit::singleline(&mut self.room));
+            ui.end_row();
+            ui.label(i18n::tr("rooms-your-name"));
+            ui.add_enabled(!connecting, egui::TextEdit::singleline(&mut self.name));
+            ui.end_row();
+        });
+        if connecting {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(i18n::tr("rooms-connecting"));
+                if ui.button(i18n::tr("rooms-cancel")).clicked() {
+                    self.disconnect();
+                }
+            });
+        } else {
+            let ready = !self.room.trim().is_empty() && !self.url.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new(i18n::tr("rooms-join"))).clicked() {
+                self.join(ui.ctx());
+            }
+        }
+    }
+
+    fn show_members(&mut self, ui: &mut egui::Ui, state: &RoomState, host: bool) {
+        let me = self.client.me();
+        let mut make_host: Option<MemberId> = None;
+        egui::Grid::new("rooms members").num_columns(2).spacing([19.0, 7.0]).striped(true).show(ui, |ui| {
+            for member in &state.members {
+                ui.horizontal(|ui| {
+                    ui.strong(&member.name);
+                    if member.id == state.host {
+                        ui.weak(i18n::tr("rooms-host"));
+                    }
+                    if Some(member.id) == me {
+                        ui.weak(i18n::tr("rooms-you"));
+                    }
+                });
+                if host && member.id != state.host {
+                    if ui.button(i18n::tr("rooms-make-host")).clicked() {
+                        make_host = Some(member.id);
+                    }
+                } else {
+                    ui.label("");
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(member) = make_host {
+            self.send(Command::MakeHost(member));
+        }
+    }
+
+    fn show_music(&mut self, ui: &mut egui::Ui, state: &RoomState, player: Option<&dyn Playback>, host: bool, now: u64) {
+        let Some(room) = self.client.playing_at(now) else {
+            return;
+        };
+        match room.current.and_then(|index| Some((index, state.queue.get(index)?))) {
+            Some((index, track)) => {
+                ui.strong(&track.title);
+                ui.label(i18n::tr_with("rooms-track", &[
+                    ("number", (index + 1).into()),
+                    ("count", state.queue.len().into()),
+                ]));
+                ui.horizontal(|ui| {
+                    let duration = track.duration.map(|duration| format!(" / {}", player::clock(duration)));
+                    ui.weak(format!("{}{}", player::clock(room.position), duration.unwrap_or_default()));
+                    if !room.playing {
+                        ui.weak(i18n::tr("rooms-paused"));
+                    }
+                });
+                if host {
+                    self.show_transport(ui, room.playing, room.position, track.duration);
+                }
+            }
+            None => {
+                ui.weak(i18n::tr("rooms-nothing"));
+            }
+        }
+        let Some(player) = player else {
+            ui.weak(i18n::tr("rooms-no-player"));
+            return;
+        };
+        let queue = player.status().queue;
+        if !self.following(&queue) {
+            ui.add_space(11.0);
+            ui.weak(i18n::tr("rooms-queue-differs"));
+            ui.horizontal_wrapped(|ui| {
+                if host && !queue.is_empty() && ui.button(i18n::tr("rooms-share-queue")).clicked() {
+                    self.send(Command::SetQueue(queue));
+                }
+                if ui.button(i18n::tr("rooms-follow")).clicked() {
+                    // Puts the room's queue back in on the next update.
+                    self.applied.clear();
+                }
+            });
+        } else if host {
+            ui.add_space(11.0);
+            ui.weak(i18n::tr("rooms-host-hint"));
+        }
+    }
+
+    fn show_transport(&mut self, ui: &mut egui::Ui, playing: bool, position: Duration, duration: Option<Duration>) {
+        ui.horizontal(|ui| {
+            let key = if playing { "rooms-pause" } else { "rooms-play" };
+            if ui.button(i18n::tr(key)).clicked() {
+                self.send(if playing { Command::Pause } else { Command::Play });
+            }
+            if ui.button(i18n::tr("rooms-next")).clicked() {
+                self.send(Command::Next);
+            }
+            let Some(duration) = duration else {
+                return;
+            };
+            // While dragging the slider shows where it will seek to rather
+            // than following the room.
+            let id = ui.id().with("rooms seek");
+            let dragging = ui.data(|d| d.get_temp::<f32>(id));
+            let mut seconds = dragging.unwrap_or(position.as_secs_f32());
+            let slider = ui.add(egui::Slider::new(&mut seconds, 0.0..=duration.as_secs_f32()).show_value(false));
+            let slider = slider.on_hover_text(i18n::tr("player-seek"));
+            if slider.dragged() {
+                ui.data_mut(|d| d.insert_temp(id, seconds));
+            } else if slider.drag_stopped() || slider.changed() {
+                ui.data_mut(|d| d.remove::<f32>(id));
+                self.send(Command::Seek(Duration::from_secs_f32(seconds)));
+            }
+        });
+    }
+
+    fn show_timer(&mut self, ui: &mut egui::Ui, focus: &Focus, host: bool, now: u64) {
+        let Some(timer) = self.client.timer_at(now) else {
+            return;
+        };
+        ui.label(egui::RichText::new(focus::phase_name(timer.phase())).text_style(typography::title()));
+        ui.label(egui::RichText::new(focus::countdown(timer.remaining())).text_style(typography::display()));
+        ui.add(egui::ProgressBar::new(timer.progress()).desired_width(311.0));
+        ui.weak(i18n::tr_with("focus-completed", &[("count", timer.completed().into())]));
+        if !host {
+            return;
+        }
+        ui.add_space(11.0);
+        let running = timer.is_running();
+        ui.horizontal_wrapped(|ui| {
+            let key = if running { "focus-pause" } else { "focus-start" };
+            if ui.button(i18n::tr(key)).clicked() {
+                self.send(Command::Timer(if running { TimerCommand::Pause } else { TimerCommand::Start }));
+            }
+            if ui.button(i18n::tr("focus-skip")).clicked() {
+                self.send(Command::Timer(TimerCommand::Skip));
+            }
+            if ui.button(i18n::tr("focus-reset")).clicked() {
+                self.send(Command::Timer(TimerCommand::Reset));
+            }
+            let settings = focus.settings();
+            if settings != timer.settings() && ui.button(i18n::tr("rooms-use-lengths")).clicked() {
+                self.send(Command::Timer(TimerCommand::Settings(settings)));
+            }
+        });
+    }
+}
+
+fn sources(queue: &[plistr_core::Track]) -> Vec<String> {
+    queue.iter().map(|track| track.source.clone()).collect()
+}
+
+
+const DEFAULT_RELAY: &str = "ws://127.0.0.1:9001";
+/// Pings sent 300 ms apart after joining, before slowing to one every two
+/// seconds.
+const QUICK_PINGS: u32 = 5;
+/// Milliseconds the player is given to catch up with a correction.
+const SETTLE: u64 = 1000;
+/// How far the player may stray from the room before it's sought back.
+const MAX_DRIFT: Duration = Duration::from_millis(750);
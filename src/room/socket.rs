@@ -0,0 +1,174 @@
+//! A WebSocket carrying text messages: tungstenite on a thread of its own
+//! for the desktop app, the browser's own `WebSocket` for the web.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc;
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use eframe::egui;
+#[cfg(target_arch = "wasm32")]
+use eframe::wasm_bindgen::{closure::Closure, JsCast as _};
+
+
+pub enum Event {
+    Open,
+    Message(String),
+    /// With the reason, if it wasn't closed on purpose.
+    Closed(Option<String>),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Socket {
+    outgoing: mpsc::Sender<String>,
+    incoming: mpsc::Receiver<Event>,
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct Socket {
+    /// `None` if the address was refused outright.
+    socket: Option<web_sys::WebSocket>,
+    incoming: Rc<RefCell<VecDeque<Event>>>,
+    /// Kept for as long as the socket, which calls them.
+    _handlers: Vec<Closure<dyn FnMut(web_sys::Event)>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Socket {
+    /// Connects in the background; `Event::Open` says when it has.
+    pub fn connect(ctx: &egui::Context, url: &str) -> Self {
+        let (outgoing, outbox) = mpsc::channel();
+        let (events, incoming) = mpsc::channel();
+        let ctx = ctx.clone();
+        let url = url.to_owned();
+        std::thread::spawn(move || {
+            let reason = run(&url, &outbox, &events, &ctx).err();
+            let _ = events.send(Event::Closed(reason));
+            ctx.request_repaint();
+        });
+        Self { outgoing, incoming }
+    }
+
+    /// Sent once the socket is open, in order.
+    pub fn send(&self, text: String) {
+        let _ = self.outgoing.send(text);
+    }
+
+    pub fn poll(&self) -> Vec<Event> {
+        self.incoming.try_iter().collect()
+    }
+}
+
+/// Runs the connection until either end closes it. Dropping the `Socket`
+/// closes it from this end.
+#[cfg(not(target_arch = "wasm32"))]
+fn run(url: &str, outbox: &mpsc::Receiver<String>, events: &mpsc::Sender<Event>, ctx: &egui::Context) -> Result<(), String> {
+    use tungstenite::stream::MaybeTlsStream;
+    use tungstenite::Message;
+
+    let (mut socket, _) = tungstenite::connect(url).map_err(|e| e.to_string())?;
+    match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(POLL)).map_err(|e| e.to_string())?,
+        _ => return Err("only ws:// addresses are supported".to_owned()),
+    }
+    let _ = events.send(Event::Open);
+    ctx.request_repaint();
+    loop {
+        loop {
+            match outbox.try_recv() {
+                Ok(text) => socket.send(Message::text(text)).map_err(|e| e.to_string())?,
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.flush();
+                    return Ok(());
+                }
+            }
+        }
+        match socket.read() {
+            Ok(message) if message.is_text() => {
+                let text = message.into_text().map_err(|e| e.to_string())?;
+                let _ = events.send(Event::Message(text));
+                ctx.request_repaint();
+            }
+            Ok(message) if message.is_close() => return Err("the relay closed the connection".to_owned()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Socket {
+    /// Connects in the background; `Event::Open` says when it has.
+    pub fn connect(ctx: &egui::Context, url: &str) -> Self {
+        let incoming = Rc::new(RefCell::new(VecDeque::new()));
+        let socket = match web_sys::WebSocket::new(url) {
+            Ok(socket) => socket,
+            Err(e) => {
+                incoming.borrow_mut().push_back(Event::Closed(Some(e.as_string().unwrap_or_else(|| format!("{e:?}")))));
+                return Self { socket: None, incoming, _handlers: Vec::new() };
+            }
+        };
+        let handler = |to_event: fn(web_sys::Event) -> Event| {
+            let incoming = incoming.clone();
+            let ctx = ctx.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event| {
+                incoming.borrow_mut().push_back(to_event(event));
+                ctx.request_repaint();
+            })
+        };
+        let handlers = vec![
+            handler(|_| Event::Open),
+            handler(|event| {
+                let event: web_sys::MessageEvent = event.unchecked_into();
+                Event::Message(event.data().as_string().unwrap_or_default())
+            }),
+            handler(|event| {
+                let event: web_sys::CloseEvent = event.unchecked_into();
+                Event::Closed((!event.was_clean()).then(|| format!("closed with code {}", event.code())))
+            }),
+            handler(|_| Event::Closed(Some("couldn't connect".to_owned()))),
+        ];
+        socket.set_onopen(Some(handlers[0].as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(handlers[1].as_ref().unchecked_ref()));
+        socket.set_onclose(Some(handlers[2].as_ref().unchecked_ref()));
+        socket.set_onerror(Some(handlers[3].as_ref().unchecked_ref()));
+        Self { socket: Some(socket), incoming, _handlers: handlers }
+    }
+
+    /// Only once the socket is open; the browser won't queue for it.
+    pub fn send(&self, text: String) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+        if let Err(e) = socket.send_with_str(&text) {
+            crate::content::warn(&format!("sending to the relay: {e:?}"));
+        }
+    }
+
+    pub fn poll(&self) -> Vec<Event> {
+        self.incoming.borrow_mut().drain(..).collect()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if let Some(socket) = &self.socket {
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            socket.set_onerror(None);
+            let _ = socket.close();
+        }
+    }
+}
+
+
+/// How long the connection thread waits for a message before checking for
+/// ones to send.
+#[cfg(not(target_arch = "wasm32"))]
+const POLL: std::time::Duration = std::time::Duration::from_millis(10);
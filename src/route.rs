@@ -0,0 +1,177 @@
+use crate::Page;
+
+
+impl Page {
+    pub const ALL: [Page; 10] = [
+        Page::Home,
+        Page::AboutUs,
+        Page::Timeline,
+        Page::Deliverables,
+        Page::Contact,
+        Page::Focus,
+        Page::Meditate,
+        Page::Library,
+        Page::Social,
+        Page::Rooms,
+    ];
+
+    pub fn slug(self) -> &'static str {
+        match self {
+            Page::Home => "home",
+            Page::AboutUs => "about",
+            Page::Timeline => "timeline",
+            Page::Deliverables => "deliverables",
+            Page::Contact => "contact",
+            Page::Focus => "focus",
+            Page::Meditate => "meditate",
+            Page::Library => "library",
+            Page::Social => "social",
+            Page::Rooms => "rooms",
+        }
+    }
+
+    pub fn from_slug(slug: &str) -> Option<Page> {
+        Page::ALL.into_iter().find(|page| page.slug() == slug)
+    }
+
+    /// Accepts `#/deliverables`, `#deliverables` or `deliverables`. An empty
+    /// fragment is the site root, which is the home page.
+    pub fn from_fragment(fragment: &str) -> Option<Page> {
+        let slug = fragment.trim_start_matches('#').trim_start_matches('/');
+        if slug.is_empty() {
+            return Some(Page::Home);
+        }
+        Page::from_slug(slug)
+    }
+
+    pub fn fragment(self) -> String {
+        format!("#/{}", self.slug())
+    }
+}
+
+/// Keeps `Program.page` and the URL fragment in step. Pushing a new fragment
+/// adds a browser history entry, so back/forward just show up as fragment
+/// changes on the next frame.
+pub struct Router {
+    synced: Page,
+}
+
+impl Router {
+    pub fn new(page: Page) -> Self {
+        Self { synced: page }
+    }
+
+    /// Called at the top of a frame. Returns the page the URL now points at if
+    /// it was changed from outside (back/forward, edited address bar).
+    pub fn incoming(&mut self) -> Option<Page> {
+        let page = Page::from_fragment(&current_fragment()?)?;
+        if page == self.synced {
+            return None;
+        }
+        self.synced = page;
+        Some(page)
+    }
+
+    /// Called at the end of a frame with the page that is now showing.
+    pub fn outgoing(&mut self, page: Page) {
+        if page != self.synced {
+            self.synced = page;
+            set_fragment(page);
+        }
+    }
+}
+
+/// The page asked for at launch, if any. Native: `--page <slug>` or
+/// `--page=<slug>`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn start_page() -> Option<Page> {
+    let slug = crate::cli_arg("--page")?;
+    let page = Page::from_slug(&slug);
+    if page.is_none() {
+        let known: Vec<_> = Page::ALL.iter().map(|page| page.slug()).collect();
+        eprintln!("Unknown page {slug:?}, expected one of: {}", known.join(", "));
+    }
+    page
+}
+
+/// The page asked for at launch, if any. Web: a non-empty URL fragment.
+#[cfg(target_arch = "wasm32")]
+pub fn start_page() -> Option<Page> {
+    current_fragment()
+        .filter(|fragment| !fragment.trim_start_matches(['#', '/']).is_empty())
+        .and_then(|fragment| Page::from_fragment(&fragment))
+}
+
+/// Repaints when the fragment changes, otherwise egui would only notice on the
+/// next input event.
+#[cfg(target_arch = "wasm32")]
+pub fn listen(ctx: &eframe::egui::Context) {
+    use eframe::wasm_bindgen::closure::Closure;
+    use eframe::wasm_bindgen::JsCast as _;
+
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let ctx = ctx.clone();
+    let on_change = Closure::<dyn FnMut()>::new(move || ctx.request_repaint());
+    let _ = window.add_event_listener_with_callback("hashchange", on_change.as_ref().unchecked_ref());
+    // The listener lives as long as the page does.
+    on_change.forget();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn listen(_ctx: &eframe::egui::Context) {}
+
+#[cfg(target_arch = "wasm32")]
+fn current_fragment() -> Option<String> {
+    web_sys::window()?.location().hash().ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn current_fragment() -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn set_fragment(page: Page) {
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().set_hash(&page.fragment());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn set_fragment(_page: Page) {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_page_round_trips() {
+        for page in Page::ALL {
+            assert_eq!(Page::from_fragment(&page.fragment()), Some(page));
+            assert_eq!(Page::from_fragment(&format!("#{}", page.slug())), Some(page));
+            assert_eq!(Page::from_fragment(page.slug()), Some(page));
+        }
+    }
+
+    #[test]
+    fn slugs_are_unique() {
+        for (i, page) in Page::ALL.iter().enumerate() {
+            assert!(Page::ALL[..i].iter().all(|other| other.slug() != page.slug()), "{}", page.slug());
+        }
+    }
+
+    /// The site root shows the home page; anything unknown leaves the page as
+    /// it is.
+    #[test]
+    fn empty_and_unknown_fragments() {
+        for fragment in ["", "#", "#/"] {
+            assert_eq!(Page::from_fragment(fragment), Some(Page::Home), "{fragment:?}");
+        }
+        for fragment in ["#/nowhere", "#/Home", "#/home/", "about us"] {
+            assert_eq!(Page::from_fragment(fragment), None, "{fragment:?}");
+        }
+    }
+}
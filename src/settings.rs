@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+use crate::theme::ThemeSettings;
+
+
+/// Everything about the UI that is remembered between sessions. Bump
+/// `VERSION` whenever a change would stop an older save from loading, and
+/// teach `migrate` how to bring the old shape forward.
+#[derive(Serialize, Deserialize)]
+pub struct SavedState {
+    pub version: u32,
+    /// The page's URL slug, so renaming a `Page` variant doesn't break saves.
+    pub page: String,
+    pub theme: ThemeSettings,
+    pub font_scale: f32,
+    /// Speak focused controls on the web, where there is no AccessKit.
+    #[serde(default)]
+    pub screen_reader: bool,
+    /// `None` follows the OS or browser language.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Focus timer interval lengths.
+    #[serde(default)]
+    pub focus: plistr_core::FocusSettings,
+    /// The soundscape mixer's levels, which aren't playing when loaded.
+    #[serde(default)]
+    pub soundscape: plistr_core::Soundscape,
+    /// Folders the desktop library scans.
+    #[serde(default)]
+    pub library_folders: Vec<String>,
+    /// The `plistr-server` account the Friends page signs in with.
+    #[serde(default)]
+    pub server_account: Option<plistr_core::Credentials>,
+}
+
+/// Only the version, read first to decide how to parse the rest.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl SavedState {
+    pub fn load(storage: &dyn eframe::Storage) -> Option<Self> {
+        match storage.get_string(STATE_KEY) {
+            Some(text) => {
+                let header: Header = ron::from_str(&text).ok()?;
+                migrate(header.version, &text)
+            }
+            None => migrate(UNVERSIONED, &storage.get_string(LEGACY_THEME_KEY)?),
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, STATE_KEY, self);
+    }
+
+    /// Defaults for everything but the theme.
+    fn with_theme(theme: ThemeSettings) -> Self {
+        Self {
+            version: VERSION,
+            page: crate::Page::Home.slug().to_owned(),
+            theme,
+            font_scale: 1.0,
+            screen_reader: false,
+            language: None,
+            focus: Default::default(),
+            soundscape: Default::default(),
+            library_folders: Vec::new(),
+            server_account: None,
+        }
+    }
+}
+
+/// Brings a save written by `version` up to the current shape. Saves from a
+/// newer build than this one are dropped rather than guessed at.
+fn migrate(version: u32, text: &str) -> Option<SavedState> {
+    match version {
+        // Only the theme, under a key of its own.
+        UNVERSIONED => ron::from_str(text).ok().map(SavedState::with_theme),
+        // Version 1 saves grew fields as features were added, each with a
+        // default, so the oldest of them still reads as the current shape.
+        VERSION => ron::from_str(text).ok(),
+        _ => None,
+    }
+}
+
+pub const VERSION: u32 = 1;
+/// Saves from before the state was versioned.
+const UNVERSIONED: u32 = 0;
+const STATE_KEY: &str = "state";
+const LEGACY_THEME_KEY: &str = "theme";
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::theme::ThemeChoice;
+
+    #[derive(Default)]
+    struct Memory(HashMap<String, String>);
+
+    impl eframe::Storage for Memory {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_owned(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    fn storage(key: &str, text: &str) -> Memory {
+        Memory(HashMap::from([(key.to_owned(), text.to_owned())]))
+    }
+
+    fn night() -> ThemeSettings {
+        ThemeSettings { choice: ThemeChoice::Named("Night".to_owned()), accent: Some("Teal".to_owned()) }
+    }
+
+    #[test]
+    fn a_save_loads_back_the_same() {
+        let mut state = SavedState::with_theme(night());
+        state.page = "library".to_owned();
+        state.font_scale = 1.25;
+        state.screen_reader = true;
+        state.language = Some("es".to_owned());
+        state.library_folders = vec!["/music".to_owned()];
+        let mut memory = Memory::default();
+        state.save(&mut memory);
+
+        let loaded = SavedState::load(&memory).unwrap();
+        assert_eq!(ron::to_string(&loaded).unwrap(), ron::to_string(&state).unwrap());
+    }
+
+    #[test]
+    fn the_first_version_1_saves_still_load() {
+        // All a save had when the state was first versioned.
+        let text = r#"(version: 1, page: "timeline", theme: (choice: Named("Night"), accent: Some("Teal")), font_scale: 1.5)"#;
+        let loaded = SavedState::load(&storage(STATE_KEY, text)).unwrap();
+        assert_eq!((loaded.page.as_str(), loaded.font_scale), ("timeline", 1.5));
+        assert!(loaded.theme.choice == night().choice);
+        assert!(!loaded.screen_reader && loaded.language.is_none() && loaded.library_folders.is_empty());
+    }
+
+    #[test]
+    fn an_unversioned_save_keeps_its_theme() {
+        let loaded = SavedState::load(&storage(LEGACY_THEME_KEY, &ron::to_string(&night()).unwrap())).unwrap();
+        assert_eq!(loaded.version, VERSION);
+        assert_eq!(loaded.page, crate::Page::Home.slug());
+        assert_eq!(loaded.theme.accent, night().accent);
+        assert!(SavedState::load(&Memory::default()).is_none());
+    }
+
+    #[test]
+    fn saves_from_a_newer_build_or_broken_ones_are_dropped() {
+        let mut text = ron::to_string(&SavedState::with_theme(night())).unwrap();
+        text = text.replacen(&format!("version:{VERSION}"), &format!("version:{}", VERSION + 1), 1);
+        assert!(text.contains(&format!("version:{}", VERSION + 1)), "{text}");
+        assert!(SavedState::load(&storage(STATE_KEY, &text)).is_none());
+        assert!(SavedState::load(&storage(STATE_KEY, "(version: 1, page: 3)")).is_none());
+        assert!(SavedState::load(&storage(STATE_KEY, "not ron")).is_none());
+    }
+}
@@ -0,0 +1,580 @@
+//! Friends: the user's profile, people to follow, and a feed of the
+//! playlists and focus sessions they share, with likes and comments. Until
+//! the user signs up on a `plistr-server`, it runs against a `MockServer`
+//! with a few made-up friends in it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use eframe::egui;
+use plistr_core::{
+    ApiClient, Credentials, MockServer, Playlist, PlaylistId, Post, PostId, Profile, Reply, Session, SessionId,
+    SessionKind, Shared, SocialClient, SocialError, UserId,
+};
+use crate::audio::Playback;
+use crate::focus::Focus;
+use crate::{history, i18n, player, typography};
+
+
+pub struct SocialView {
+    client: Box<dyn SocialClient>,
+    sender: mpsc::Sender<Update>,
+    updates: mpsc::Receiver<Update>,
+    /// Asked for the first time the page is shown.
+    loaded: bool,
+    me: Option<Profile>,
+    people: Vec<Profile>,
+    feed: Vec<Post>,
+    /// Everyone seen so far, for naming the authors of posts and comments.
+    names: BTreeMap<UserId, String>,
+    display_name: String,
+    bio: String,
+    query: String,
+    caption: String,
+    playlist_name: String,
+    /// Comments being typed, by post.
+    drafts: HashMap<PostId, String>,
+    /// So the same focus block isn't posted twice.
+    shared_session: Option<SessionId>,
+    /// The same client as `client`, while signed in to a server.
+    server: Option<ApiClient>,
+    server_url: String,
+    handle: String,
+    sign_up_name: String,
+    signing_up: bool,
+    saved_playlists: Vec<Playlist>,
+    saved_sessions: Vec<Session>,
+    save_name: String,
+    /// So the same focus block isn't saved twice.
+    saved_session: Option<SessionId>,
+    error: Option<String>,
+}
+
+/// A reply from the client, brought back to the UI thread.
+enum Update {
+    Me(Result<Profile, SocialError>),
+    People(Result<Vec<Profile>, SocialError>),
+    /// Someone in the feed who isn't among `people`.
+    Author(Result<Profile, SocialError>),
+    Feed(Result<Vec<Post>, SocialError>),
+    Post(Result<Post, SocialError>),
+    Shared(Result<Post, SocialError>),
+    SignedUp(Result<ApiClient, SocialError>),
+    SavedPlaylists(Result<Vec<Playlist>, SocialError>),
+    SavedSessions(Result<Vec<Session>, SocialError>),
+    /// Something saved to or deleted from the server.
+    Saved(Result<(), SocialError>),
+}
+
+impl SocialView {
+    pub fn new(client: Box<dyn SocialClient>) -> Self {
+        let (sender, updates) = mpsc::channel();
+        Self {
+            client,
+            sender,
+            updates,
+            loaded: false,
+            me: None,
+            people: Vec::new(),
+            feed: Vec::new(),
+            names: BTreeMap::new(),
+            display_name: String::new(),
+            bio: String::new(),
+            query: String::new(),
+            caption: String::new(),
+            playlist_name: String::new(),
+            drafts: HashMap::new(),
+            shared_session: None,
+            server: None,
+            server_url: DEFAULT_SERVER.to_owned(),
+            handle: String::new(),
+            sign_up_name: String::new(),
+            signing_up: false,
+            saved_playlists: Vec::new(),
+            saved_sessions: Vec::new(),
+            save_name: String::new(),
+            saved_session: None,
+            error: None,
+        }
+    }
+
+    /// Talking to a real server.
+    pub fn signed_in(client: ApiClient) -> Self {
+        let mut view = Self::new(Box::new(client.clone()));
+        view.server_url = client.credentials().server.clone();
+        view.server = Some(client);
+        view
+    }
+
+    /// What to sign back in with next time, if signed in to a server.
+    pub fn credentials(&self) -> Option<Credentials> {
+        self.server.as_ref().map(|server| server.credentials().clone())
+    }
+
+    /// Signed in to an in-process server with some friends already
+    /// sharing.
+    pub fn offline() -> Self {
+        let server = MockServer::new(history::now);
+        let me = demo(&server).unwrap_or_else(|e| {
+            crate::content::warn(&format!("setting up the offline friends: {e}"));
+            UserId(0)
+        });
+        Self::new(Box::new(server.client(me)))
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>, focus: &Focus) {
+        if !self.loaded {
+            self.loaded = true;
+            self.refresh(ui.ctx());
+        }
+        self.poll(ui.ctx());
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.heading(i18n::tr("social-account"));
+        ui.separator();
+        self.show_account(ui, player, focus);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("social-profile"));
+        ui.separator();
+        self.show_profile(ui);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("social-share"));
+        ui.separator();
+        self.show_share(ui, player, focus);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("social-people"));
+        ui.separator();
+        self.show_people(ui);
+        ui.add_space(29.0);
+        ui.heading(i18n::tr("social-feed"));
+        ui.separator();
+        self.show_feed(ui, player);
+    }
+
+    fn refresh(&self, ctx: &egui::Context) {
+        self.client.profile(self.client.me(), self.reply(ctx, Update::Me));
+        self.client.people(&self.query, self.reply(ctx, Update::People));
+        self.client.feed(FEED_LENGTH, self.reply(ctx, Update::Feed));
+        self.refresh_saved(ctx);
+    }
+
+    fn refresh_saved(&self, ctx: &egui::Context) {
+        if let Some(server) = &self.server {
+            server.playlists(self.client.me(), self.reply(ctx, Update::SavedPlaylists));
+            server.sessions(self.client.me(), self.reply(ctx, Update::SavedSessions));
+        }
+    }
+
+    /// A `Reply` that hands the result to `poll` and wakes the UI up.
+    fn reply<T: 'static>(&self, ctx: &egui::Context, update: fn(Result<T, SocialError>) -> Update) -> Reply<T> {
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        Box::new(move |result| {
+            let _ = sender.send(update(result));
+            ctx.request_repaint();
+        })
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let updates: Vec<Update> = self.updates.try_iter().collect();
+        for update in updates {
+            let result = match update {
+                Update::Me(result) => result.map(|me| {
+                    let following_changed = self.me.as_ref().is_some_and(|old| old.user.following != me.user.following);
+                    // Leaves alone edits that haven't been saved yet.
+                    let editing = self.me.as_ref()
+                        .is_some_and(|old| self.display_name != old.user.display_name || self.bio != old.bio);
+                    if !editing {
+                        self.display_name = me.user.display_name.clone();
+                        self.bio = me.bio.clone();
+                    }
+                    self.learn_name(&me);
+                    self.me = Some(me);
+                    if following_changed {
+                        self.refresh(ctx);
+                    }
+                }),
+                Update::People(result) => result.map(|people| {
+                    for profile in &people {
+                        self.learn_name(profile);
+                    }
+                    self.people = people;
+                }),
+                Update::Author(result) => result.map(|profile| self.learn_name(&profile)),
+                Update::Feed(result) => result.map(|feed| {
+                    self.feed = feed;
+                    self.fetch_unknown_authors(ctx);
+                }),
+                Update::Post(result) => result.map(|post| {
+                    if let Some(shown) = self.feed.iter_mut().find(|shown| shown.id == post.id) {
+                        *shown = post;
+                    }
+                    self.fetch_unknown_authors(ctx);
+                }),
+                Update::Shared(result) => result.map(|post| {
+                    self.caption.clear();
+                    self.feed.insert(0, post);
+                    self.feed.truncate(FEED_LENGTH);
+                }),
+                Update::SignedUp(result) => {
+                    self.signing_up = false;
+                    match result {
+                        Ok(client) => {
+                            // Replies still on their way are for the old
+                            // client, and go nowhere once it's replaced.
+                            *self = Self::signed_in(client);
+                            return;
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                Update::SavedPlaylists(result) => result.map(|playlists| self.saved_playlists = playlists),
+                Update::SavedSessions(result) => result.map(|sessions| self.saved_sessions = sessions),
+                Update::Saved(result) => result.map(|()| self.refresh_saved(ctx)),
+            };
+            self.error = result.err().map(|e| i18n::tr_with("social-failed", &[("error", e.to_string().into())]));
+        }
+    }
+
+    fn learn_name(&mut self, profile: &Profile) {
+        self.names.insert(profile.user.id, profile.user.display_name.clone());
+    }
+
+    /// Commenters needn't be followed, so they may not be among `people`.
+    fn fetch_unknown_authors(&self, ctx: &egui::Context) {
+        let mut unknown: Vec<UserId> = self.feed.iter()
+            .flat_map(|post| std::iter::once(post.author).chain(post.comments.iter().map(|comment| comment.author)))
+            .filter(|user| !self.names.contains_key(user))
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        for user in unknown {
+            self.client.profile(user, self.reply(ctx, Update::Author));
+        }
+    }
+
+    fn name(&self, user: UserId) -> String {
+        self.names.get(&user).cloned().unwrap_or_else(|| i18n::tr("social-someone"))
+    }
+
+    fn show_account(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>, focus: &Focus) {
+        let Some(server) = self.server.clone() else {
+            self.show_sign_up(ui);
+            return;
+        };
+        ui.horizontal_wrapped(|ui| {
+            let handle = self.me.as_ref().map(|me| me.user.handle.clone()).unwrap_or_default();
+            ui.label(i18n::tr_with("social-signed-in", &[
+                ("server", server.credentials().server.clone().into()),
+                ("handle", handle.into()),
+            ]));
+            if ui.button(i18n::tr("social-sign-out")).clicked() {
+                *self = Self::offline();
+            }
+        });
+        ui.add_space(11.0);
+        ui.strong(i18n::tr("social-saved"));
+        let queue = player.map(|player| player.status().queue).unwrap_or_default();
+        ui.horizontal_wrapped(|ui| {
+            let name = egui::TextEdit::singleline(&mut self.save_name).hint_text(i18n::tr("social-playlist-name"));
+            ui.add(name);
+            let ready = !queue.is_empty() && !self.save_name.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new(i18n::tr("social-save-queue"))).clicked() {
+                let mut playlist = Playlist::new(PlaylistId(0), self.client.me(), self.save_name.trim());
+                for track in queue {
+                    playlist.add(track);
+                }
+                server.create_playlist(&playlist, self.reply(ui.ctx(), |result| Update::Saved(result.map(drop))));
+                self.save_name.clear();
+            }
+        });
+        let session = focus.last_finished(self.client.me()).filter(|session| Some(session.id) != self.saved_session);
+        let label = match &session {
+            Some(session) => i18n::tr_with("social-save-session", &[("minutes", minutes(session.elapsed).into())]),
+            None => i18n::tr("social-no-session"),
+        };
+        if ui.add_enabled(session.is_some(), egui::Button::new(label)).clicked() {
+            if let Some(session) = session {
+                self.saved_session = Some(session.id);
+                server.create_session(&session, self.reply(ui.ctx(), |result| Update::Saved(result.map(drop))));
+            }
+        }
+        if self.saved_playlists.is_empty() && self.saved_sessions.is_empty() {
+            ui.weak(i18n::tr("social-nothing-saved"));
+        }
+        let mut delete = None;
+        for playlist in &self.saved_playlists {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(i18n::tr_with("social-saved-playlist", &[
+                    ("name", playlist.name.clone().into()),
+                    ("count", playlist.len().into()),
+                ]));
+                if let Some(player) = player.filter(|_| !playlist.is_empty()) {
+                    if ui.button(i18n::tr("social-queue")).clicked() {
+                        player::enqueue(player, playlist.tracks().to_vec());
+                    }
+                }
+                if ui.button(i18n::tr("social-delete")).clicked() {
+                    delete = Some(playlist.id);
+                }
+            });
+        }
+        if let Some(id) = delete {
+            server.delete_playlist(id, self.reply(ui.ctx(), Update::Saved));
+        }
+        if !self.saved_sessions.is_empty() {
+            let total = self.saved_sessions.iter().map(|session| session.elapsed).sum();
+            ui.label(i18n::tr_with("social-saved-sessions", &[
+                ("count", self.saved_sessions.len().into()),
+                ("minutes", minutes(total).into()),
+            ]));
+        }
+    }
+
+    fn show_sign_up(&mut self, ui: &mut egui::Ui) {
+        ui.weak(i18n::tr("social-offline-hint"));
+        egui::Grid::new("social sign up").num_columns(2).spacing([19.0, 7.0]).show(ui, |ui| {
+            ui.label(i18n::tr("social-server"));
+            ui.text_edit_singleline(&mut self.server_url);
+            ui.end_row();
+            ui.label(i18n::tr("social-handle"));
+            ui.text_edit_singleline(&mut self.handle);
+            ui.end_row();
+            ui.label(i18n::tr("social-name"));
+            ui.text_edit_singleline(&mut self.sign_up_name);
+            ui.end_row();
+        });
+        if self.signing_up {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(i18n::tr("social-signing-up"));
+            });
+        } else {
+            let ready = !self.server_url.trim().is_empty() && !self.handle.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new(i18n::tr("social-sign-up"))).clicked() {
+                self.signing_up = true;
+                self.error = None;
+                let reply = self.reply(ui.ctx(), Update::SignedUp);
+                ApiClient::sign_up(&self.server_url, &self.handle, &self.sign_up_name, reply);
+            }
+        }
+    }
+
+    fn show_profile(&mut self, ui: &mut egui::Ui) {
+        let Some(me) = &self.me else {
+            ui.weak(i18n::tr("social-loading"));
+            return;
+        };
+        ui.label(egui::RichText::new(&me.user.display_name).text_style(typography::title()));
+        ui.weak(format!("@{}", me.user.handle));
+        ui.label(i18n::tr_with("social-counts", &[
+            ("followers", me.followers.into()),
+            ("following", me.user.following.len().into()),
+        ]));
+        ui.add_space(11.0);
+        egui::Grid::new("social profile").num_columns(2).spacing([19.0, 7.0]).show(ui, |ui| {
+            ui.label(i18n::tr("social-name"));
+            ui.text_edit_singleline(&mut self.display_name);
+            ui.end_row();
+            ui.label(i18n::tr("social-bio"));
+            ui.text_edit_multiline(&mut self.bio);
+            ui.end_row();
+        });
+        let changed = self.display_name != me.user.display_name || self.bio != me.bio;
+        if ui.add_enabled(changed, egui::Button::new(i18n::tr("social-save-profile"))).clicked() {
+            let reply = self.reply(ui.ctx(), Update::Me);
+            self.client.update_profile(self.display_name.clone(), self.bio.clone(), reply);
+        }
+    }
+
+    fn show_share(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>, focus: &Focus) {
+        ui.horizontal(|ui| {
+            ui.label(i18n::tr("social-caption"));
+            ui.text_edit_singleline(&mut self.caption);
+        });
+        let queue = player.map(|player| player.status().queue).unwrap_or_default();
+        ui.horizontal_wrapped(|ui| {
+            let name = egui::TextEdit::singleline(&mut self.playlist_name).hint_text(i18n::tr("social-playlist-name"));
+            ui.add(name);
+            let ready = !queue.is_empty() && !self.playlist_name.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new(i18n::tr("social-share-queue"))).clicked() {
+                let mut playlist = Playlist::new(PlaylistId(history::now()), self.client.me(), self.playlist_name.trim());
+                for track in queue {
+                    playlist.add(track);
+                }
+                self.client.share(Shared::Playlist(playlist), self.caption.clone(), self.reply(ui.ctx(), Update::Shared));
+                self.playlist_name.clear();
+            }
+        });
+        let session = focus.last_finished(self.client.me()).filter(|session| Some(session.id) != self.shared_session);
+        let label = match &session {
+            Some(session) => i18n::tr_with("social-share-session", &[("minutes", minutes(session.elapsed).into())]),
+            None => i18n::tr("social-no-session"),
+        };
+        if ui.add_enabled(session.is_some(), egui::Button::new(label)).clicked() {
+            if let Some(session) = session {
+                self.shared_session = Some(session.id);
+                self.client.share(Shared::Session(session), self.caption.clone(), self.reply(ui.ctx(), Update::Shared));
+            }
+        }
+    }
+
+    fn show_people(&mut self, ui: &mut egui::Ui) {
+        let search = egui::TextEdit::singleline(&mut self.query).hint_text(i18n::tr("social-search"));
+        if ui.add(search).changed() {
+            self.client.people(&self.query, self.reply(ui.ctx(), Update::People));
+        }
+        let me = self.client.me();
+        let following = self.me.as_ref().map(|me| me.user.following.clone()).unwrap_or_default();
+        egui::Grid::new("social people").num_columns(3).spacing([19.0, 7.0]).striped(true).show(ui, |ui| {
+            for profile in self.people.iter().filter(|profile| profile.user.id != me) {
+                ui.vertical(|ui| {
+                    ui.strong(&profile.user.display_name);
+                    ui.weak(format!("@{}", profile.user.handle));
+                    if !profile.bio.is_empty() {
+                        ui.label(&profile.bio);
+                    }
+                });
+                ui.label(i18n::tr_with("social-followers", &[("count", profile.followers.into())]));
+                let id = profile.user.id;
+                if following.contains(&id) {
+                    if ui.button(i18n::tr("social-unfollow")).clicked() {
+                        self.client.unfollow(id, self.reply(ui.ctx(), Update::Me));
+                    }
+                } else if ui.button(i18n::tr("social-follow")).clicked() {
+                    self.client.follow(id, self.reply(ui.ctx(), Update::Me));
+                }
+                ui.end_row();
+            }
+        });
+        if self.people.iter().all(|profile| profile.user.id == me) {
+            ui.weak(i18n::tr("social-nobody"));
+        }
+    }
+
+    fn show_feed(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        if self.feed.is_empty() {
+            ui.weak(i18n::tr("social-feed-empty"));
+        }
+        let me = self.client.me();
+        let mut drafts = std::mem::take(&mut self.drafts);
+        for post in &self.feed {
+            ui.add_space(11.0);
+            ui.group(|ui| {
+                ui.set_width(ui.available_width());
+                ui.strong(self.name(post.author));
+                if !post.caption.is_empty() {
+                    ui.label(&post.caption);
+                }
+                match &post.shared {
+                    Shared::Playlist(playlist) => {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(i18n::tr_with("social-shared-playlist", &[
+                                ("name", playlist.name.clone().into()),
+                                ("count", playlist.len().into()),
+                            ]));
+                            if let Some(player) = player.filter(|_| !playlist.is_empty()) {
+                                if ui.button(i18n::tr("social-queue")).clicked() {
+                                    player::enqueue(player, playlist.tracks().to_vec());
+                                }
+                            }
+                        });
+                    }
+                    Shared::Session(session) => {
+                        let key = match session.kind {
+                            SessionKind::Study => "social-shared-study",
+                            SessionKind::Meditation => "social-shared-meditation",
+                        };
+                        ui.label(i18n::tr_with(key, &[("minutes", minutes(session.elapsed).into())]));
+                    }
+                }
+                ui.horizontal(|ui| {
+                    let liked = post.is_liked_by(me);
+                    let key = if liked { "social-unlike" } else { "social-like" };
+                    if ui.button(i18n::tr_with(key, &[("count", post.likes().into())])).clicked() {
+                        let reply = self.reply(ui.ctx(), Update::Post);
+                        if liked {
+                            self.client.unlike(post.id, reply);
+                        } else {
+                            self.client.like(post.id, reply);
+                        }
+                    }
+                });
+                for comment in &post.comments {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.strong(self.name(comment.author));
+                        ui.label(&comment.text);
+                    });
+                }
+                let draft = drafts.entry(post.id).or_default();
+                ui.horizontal(|ui| {
+                    let field = ui.add(egui::TextEdit::singleline(draft).hint_text(i18n::tr("social-comment-hint")));
+                    let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let send = ui.add_enabled(!draft.trim().is_empty(), egui::Button::new(i18n::tr("social-comment")));
+                    if (send.clicked() || entered) && !draft.trim().is_empty() {
+                        self.client.comment(post.id, std::mem::take(draft), self.reply(ui.ctx(), Update::Post));
+                    }
+                });
+            });
+        }
+        self.drafts = drafts;
+    }
+}
+
+/// Rounded to the nearest minute.
+fn minutes(time: Duration) -> u64 {
+    (time.as_secs() + 30) / 60
+}
+
+/// Signs up the user and a few friends, and has the friends share and
+/// comment on some study sessions. Returns the user.
+fn demo(server: &MockServer) -> Result<UserId, SocialError> {
+    let me = server.sign_up("you", "You")?;
+    let mut friends = Vec::new();
+    for (handle, name, bio) in DEMO_FRIENDS {
+        let friend = server.client(server.sign_up(handle, name)?);
+        friend.update_profile(name.to_owned(), bio.to_owned(), ignore());
+        friends.push(friend);
+    }
+    let me = server.client(me);
+    me.follow(friends[0].me(), ignore());
+    for (friend, (minutes, caption)) in friends.iter().zip(DEMO_SESSIONS) {
+        let mut session = plistr_core::Session::new(
+            SessionId(0),
+            friend.me(),
+            SessionKind::Study,
+            history::now(),
+            Duration::from_secs(minutes * 60),
+        );
+        session.record(session.planned);
+        friend.share(Shared::Session(session), caption.to_owned(), ignore());
+    }
+    if let [ana, ben, ..] = friends.as_slice() {
+        ana.follow(ben.me(), ignore());
+        ben.follow(ana.me(), ignore());
+        ben.like(PostId(1), ignore());
+        ben.comment(PostId(1), "Nice streak!".to_owned(), ignore());
+    }
+    Ok(me.me())
+}
+
+fn ignore<T>() -> Reply<T> {
+    Box::new(|_| {})
+}
+
+
+/// Posts kept in the feed.
+const FEED_LENGTH: usize = 50;
+const DEFAULT_SERVER: &str = "http://127.0.0.1:8080";
+const DEMO_FRIENDS: [(&str, &str, &str); 3] = [
+    ("ana", "Ana", "Revising for finals, one Pomodoro at a time."),
+    ("ben", "Ben", "Lo-fi and long breaks."),
+    ("chloe", "Chloe", "Meditation before maths."),
+];
+const DEMO_SESSIONS: [(u64, &str); 3] = [
+    (50, "Two blocks of chemistry done"),
+    (25, "Essay plan finished"),
+    (25, ""),
+];
@@ -0,0 +1,165 @@
+use eframe::egui;
+use plistr_core::{Layer, Sound, Soundscape};
+
+use crate::audio::Playback;
+use crate::{i18n, icons};
+
+
+/// A mixer for the generated background sound: one volume per kind of
+/// sound, and the tone for binaural beats.
+pub struct Mixer {
+    soundscape: Soundscape,
+    playing: bool,
+    /// Changed since the player last heard, held back until the slider is
+    /// let go: the web player renders the whole loop again each time.
+    changed: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    export: Option<std::sync::mpsc::Receiver<Result<std::path::PathBuf, String>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    exported: Option<Result<std::path::PathBuf, String>>,
+}
+
+impl Mixer {
+    pub fn new(mut soundscape: Soundscape) -> Self {
+        // One layer for every kind of sound, so each gets a slider.
+        for sound in SOUNDS {
+            if !soundscape.layers.iter().any(|layer| same_kind(layer.sound, sound)) {
+                soundscape.layers.push(Layer { sound, volume: 0.0 });
+            }
+        }
+        soundscape.layers.sort_by_key(|layer| SOUNDS.iter().position(|&sound| same_kind(layer.sound, sound)));
+        Self {
+            soundscape,
+            playing: false,
+            changed: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            export: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            exported: None,
+        }
+    }
+
+    pub fn soundscape(&self) -> Soundscape {
+        self.soundscape.clone()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, player: Option<&dyn Playback>) {
+        let silent = self.soundscape.layers.iter().all(|layer| layer.volume == 0.0);
+        ui.horizontal(|ui| {
+            let (icon, key) = if self.playing { (icons::PAUSE, "soundscape-stop") } else { (icons::PLAY, "soundscape-play") };
+            let button = egui::Button::new(format!("{icon} {}", i18n::tr(key)));
+            if ui.add_enabled(player.is_some() && (self.playing || !silent), button).clicked() {
+                self.playing = !self.playing;
+                self.changed = true;
+            }
+            ui.label(i18n::tr("soundscape-seed"));
+            let seed = ui.add(egui::DragValue::new(&mut self.soundscape.seed).range(0..=9999));
+            self.changed |= seed.on_hover_text(i18n::tr("soundscape-seed-hint")).changed();
+            self.show_export(ui, silent);
+        });
+        let mut changed = false;
+        egui::Grid::new("soundscape layers").num_columns(2).spacing([19.0, 7.0]).show(ui, |ui| {
+            for layer in &mut self.soundscape.layers {
+                ui.label(sound_name(layer.sound));
+                changed |= ui.add(egui::Slider::new(&mut layer.volume, 0.0..=1.0).show_value(false)).changed();
+                ui.end_row();
+                if let Sound::Binaural { carrier, beat } = &mut layer.sound {
+                    ui.label(i18n::tr("soundscape-carrier"));
+                    changed |= ui.add(egui::Slider::new(carrier, 100.0..=500.0).suffix(" Hz").integer()).changed();
+                    ui.end_row();
+                    ui.label(i18n::tr("soundscape-beat"));
+                    changed |= ui.add(egui::Slider::new(beat, 1.0..=30.0).suffix(" Hz")).changed();
+                    ui.end_row();
+                }
+            }
+        });
+        ui.weak(i18n::tr("soundscape-headphones"));
+
+        self.changed |= changed;
+        let letting_go = ui.input(|i| !i.pointer.any_down());
+        if let (true, true, Some(player)) = (self.changed, letting_go, player) {
+            player.set_soundscape(Some(self.soundscape.clone()).filter(|_| self.playing));
+            self.changed = false;
+        }
+    }
+
+    /// Renders a few minutes to a WAV file in the working directory, on
+    /// another thread since it takes a moment.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn show_export(&mut self, ui: &mut egui::Ui, silent: bool) {
+        if let Some(result) = self.export.as_ref().and_then(|receiver| receiver.try_recv().ok()) {
+            self.exported = Some(result);
+            self.export = None;
+        }
+        let exporting = self.export.is_some();
+        let button = egui::Button::new(i18n::tr_with("soundscape-export", &[("minutes", EXPORT_MINUTES.into())]));
+        if ui.add_enabled(!exporting && !silent, button).clicked() {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let (soundscape, ctx) = (self.soundscape.clone(), ui.ctx().clone());
+            std::thread::spawn(move || {
+                let _ = sender.send(export(&soundscape));
+                ctx.request_repaint();
+            });
+            self.export = Some(receiver);
+            self.exported = None;
+        }
+        if exporting {
+            ui.spinner();
+        }
+        match &self.exported {
+            Some(Ok(path)) => {
+                ui.weak(i18n::tr_with("soundscape-exported", &[("path", path.display().to_string().into())]));
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+            None => {}
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn show_export(&mut self, _ui: &mut egui::Ui, _silent: bool) {}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export(soundscape: &Soundscape) -> Result<std::path::PathBuf, String> {
+    let path = std::env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join(format!("soundscape-{}.wav", soundscape.seed));
+    let file = std::fs::File::create(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+    soundscape.write_wav(std::io::BufWriter::new(file), EXPORT_RATE, EXPORT_MINUTES as f32 * 60.0)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(path)
+}
+
+fn same_kind(a: Sound, b: Sound) -> bool {
+    std::mem::discriminant(&a) == std::mem::discriminant(&b)
+}
+
+fn sound_name(sound: Sound) -> String {
+    i18n::tr(match sound {
+        Sound::White => "soundscape-white",
+        Sound::Pink => "soundscape-pink",
+        Sound::Brown => "soundscape-brown",
+        Sound::Rain => "soundscape-rain",
+        Sound::Wind => "soundscape-wind",
+        Sound::Binaural { .. } => "soundscape-binaural",
+    })
+}
+
+
+/// The mixer's rows, in order. Binaural beats start on a 200 Hz tone
+/// pulsing at 10 Hz.
+const SOUNDS: [Sound; 6] = [
+    Sound::Rain,
+    Sound::Wind,
+    Sound::White,
+    Sound::Pink,
+    Sound::Brown,
+    Sound::Binaural { carrier: 200.0, beat: 10.0 },
+];
+
+#[cfg(not(target_arch = "wasm32"))]
+const EXPORT_MINUTES: u32 = 5;
+#[cfg(not(target_arch = "wasm32"))]
+const EXPORT_RATE: u32 = 44_100;
@@ -0,0 +1,349 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use eframe::wasm_bindgen::closure::Closure;
+use eframe::wasm_bindgen::{JsCast as _, JsValue};
+use plistr_core::{
+    MemoryRepository, Play, Playlist, PlaylistId, Repository, RepositoryError, Session, SessionId, Track, TrackId,
+};
+use serde::{Deserialize, Serialize};
+use web_sys::{
+    IdbCursorWithValue, IdbDatabase, IdbObjectStoreParameters, IdbOpenDbRequest, IdbTransaction, IdbTransactionMode,
+    IdbVersionChangeEvent,
+};
+
+use crate::content;
+
+
+/// IndexedDB only answers asynchronously, so everything is read into a
+/// `MemoryRepository` when the database opens. Reads come from there and
+/// writes go to both; until it has opened, writes fail rather than risk
+/// clashing with ids already stored.
+pub struct IndexedDbRepository {
+    shared: Rc<RefCell<Shared>>,
+}
+
+struct Shared {
+    memory: MemoryRepository,
+    /// Set once everything stored has been read in.
+    database: Option<IdbDatabase>,
+}
+
+/// One step in `MIGRATIONS`, run while the database is being upgraded.
+type Migration = fn(&IdbDatabase, &IdbTransaction) -> Result<(), JsValue>;
+
+/// A playlist's record: the tracks are stored on their own and only
+/// referred to, so saving a track doesn't leave stale copies behind.
+#[derive(Serialize, Deserialize)]
+struct StoredPlaylist {
+    playlist: Playlist,
+    tracks: Vec<TrackId>,
+}
+
+impl IndexedDbRepository {
+    pub fn open() -> Result<Self, RepositoryError> {
+        let factory = web_sys::window()
+            .and_then(|window| window.indexed_db().ok().flatten())
+            .ok_or_else(|| RepositoryError::Backend("this browser has no IndexedDB".to_owned()))?;
+        let request = factory.open_with_u32(DATABASE, MIGRATIONS.len() as u32).map_err(backend)?;
+        let shared = Rc::new(RefCell::new(Shared { memory: MemoryRepository::new(), database: None }));
+
+        let upgrading = request.clone();
+        let upgrade = Closure::once_into_js(move |event: IdbVersionChangeEvent| {
+            let result = upgrading.result().and_then(|database| {
+                let database: IdbDatabase = database.unchecked_into();
+                let transaction = upgrading.transaction().ok_or_else(|| JsValue::from_str("no upgrade transaction"))?;
+                MIGRATIONS.iter()
+                    .skip(event.old_version() as usize)
+                    .try_for_each(|migrate| migrate(&database, &transaction))
+            });
+            if let Err(e) = result {
+                content::warn(&format!("upgrading the browser database: {}", describe(&e)));
+            }
+        });
+        request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+
+        let opened = request.clone();
+        let weak = Rc::downgrade(&shared);
+        let success = Closure::once_into_js(move || {
+            let (Some(shared), Ok(database)) = (weak.upgrade(), opened.result()) else {
+                return;
+            };
+            if let Err(e) = load(&shared, database.unchecked_into()) {
+                content::warn(&format!("reading the browser database: {}", describe(&e)));
+            }
+        });
+        request.set_onsuccess(Some(success.unchecked_ref()));
+        request.set_onerror(Some(failed(&request).unchecked_ref()));
+        Ok(Self { shared })
+    }
+
+    fn put(&self, store: &str, key: u64, value: &impl Serialize) -> Result<(), RepositoryError> {
+        let text = ron::to_string(value).map_err(|e| RepositoryError::Corrupt(e.to_string()))?;
+        let objects = self.write(store)?;
+        objects.put_with_key(&JsValue::from_str(&text), &record_key(key)).map_err(backend)?;
+        Ok(())
+    }
+
+    fn delete(&self, store: &str, key: u64) -> Result<(), RepositoryError> {
+        self.write(store)?.delete(&record_key(key)).map_err(backend)?;
+        Ok(())
+    }
+
+    fn put_playlist(&self, playlist: &Playlist) -> Result<(), RepositoryError> {
+        let mut shell = Playlist::new(playlist.id, playlist.owner, playlist.name.clone());
+        shell.description = playlist.description.clone();
+        shell.tags = playlist.tags.clone();
+        let stored = StoredPlaylist {
+            playlist: shell,
+            tracks: playlist.tracks().iter().map(|track| track.id).collect(),
+        };
+        self.put(PLAYLISTS, playlist.id.0, &stored)
+    }
+
+    /// A store to write to in a transaction of its own. The browser
+    /// commits it once the request is done.
+    fn write(&self, store: &str) -> Result<web_sys::IdbObjectStore, RepositoryError> {
+        let shared = self.shared.borrow();
+        let database = shared.database.as_ref()
+            .ok_or_else(|| RepositoryError::Backend("the browser database hasn't opened yet".to_owned()))?;
+        let transaction = database.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite).map_err(backend)?;
+        transaction.object_store(store).map_err(backend)
+    }
+
+    /// Fails the same way `write` would, before anything changes in memory.
+    fn ready(&self) -> Result<(), RepositoryError> {
+        match self.shared.borrow().database {
+            Some(_) => Ok(()),
+            None => Err(RepositoryError::Backend("the browser database hasn't opened yet".to_owned())),
+        }
+    }
+}
+
+impl Repository for IndexedDbRepository {
+    fn save_track(&mut self, track: &Track) -> Result<(), RepositoryError> {
+        self.ready()?;
+        self.shared.borrow_mut().memory.save_track(track)?;
+        self.put(TRACKS, track.id.0, track)
+    }
+
+    fn track(&self, id: TrackId) -> Result<Option<Track>, RepositoryError> {
+        self.shared.borrow().memory.track(id)
+    }
+
+    fn tracks(&self) -> Result<Vec<Track>, RepositoryError> {
+        self.shared.borrow().memory.tracks()
+    }
+
+    fn remember_track(&mut self, track: &Track) -> Result<Track, RepositoryError> {
+        self.ready()?;
+        let stored = self.shared.borrow_mut().memory.remember_track(track)?;
+        self.put(TRACKS, stored.id.0, &stored)?;
+        Ok(stored)
+    }
+
+    fn delete_track(&mut self, id: TrackId) -> Result<(), RepositoryError> {
+        self.ready()?;
+        let affected: Vec<PlaylistId> = self.shared.borrow().memory.playlists()?
+            .into_iter()
+            .filter(|playlist| playlist.tracks().iter().any(|track| track.id == id))
+            .map(|playlist| playlist.id)
+            .collect();
+        self.shared.borrow_mut().memory.delete_track(id)?;
+        self.delete(TRACKS, id.0)?;
+        for playlist in affected {
+            if let Some(playlist) = self.playlist(playlist)? {
+                self.put_playlist(&playlist)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn save_playlist(&mut self, playlist: &Playlist) -> Result<(), RepositoryError> {
+        self.ready()?;
+        self.shared.borrow_mut().memory.save_playlist(playlist)?;
+        for track in playlist.tracks() {
+            self.put(TRACKS, track.id.0, track)?;
+        }
+        self.put_playlist(playlist)
+    }
+
+    fn playlist(&self, id: PlaylistId) -> Result<Option<Playlist>, RepositoryError> {
+        self.shared.borrow().memory.playlist(id)
+    }
+
+    fn playlists(&self) -> Result<Vec<Playlist>, RepositoryError> {
+        self.shared.borrow().memory.playlists()
+    }
+
+    fn delete_playlist(&mut self, id: PlaylistId) -> Result<(), RepositoryError> {
+        self.ready()?;
+        self.shared.borrow_mut().memory.delete_playlist(id)?;
+        self.delete(PLAYLISTS, id.0)
+    }
+
+    fn save_session(&mut self, session: &Session) -> Result<(), RepositoryError> {
+        self.ready()?;
+        self.shared.borrow_mut().memory.save_session(session)?;
+        self.put(SESSIONS, session.id.0, session)
+    }
+
+    fn session(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+        self.shared.borrow().memory.session(id)
+    }
+
+    fn sessions(&self) -> Result<Vec<Session>, RepositoryError> {
+        self.shared.borrow().memory.sessions()
+    }
+
+    fn delete_session(&mut self, id: SessionId) -> Result<(), RepositoryError> {
+        self.ready()?;
+        self.shared.borrow_mut().memory.delete_session(id)?;
+        self.delete(SESSIONS, id.0)
+    }
+
+    fn record_play(&mut self, play: &Play) -> Result<(), RepositoryError> {
+        self.ready()?;
+        self.shared.borrow_mut().memory.record_play(play)?;
+        let text = ron::to_string(play).map_err(|e| RepositoryError::Corrupt(e.to_string()))?;
+        // Plays are keyed by the store's own counter.
+        self.write(PLAYS)?.add(&JsValue::from_str(&text)).map_err(backend)?;
+        Ok(())
+    }
+
+    fn history(&self, limit: usize) -> Result<Vec<Play>, RepositoryError> {
+        self.shared.borrow().memory.history(limit)
+    }
+}
+
+/// Reads every store in one transaction, tracks before the playlists that
+/// refer to them, and only then lets writes through.
+fn load(shared: &Rc<RefCell<Shared>>, database: IdbDatabase) -> Result<(), JsValue> {
+    let names = js_sys::Array::new();
+    for store in STORES {
+        names.push(&JsValue::from_str(store));
+    }
+    let transaction = database.transaction_with_str_sequence(&names)?;
+    let requests = STORES.iter()
+        .map(|store| transaction.object_store(store)?.get_all())
+        .collect::<Result<Vec<_>, _>>()?;
+    let weak = Rc::downgrade(shared);
+    let complete = Closure::once_into_js(move || {
+        let Some(shared) = weak.upgrade() else {
+            return;
+        };
+        let mut shared = shared.borrow_mut();
+        for (store, request) in STORES.iter().zip(&requests) {
+            let Ok(values) = request.result() else {
+                continue;
+            };
+            for value in js_sys::Array::from(&values).iter() {
+                let restored = value.as_string()
+                    .ok_or_else(|| RepositoryError::Corrupt(format!("a {store} record isn't text")))
+                    .and_then(|text| restore(&mut shared.memory, store, &text));
+                if let Err(e) = restored {
+                    content::warn(&format!("browser database: {e}"));
+                }
+            }
+        }
+        shared.database = Some(database);
+    });
+    transaction.set_oncomplete(Some(complete.unchecked_ref()));
+    Ok(())
+}
+
+fn restore(memory: &mut MemoryRepository, store: &str, text: &str) -> Result<(), RepositoryError> {
+    let corrupt = |e: ron::error::SpannedError| RepositoryError::Corrupt(format!("{store}: {e}"));
+    match store {
+        TRACKS => memory.save_track(&ron::from_str(text).map_err(corrupt)?),
+        PLAYLISTS => {
+            let stored: StoredPlaylist = ron::from_str(text).map_err(corrupt)?;
+            let mut playlist = stored.playlist;
+            for id in stored.tracks {
+                if let Some(track) = memory.track(id)? {
+                    playlist.add(track);
+                }
+            }
+            memory.save_playlist(&playlist)
+        }
+        SESSIONS => memory.save_session(&ron::from_str(text).map_err(corrupt)?),
+        PLAYS => memory.record_play(&ron::from_str(text).map_err(corrupt)?),
+        _ => Ok(()),
+    }
+}
+
+fn failed(request: &IdbOpenDbRequest) -> JsValue {
+    let request = request.clone();
+    Closure::once_into_js(move || {
+        let error = request.error().ok().flatten().map_or_else(|| "unknown error".to_owned(), |e| e.message());
+        content::warn(&format!("opening the browser database: {error}"));
+    })
+}
+
+/// Version 1: a store per kind of record. Plays number themselves.
+fn create_stores(database: &IdbDatabase, _: &IdbTransaction) -> Result<(), JsValue> {
+    for store in [TRACKS, PLAYLISTS, SESSIONS] {
+        database.create_object_store(store)?;
+    }
+    let counted = IdbObjectStoreParameters::new();
+    counted.set_auto_increment(true);
+    database.create_object_store_with_optional_parameters(PLAYS, &counted)?;
+    Ok(())
+}
+
+/// Version 2: records move from number keys to `record_key`s. The upgrade
+/// transaction stays open until every cursor has run out.
+fn string_keys(_: &IdbDatabase, transaction: &IdbTransaction) -> Result<(), JsValue> {
+    for store in [TRACKS, PLAYLISTS, SESSIONS] {
+        let objects = transaction.object_store(store)?;
+        let request = objects.open_cursor()?;
+        let reading = request.clone();
+        let step = Closure::<dyn FnMut()>::new(move || {
+            let Some(cursor) = reading.result().ok().and_then(|cursor| cursor.dyn_into::<IdbCursorWithValue>().ok()) else {
+                return;
+            };
+            let moved = match cursor.key().map(|key| key.as_f64()) {
+                // String keys sort after numbers, so the cursor gets to the
+                // moved copies last and leaves them be.
+                Ok(Some(number)) => cursor.value()
+                    .and_then(|value| objects.put_with_key(&value, &record_key(number as u64)))
+                    .and_then(|_| cursor.delete())
+                    .map(drop),
+                _ => Ok(()),
+            };
+            if let Err(e) = moved.and_then(|()| cursor.continue_()) {
+                content::warn(&format!("upgrading the browser database: {}", describe(&e)));
+            }
+        });
+        request.set_onsuccess(Some(step.as_ref().unchecked_ref()));
+        // Lives as long as the page; there's one per store, once.
+        step.forget();
+    }
+    Ok(())
+}
+
+/// Ids as decimal text: unstored track ids start at 2^63, past where a
+/// JavaScript number can tell them apart.
+fn record_key(id: u64) -> JsValue {
+    JsValue::from_str(&id.to_string())
+}
+
+fn backend(e: JsValue) -> RepositoryError {
+    RepositoryError::Backend(describe(&e))
+}
+
+fn describe(e: &JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{e:?}"))
+}
+
+
+const DATABASE: &str = "plistr";
+/// Each entry moves the database on one version; the browser runs the ones
+/// an older database hasn't had. Only ever add to the end.
+const MIGRATIONS: [Migration; 2] = [create_stores, string_keys];
+const TRACKS: &str = "tracks";
+const PLAYLISTS: &str = "playlists";
+const SESSIONS: &str = "sessions";
+const PLAYS: &str = "plays";
+/// In the order they're read back.
+const STORES: [&str; 4] = [TRACKS, PLAYLISTS, SESSIONS, PLAYS];
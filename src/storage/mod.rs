@@ -0,0 +1,39 @@
+//! Where the app keeps its `Repository`: a SQLite file next to the native
+//! saves, or the browser's IndexedDB.
+
+use plistr_core::{MemoryRepository, Repository};
+
+use crate::content;
+
+#[cfg(target_arch = "wasm32")]
+mod indexed_db;
+
+
+/// Falls back to keeping things in memory for the session, with a warning,
+/// if the real store can't be opened.
+pub fn open() -> Box<dyn Repository> {
+    match open_persistent() {
+        Ok(repository) => repository,
+        Err(e) => {
+            content::warn(&format!("nothing will be kept after this session: {e}"));
+            Box::new(MemoryRepository::new())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn open_persistent() -> Result<Box<dyn Repository>, String> {
+    let dir = eframe::storage_dir(crate::APP_NAME).ok_or("no folder to save in")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let repository = plistr_core::SqliteRepository::open(dir.join(DATABASE_FILE)).map_err(|e| e.to_string())?;
+    Ok(Box::new(repository))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn open_persistent() -> Result<Box<dyn Repository>, String> {
+    Ok(Box::new(indexed_db::IndexedDbRepository::open().map_err(|e| e.to_string())?))
+}
+
+
+#[cfg(not(target_arch = "wasm32"))]
+const DATABASE_FILE: &str = "plistr.sqlite";
@@ -0,0 +1,78 @@
+use eframe::egui;
+
+use crate::content::{self, TeamMember};
+use crate::i18n;
+use crate::layout::Breakpoint;
+
+
+/// Lays the team out as cards, as many per row as the breakpoint allows and
+/// fit at `CARD_MIN_WIDTH`. On phones they stack.
+pub fn show_team(ui: &mut egui::Ui, team: &[TeamMember]) {
+    let columns = ((ui.available_width() / CARD_MIN_WIDTH) as usize)
+        .min(Breakpoint::current(ui.ctx()).columns())
+        .clamp(1, team.len().max(1));
+    for row in team.chunks(columns) {
+        ui.columns(columns, |columns| {
+            for (ui, member) in columns.iter_mut().zip(row) {
+                ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                    show_card(ui, member);
+                });
+            }
+        });
+        ui.add_space(29.0);
+    }
+}
+
+fn show_card(ui: &mut egui::Ui, member: &TeamMember) {
+    match &member.avatar {
+        Some(path) => {
+            ui.add(
+                egui::Image::new(content::asset_uri(path))
+                    .fit_to_exact_size(egui::Vec2::splat(AVATAR_SIZE))
+                    .rounding(AVATAR_SIZE / 2.0),
+            ).widget_info(|| {
+                egui::WidgetInfo::labeled(egui::WidgetType::Label, true, i18n::tr_with("team-photo", &[("name", member.name.as_str().into())]))
+            });
+        }
+        None => show_initials(ui, &member.name),
+    }
+    ui.heading(&member.name);
+    ui.weak(&member.role);
+    ui.separator();
+    ui.label(&member.bio);
+    ui.separator();
+    ui.heading(i18n::tr("team-responsibilities"));
+    for responsibility in &member.responsibilities {
+        ui.strong(responsibility);
+    }
+    if !member.links.is_empty() {
+        ui.add_space(11.0);
+        ui.horizontal_wrapped(|ui| {
+            for link in &member.links {
+                ui.hyperlink_to(&link.label, &link.url);
+            }
+        });
+    }
+}
+
+fn show_initials(ui: &mut egui::Ui, name: &str) {
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .collect();
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(AVATAR_SIZE), egui::Sense::hover());
+    let visuals = ui.visuals();
+    ui.painter().circle_filled(rect.center(), AVATAR_SIZE / 2.0, visuals.widgets.inactive.bg_fill);
+    ui.painter().text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        initials,
+        egui::TextStyle::Heading.resolve(ui.style()),
+        visuals.weak_text_color(),
+    );
+}
+
+
+const CARD_MIN_WIDTH: f32 = 350.0;
+const AVATAR_SIZE: f32 = 96.0;
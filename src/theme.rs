@@ -0,0 +1,201 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+
+/// The palettes and accents from `assets/themes.ron`, plus what the user
+/// picked from them.
+pub struct Themes {
+    file: ThemeFile,
+    pub settings: ThemeSettings,
+    applied: Option<(String, Option<String>)>,
+}
+
+/// The part that is saved between sessions.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ThemeSettings {
+    pub choice: ThemeChoice,
+    /// Name of an accent that replaces the palette's own.
+    pub accent: Option<String>,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            choice: ThemeChoice::System,
+            accent: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    /// Brand light or brand dark, whichever matches the OS setting.
+    System,
+    Named(String),
+}
+
+#[derive(Deserialize)]
+struct ThemeFile {
+    /// Used for `ThemeChoice::System`.
+    system_light: String,
+    system_dark: String,
+    palettes: Vec<Palette>,
+    accents: Vec<Accent>,
+}
+
+#[derive(Deserialize)]
+struct Palette {
+    name: String,
+    dark: bool,
+    background: Hex,
+    panel: Hex,
+    text: Hex,
+    accent: Hex,
+    /// Outline every widget in the text colour.
+    #[serde(default)]
+    outlines: bool,
+}
+
+#[derive(Deserialize)]
+struct Accent {
+    name: String,
+    color: Hex,
+}
+
+/// A `#rrggbb` colour.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+struct Hex(egui::Color32);
+
+impl TryFrom<String> for Hex {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        egui::Color32::from_hex(&text)
+            .map(Hex)
+            .map_err(|_| format!("{text:?} is not a #rrggbb colour"))
+    }
+}
+
+impl Themes {
+    pub fn bundled(settings: ThemeSettings) -> Self {
+        let file: ThemeFile = match ron::from_str(BUNDLED) {
+            Ok(file) => file,
+            Err(e) => panic!("themes.ron:{}:{}: {}", e.position.line, e.position.col, e.code),
+        };
+        for name in [&file.system_light, &file.system_dark] {
+            assert!(
+                file.palettes.iter().any(|palette| &palette.name == name),
+                "themes.ron: no palette named {name:?}",
+            );
+        }
+        Self {
+            file,
+            settings,
+            applied: None,
+        }
+    }
+
+    pub fn palette_names(&self) -> impl Iterator<Item = &str> {
+        self.file.palettes.iter().map(|palette| palette.name.as_str())
+    }
+
+    pub fn accent_names(&self) -> impl Iterator<Item = &str> {
+        self.file.accents.iter().map(|accent| accent.name.as_str())
+    }
+
+    pub fn is_dark(&self, ctx: &egui::Context) -> bool {
+        self.palette(ctx).dark
+    }
+
+    /// Installs the current palette if it changed since the last frame,
+    /// including when the OS switches between light and dark.
+    pub fn apply(&mut self, ctx: &egui::Context) {
+        let palette = self.palette(ctx);
+        let key = (palette.name.clone(), self.settings.accent.clone());
+        if self.applied.as_ref() == Some(&key) {
+            return;
+        }
+        let accent = self.settings.accent.as_ref()
+            .and_then(|name| self.file.accents.iter().find(|accent| &accent.name == name))
+            .map_or(palette.accent, |accent| accent.color);
+        let theme = if palette.dark { egui::Theme::Dark } else { egui::Theme::Light };
+        ctx.set_visuals_of(theme, palette.visuals(accent.0));
+        ctx.set_theme(theme);
+        self.applied = Some(key);
+    }
+
+    fn palette(&self, ctx: &egui::Context) -> &Palette {
+        let name = match &self.settings.choice {
+            ThemeChoice::Named(name) => name,
+            ThemeChoice::System => match ctx.system_theme() {
+                Some(egui::Theme::Dark) => &self.file.system_dark,
+                _ => &self.file.system_light,
+            },
+        };
+        let find = |name: &str| self.file.palettes.iter().find(|palette| palette.name == name);
+        // A saved choice can outlive a palette that was renamed or removed.
+        find(name).or_else(|| find(&self.file.system_light)).expect("checked in Themes::bundled")
+    }
+}
+
+impl Palette {
+    fn visuals(&self, accent: egui::Color32) -> egui::Visuals {
+        let mut visuals = if self.dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+        let text = self.text.0;
+        visuals.panel_fill = self.panel.0;
+        visuals.window_fill = self.panel.0;
+        visuals.extreme_bg_color = self.background.0;
+        visuals.faint_bg_color = self.background.0;
+        visuals.hyperlink_color = accent;
+        visuals.selection.bg_fill = accent;
+        visuals.selection.stroke.color = text;
+        visuals.widgets.noninteractive.fg_stroke.color = text;
+        visuals.widgets.inactive.fg_stroke.color = text;
+        visuals.widgets.hovered.bg_stroke.color = accent;
+        visuals.widgets.active.bg_stroke.color = accent;
+        if self.outlines {
+            for widget in [
+                &mut visuals.widgets.noninteractive,
+                &mut visuals.widgets.inactive,
+                &mut visuals.widgets.hovered,
+                &mut visuals.widgets.active,
+            ] {
+                widget.bg_stroke = egui::Stroke::new(2.0, text);
+                widget.fg_stroke.color = text;
+            }
+            visuals.widgets.hovered.bg_stroke.color = accent;
+        }
+        visuals
+    }
+}
+
+
+const BUNDLED: &str = include_str!("../assets/themes.ron");
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_themes_parse() {
+        let themes = Themes::bundled(ThemeSettings::default());
+        assert!(themes.accent_names().count() > 0);
+        let names: Vec<_> = themes.palette_names().collect();
+        for (i, name) in names.iter().enumerate() {
+            assert!(!names[..i].contains(name), "themes.ron: two palettes named {name:?}");
+        }
+    }
+
+    /// A saved palette that no longer exists falls back to the light one.
+    #[test]
+    fn unknown_palettes_fall_back() {
+        let ctx = egui::Context::default();
+        let themes = Themes::bundled(ThemeSettings {
+            choice: ThemeChoice::Named("Removed".to_owned()),
+            accent: None,
+        });
+        assert_eq!(themes.palette(&ctx).name, themes.file.system_light);
+    }
+}
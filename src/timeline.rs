@@ -0,0 +1,188 @@
+use eframe::egui;
+
+use crate::a11y;
+use crate::content::{Date, Milestone};
+use crate::{i18n, icons};
+
+
+/// A horizontal timeline of dated milestones. Drag to pan, pinch or
+/// Ctrl+scroll to zoom; hovering or tabbing to a marker shows what happened
+/// that day.
+pub struct Timeline<'a> {
+    milestones: &'a [Milestone],
+    today: Date,
+    height: f32,
+}
+
+/// The visible window, in days since the Unix epoch.
+#[derive(Clone, Copy)]
+struct View {
+    start: f64,
+    span: f64,
+}
+
+impl<'a> Timeline<'a> {
+    pub fn new(milestones: &'a [Milestone], today: Date) -> Self {
+        Self {
+            milestones,
+            today,
+            height: 180.0,
+        }
+    }
+
+    pub fn show(self, ui: &mut egui::Ui) -> egui::Response {
+        let id = ui.id().with("timeline");
+        let fitted = self.fitted_view();
+        let mut view = ui.data(|d| d.get_temp::<View>(id)).unwrap_or(fitted);
+
+        let fit_label = i18n::tr_with("timeline-fit", &[("count", self.milestones.len().into())]);
+        ui.horizontal(|ui| {
+            if a11y::label(ui.button(icons::MAGNIFYING_GLASS_MINUS), &i18n::tr("timeline-zoom-out")).clicked() {
+                view.zoom(1.5, view.start + view.span / 2.0);
+            }
+            if a11y::label(ui.button(icons::MAGNIFYING_GLASS_PLUS), &i18n::tr("timeline-zoom-in")).clicked() {
+                view.zoom(1.0 / 1.5, view.start + view.span / 2.0);
+            }
+            if a11y::label(ui.button(icons::ARROWS_OUT), &fit_label).clicked() {
+                view = fitted;
+            }
+            if a11y::label(ui.button(icons::CROSSHAIR), &i18n::tr("timeline-centre")).clicked() {
+                view.start = self.today.days() as f64 - view.span / 2.0;
+            }
+        });
+
+        let size = egui::vec2(ui.available_width(), self.height);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+        let per_day = rect.width() as f64 / view.span;
+
+        if response.dragged() {
+            view.start -= response.drag_delta().x as f64 / per_day;
+        }
+        if let Some(pointer) = response.hover_pos() {
+            let zoom = ui.input(|i| i.zoom_delta());
+            if zoom != 1.0 {
+                let anchor = view.start + (pointer.x - rect.left()) as f64 / per_day;
+                view.zoom(1.0 / zoom as f64, anchor);
+            }
+        }
+        ui.data_mut(|d| d.insert_temp(id, view));
+
+        let per_day = rect.width() as f64 / view.span;
+        let x_of = |days: f64| rect.left() + ((days - view.start) * per_day) as f32;
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        let axis_y = rect.center().y;
+        let small = egui::TextStyle::Small.resolve(ui.style());
+
+        painter.hline(rect.x_range(), axis_y, visuals.widgets.noninteractive.fg_stroke);
+
+        // Month ticks, thinned out to January only once months get cramped.
+        let month_px = 30.0 * per_day;
+        let mut month = Date::from_days(view.start.floor() as i64).first_of_month();
+        while (month.days() as f64) < view.start + view.span {
+            let x = x_of(month.days() as f64);
+            if month_px >= 60.0 || month.month == 1 {
+                painter.vline(x, (axis_y - 6.0)..=(axis_y + 6.0), visuals.widgets.noninteractive.fg_stroke);
+                let label = if month_px >= 60.0 {
+                    format!("{} {}", month.month_name(), month.year)
+                } else {
+                    month.year.to_string()
+                };
+                painter.text(
+                    egui::pos2(x + 4.0, rect.bottom() - 4.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    label,
+                    small.clone(),
+                    visuals.weak_text_color(),
+                );
+            }
+            month = month.next_month();
+        }
+
+        let today_x = x_of(self.today.days() as f64 + 0.5);
+        if rect.x_range().contains(today_x) {
+            let color = visuals.selection.bg_fill;
+            painter.vline(today_x, rect.y_range(), egui::Stroke::new(2.0, color));
+            painter.text(
+                egui::pos2(today_x + 4.0, rect.top() + 2.0),
+                egui::Align2::LEFT_TOP,
+                i18n::tr("timeline-today"),
+                small.clone(),
+                color,
+            );
+        }
+
+        // Alternate labels above and below the axis so neighbours don't collide.
+        let heading = egui::TextStyle::Heading.resolve(ui.style());
+        for (i, milestone) in self.milestones.iter().enumerate() {
+            let x = x_of(milestone.date.days() as f64 + 0.5);
+            if !rect.x_range().contains(x) {
+                continue;
+            }
+            let center = egui::pos2(x, axis_y);
+            let (anchor, label_pos) = if i % 2 == 0 {
+                (egui::Align2::CENTER_BOTTOM, egui::pos2(x, axis_y - 14.0))
+            } else {
+                (egui::Align2::CENTER_TOP, egui::pos2(x, axis_y + 14.0))
+            };
+            let label_rect = painter.text(
+                label_pos,
+                anchor,
+                milestone.date.short(),
+                heading.clone(),
+                visuals.strong_text_color(),
+            );
+            let marker_rect = egui::Rect::from_center_size(center, egui::Vec2::splat(16.0));
+            // Focusable so the details can be reached with Tab as well as the mouse.
+            let marker = ui.interact(
+                label_rect.union(marker_rect),
+                id.with(i),
+                egui::Sense::focusable_noninteractive(),
+            );
+            marker.widget_info(|| {
+                egui::WidgetInfo::labeled(
+                    egui::WidgetType::Label,
+                    true,
+                    format!("{}: {}", milestone.date, milestone.title),
+                )
+            });
+            let details = |ui: &mut egui::Ui| {
+                ui.strong(milestone.date.to_string());
+                ui.label(&milestone.title);
+            };
+            if marker.has_focus() {
+                egui::show_tooltip_for(ui.ctx(), ui.layer_id(), marker.id, &marker.rect, details);
+            }
+            let marker = marker.on_hover_ui(details);
+            let radius = if marker.hovered() || marker.has_focus() { 8.0 } else { 6.0 };
+            painter.circle_filled(center, radius, visuals.strong_text_color());
+        }
+
+        response
+    }
+
+    /// Every milestone plus a little margin either side.
+    fn fitted_view(&self) -> View {
+        let first = self.milestones.first().map_or(self.today, |m| m.date).days() as f64;
+        let last = self.milestones.last().map_or(self.today, |m| m.date).days() as f64 + 1.0;
+        let margin = ((last - first) * 0.1).max(7.0);
+        View {
+            start: first - margin,
+            span: last - first + 2.0 * margin,
+        }
+    }
+}
+
+impl View {
+    /// Scales the span by `factor` while keeping `anchor` at the same place
+    /// on screen.
+    fn zoom(&mut self, factor: f64, anchor: f64) {
+        let span = (self.span * factor).clamp(MIN_SPAN_DAYS, MAX_SPAN_DAYS);
+        self.start = anchor - (anchor - self.start) * span / self.span;
+        self.span = span;
+    }
+}
+
+
+const MIN_SPAN_DAYS: f64 = 14.0;
+const MAX_SPAN_DAYS: f64 = 365.0 * 20.0;
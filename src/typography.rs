@@ -0,0 +1,87 @@
+use eframe::egui::{self, FontFamily, FontId, TextStyle};
+
+use crate::fonts;
+
+
+/// Page titles.
+pub fn display() -> TextStyle {
+    TextStyle::Name("Display".into())
+}
+
+/// Taglines and other large lead-ins.
+pub fn title() -> TextStyle {
+    TextStyle::Name("Title".into())
+}
+
+/// Section headings.
+pub fn heading() -> TextStyle {
+    TextStyle::Heading
+}
+
+/// Running text.
+pub fn body() -> TextStyle {
+    TextStyle::Body
+}
+
+/// Controls and short supporting copy; the size buttons use.
+pub fn label() -> TextStyle {
+    TextStyle::Name("Label".into())
+}
+
+/// Fine print.
+pub fn caption() -> TextStyle {
+    TextStyle::Small
+}
+
+/// Sets every text style from the type scale, multiplied by `scale`.
+pub fn install(ctx: &egui::Context, scale: f32) {
+    let proportional = |size: f32| FontId::new(size * scale, FontFamily::Proportional);
+    let brand = |size: f32| FontId::new(size * scale, fonts::brand());
+    ctx.all_styles_mut(|style| {
+        style.text_styles = [
+            (display(), brand(DISPLAY)),
+            (title(), brand(TITLE)),
+            (heading(), brand(HEADING)),
+            (body(), proportional(BODY)),
+            (label(), proportional(LABEL)),
+            (TextStyle::Button, proportional(LABEL)),
+            (caption(), proportional(CAPTION)),
+            (TextStyle::Monospace, FontId::new(BODY * scale, FontFamily::Monospace)),
+        ].into();
+    });
+}
+
+/// Ctrl/Cmd with `+`, `-` and `0` step through `SCALES` instead of egui's
+/// own zoom, so text grows without the layout spacing growing with it.
+pub fn handle_shortcuts(ctx: &egui::Context, scale: &mut f32) -> bool {
+    let (bigger, smaller, reset) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::COMMAND, egui::Key::Plus)
+                || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Equals),
+            i.consume_key(egui::Modifiers::COMMAND, egui::Key::Minus),
+            i.consume_key(egui::Modifiers::COMMAND, egui::Key::Num0),
+        )
+    });
+    let current = SCALES.iter().position(|&s| s >= *scale).unwrap_or(SCALES.len() - 1);
+    let next = if bigger {
+        SCALES[(current + 1).min(SCALES.len() - 1)]
+    } else if smaller {
+        SCALES[current.saturating_sub(1)]
+    } else if reset {
+        1.0
+    } else {
+        return false;
+    };
+    let changed = next != *scale;
+    *scale = next;
+    changed
+}
+
+
+pub const SCALES: [f32; 6] = [0.85, 1.0, 1.15, 1.3, 1.5, 1.75];
+const DISPLAY: f32 = 59.0;
+const TITLE: f32 = 43.0;
+const HEADING: f32 = 37.0;
+const BODY: f32 = 29.0;
+const LABEL: f32 = 23.0;
+const CAPTION: f32 = 19.0;